@@ -0,0 +1,56 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transfer Post Codec Benchmarks
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pallet_manta_pay::types::TransferPost;
+use scale_codec::{Decode, Encode};
+
+#[allow(dead_code)]
+#[path = "../src/precomputed_coins.rs"]
+mod precomputed_coins;
+
+/// Decodes the precomputed two-in-two-out private transfer.
+#[inline]
+fn private_transfer() -> TransferPost {
+    TransferPost::decode(&mut precomputed_coins::PRIVATE_TRANSFER)
+        .expect("Unable to decode the precomputed private transfer.")
+}
+
+fn encode(c: &mut Criterion) {
+    let post = private_transfer();
+    c.bench_function("encode two-in-two-out post", |b| {
+        b.iter(|| {
+            let _ = black_box(black_box(&post).encode());
+        })
+    });
+}
+
+fn decode(c: &mut Criterion) {
+    let bytes = private_transfer().encode();
+    c.bench_function("decode two-in-two-out post", |b| {
+        b.iter(|| {
+            let _ = black_box(
+                TransferPost::decode(&mut black_box(bytes.as_slice()))
+                    .expect("Unable to decode the post."),
+            );
+        })
+    });
+}
+
+criterion_group!(codec, encode, decode);
+criterion_main!(codec);
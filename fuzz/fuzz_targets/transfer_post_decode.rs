@@ -0,0 +1,36 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transfer Post Decoding Fuzz Target
+//!
+//! Feeds arbitrary bytes to the [`TransferPost`] decoder, which must return an error instead of
+//! panicking, and checks that every post it accepts round-trips through its encoding.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pallet_manta_pay::prelude::TransferPost;
+use scale_codec::{Decode, Encode};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(post) = TransferPost::decode(&mut &*data) {
+        assert_eq!(
+            TransferPost::decode(&mut post.encode().as_slice()).ok(),
+            Some(post),
+            "Decoded posts should round-trip through their encoding."
+        );
+    }
+});
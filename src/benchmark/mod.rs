@@ -15,18 +15,17 @@
 // along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-    benchmark::precomputed_coins::{
-        MINT, PRIVATE_TRANSFER, PRIVATE_TRANSFER_INPUT, RECLAIM, RECLAIM_INPUT,
-    },
-    Asset, Balances, Call, Config, Event, Pallet, TransferPost,
+    precomputed_coins::{MINT, PRIVATE_TRANSFER, PRIVATE_TRANSFER_INPUT, RECLAIM, RECLAIM_INPUT},
+    Asset, Balances, BatchMode, Call, Config, Event, Pallet, TransferPost,
 };
 use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::Get;
 use frame_system::RawOrigin;
+use manta_crypto::merkle_tree::forest::Configuration as _;
+use manta_pay::config::MerkleTreeConfiguration;
 use scale_codec::Decode;
 use sp_runtime::traits::StaticLookup;
 
-mod precomputed_coins;
-
 /// Asserts that the last event that has occured is the same as `event`.
 #[inline]
 pub fn assert_last_event<T, E>(event: E)
@@ -42,10 +41,10 @@ benchmarks! {
     transfer {
         let caller: T::AccountId = whitelisted_caller();
         let origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
-        Pallet::<T>::init_asset(&caller, 0, 1_000);
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000);
         let recipient: T::AccountId = account("recipient", 0, 0);
         let recipient_lookup = T::Lookup::unlookup(recipient.clone());
-        let asset = Asset::new(0, 10);
+        let asset = Asset::new(0, 10).convert_id::<T::AssetId>();
     }: transfer (
         RawOrigin::Signed(caller.clone()),
         recipient_lookup,
@@ -58,21 +57,38 @@ benchmarks! {
     mint {
         let caller: T::AccountId = whitelisted_caller();
         let origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
-        Pallet::<T>::init_asset(&caller, 0, 1_000_000);
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000_000);
+        let mint_post = TransferPost::decode(&mut &*MINT).unwrap();
+        let asset = Asset::new(mint_post.asset_id.unwrap(), mint_post.sources[0]);
+    }: mint (
+        RawOrigin::Signed(caller.clone()),
+        mint_post
+    ) verify {
+        assert_last_event::<T, _>(Event::Mint { asset: asset.convert_id(), source: caller.clone() });
+        assert_eq!(Balances::<T>::get(caller, T::AssetId::from(asset.id)), 1_000_000 - asset.value);
+    }
+
+    mint_into_filled_shard {
+        let n in 0 .. 1_024;
+        let caller: T::AccountId = whitelisted_caller();
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000_000);
         let mint_post = TransferPost::decode(&mut &*MINT).unwrap();
         let asset = Asset::new(mint_post.asset_id.unwrap(), mint_post.sources[0]);
+        Pallet::<T>::prefill_shard(
+            MerkleTreeConfiguration::tree_index(&mint_post.receiver_posts[0].utxo),
+            n,
+        );
     }: mint (
         RawOrigin::Signed(caller.clone()),
         mint_post
     ) verify {
-        assert_last_event::<T, _>(Event::Mint { asset, source: caller.clone() });
-        assert_eq!(Balances::<T>::get(caller, asset.id), 1_000_000 - asset.value);
+        assert_last_event::<T, _>(Event::Mint { asset: asset.convert_id(), source: caller.clone() });
     }
 
     private_transfer {
         let caller: T::AccountId = whitelisted_caller();
         let origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
-        Pallet::<T>::init_asset(&caller, 0, 1_000_000);
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000_000);
         for coin in PRIVATE_TRANSFER_INPUT {
             Pallet::<T>::mint(origin.clone(), TransferPost::decode(&mut &**coin).unwrap()).unwrap();
         }
@@ -87,16 +103,189 @@ benchmarks! {
     reclaim {
         let caller: T::AccountId = whitelisted_caller();
         let origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
-        Pallet::<T>::init_asset(&caller, 0, 1_000_000);
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000_000);
+        for coin in RECLAIM_INPUT {
+            Pallet::<T>::mint(origin.clone(), TransferPost::decode(&mut &**coin).unwrap()).unwrap();
+        }
+        let reclaim_post = TransferPost::decode(&mut &*RECLAIM).unwrap();
+    }: reclaim (
+        RawOrigin::Signed(caller.clone()),
+        reclaim_post
+    ) verify {
+        assert_last_event::<T, _>(Event::Reclaim { asset: Asset::new(0, 10_000).convert_id(), sink: caller });
+    }
+
+    private_transfer_with_void_numbers {
+        let n in 0 .. 100_000;
+        let caller: T::AccountId = whitelisted_caller();
+        let origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000_000);
+        for coin in PRIVATE_TRANSFER_INPUT {
+            Pallet::<T>::mint(origin.clone(), TransferPost::decode(&mut &**coin).unwrap()).unwrap();
+        }
+        Pallet::<T>::prefill_void_numbers(n);
+        let private_transfer_post = TransferPost::decode(&mut &*PRIVATE_TRANSFER).unwrap();
+    }: private_transfer (
+        RawOrigin::Signed(caller.clone()),
+        private_transfer_post
+    ) verify {
+        assert_last_event::<T, _>(Event::PrivateTransfer { origin: caller });
+    }
+
+    reclaim_with_void_numbers {
+        let n in 0 .. 100_000;
+        let caller: T::AccountId = whitelisted_caller();
+        let origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000_000);
         for coin in RECLAIM_INPUT {
             Pallet::<T>::mint(origin.clone(), TransferPost::decode(&mut &**coin).unwrap()).unwrap();
         }
+        Pallet::<T>::prefill_void_numbers(n);
         let reclaim_post = TransferPost::decode(&mut &*RECLAIM).unwrap();
     }: reclaim (
         RawOrigin::Signed(caller.clone()),
         reclaim_post
     ) verify {
-        assert_last_event::<T, _>(Event::Reclaim { asset: Asset::new(0, 10_000), sink: caller });
+        assert_last_event::<T, _>(Event::Reclaim { asset: Asset::new(0, 10_000).convert_id(), sink: caller });
+    }
+
+    batch_transfer {
+        let n in 1 .. T::MaxPublicBatch::get();
+        let caller: T::AccountId = whitelisted_caller();
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000_000);
+        let transfers = (0..n)
+            .map(|i| {
+                let asset = Asset::new(0, 10).convert_id::<T::AssetId>();
+                (T::Lookup::unlookup(account("recipient", i, 0)), asset)
+            })
+            .collect::<Vec<_>>();
+    }: batch_transfer (
+        RawOrigin::Signed(caller.clone()),
+        transfers,
+        BatchMode::Atomic,
+        Some([0; 32])
+    ) verify {
+        assert_eq!(Balances::<T>::get(caller, T::AssetId::from(0)), 1_000_000 - 10 * n as u128);
+    }
+
+    batch_mint {
+        let n in 1 .. PRIVATE_TRANSFER_INPUT.len() as u32;
+        let caller: T::AccountId = whitelisted_caller();
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000_000);
+        let posts = PRIVATE_TRANSFER_INPUT[..n as usize]
+            .iter()
+            .map(|coin| TransferPost::decode(&mut &**coin).unwrap())
+            .collect::<Vec<_>>();
+    }: batch_mint (
+        RawOrigin::Signed(caller.clone()),
+        posts,
+        BatchMode::Atomic,
+        Some([0; 32])
+    ) verify {
+        assert_eq!(crate::TotalUtxoCount::<T>::get(), n as u64);
+    }
+
+    set_utxo_accumulator_model {
+        let model = manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get()
+            .unwrap()
+            .to_vec();
+    }: set_utxo_accumulator_model (
+        RawOrigin::Root,
+        model
+    ) verify {
+        assert_last_event::<T, _>(Event::ParametersUpdated {
+            fingerprint: Pallet::<T>::parameters_fingerprint(),
+        });
+    }
+
+    initialize_asset {
+        let owner: T::AccountId = account("owner", 0, 0);
+        let owner_lookup = T::Lookup::unlookup(owner.clone());
+    }: initialize_asset (
+        RawOrigin::Root,
+        owner_lookup,
+        T::AssetId::from(0),
+        1_000_000
+    ) verify {
+        assert_last_event::<T, _>(Event::AssetInitialized { id: T::AssetId::from(0), owner: owner.clone(), total: 1_000_000 });
+        assert_eq!(Balances::<T>::get(owner, T::AssetId::from(0)), 1_000_000);
+    }
+
+    destroy_asset {
+        let owner: T::AccountId = account("owner", 0, 0);
+        let owner_lookup = T::Lookup::unlookup(owner.clone());
+        Pallet::<T>::init_asset(&owner, T::AssetId::from(0), 1_000_000);
+    }: destroy_asset (
+        RawOrigin::Root,
+        owner_lookup,
+        T::AssetId::from(0)
+    ) verify {
+        assert_last_event::<T, _>(Event::AssetDestroyed { id: T::AssetId::from(0), owner: owner.clone() });
+        assert_eq!(Balances::<T>::get(owner, T::AssetId::from(0)), 0);
+    }
+
+    quick_invariant_check {
+        let caller: T::AccountId = whitelisted_caller();
+        let origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
+        Pallet::<T>::init_asset(&caller, T::AssetId::from(0), 1_000_000);
+        Pallet::<T>::mint(origin, TransferPost::decode(&mut &*MINT).unwrap()).unwrap();
+        Pallet::<T>::prefill_void_numbers(1_000);
+        let mut result = Ok(());
+    }: {
+        result = Pallet::<T>::quick_invariant_check(T::AssetId::from(0));
+    } verify {
+        assert_eq!(result, Ok(()));
+    }
+
+    import_ledger {
+        let u in 1 .. 1_024;
+        let v in 0 .. 10_000;
+        Pallet::<T>::prefill_utxos(u);
+        Pallet::<T>::prefill_void_numbers(v);
+        let snapshot = Pallet::<T>::export_ledger();
+        Pallet::<T>::clear_ledger();
+    }: import_ledger (
+        RawOrigin::Root,
+        snapshot,
+        false
+    ) verify {
+        assert_last_event::<T, _>(Event::LedgerImported);
+        assert_eq!(crate::TotalUtxoCount::<T>::get(), u as u64);
+    }
+
+    allow_asset_id_reuse {
+        crate::RetiredAssetIds::<T>::insert(T::AssetId::from(0), ());
+    }: allow_asset_id_reuse (
+        RawOrigin::Root,
+        T::AssetId::from(0)
+    ) verify {
+        assert!(!crate::RetiredAssetIds::<T>::contains_key(T::AssetId::from(0)));
+    }
+
+    force_set_balance {
+        let owner: T::AccountId = account("owner", 0, 0);
+        Pallet::<T>::init_asset(&owner, T::AssetId::from(0), 1_000_000);
+        let who: T::AccountId = account("who", 0, 0);
+        let who_lookup = T::Lookup::unlookup(who.clone());
+    }: force_set_balance (
+        RawOrigin::Root,
+        who_lookup,
+        T::AssetId::from(0),
+        1_000
+    ) verify {
+        assert_last_event::<T, _>(Event::BalanceSet { id: T::AssetId::from(0), who: who.clone(), value: 1_000 });
+        assert_eq!(Balances::<T>::get(who, T::AssetId::from(0)), 1_000);
+    }
+
+    verify_snapshot {
+        let n in 1 .. 1_024;
+        Pallet::<T>::prefill_utxos(n);
+        let snapshot = Pallet::<T>::export_ledger();
+        let mut result = Ok(());
+    }: {
+        result = Pallet::<T>::ensure_consistent_snapshot(&snapshot);
+    } verify {
+        assert!(result.is_ok());
     }
 }
 
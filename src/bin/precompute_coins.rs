@@ -16,7 +16,7 @@
 
 //! Precomputed Transactions
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use indoc::indoc;
 use manta_accounting::{
     asset::{Asset, AssetId},
@@ -29,93 +29,24 @@ use manta_crypto::{
     rand::{CryptoRng, Rand, RngCore, Sample},
 };
 use manta_pay::config::{
-    self, FullParameters, KeyAgreementScheme, MerkleTreeConfiguration, Mint, MultiProvingContext,
+    self, FullParameters, MerkleTreeConfiguration, Mint, MultiProvingContext,
     MultiVerifyingContext, Parameters, PrivateTransfer, ProofSystem, ProvingContext, Reclaim,
-    UtxoAccumulatorModel, UtxoCommitmentScheme, VerifyingContext, VoidNumberHashFunction,
+    UtxoAccumulatorModel, VerifyingContext,
 };
-use manta_util::codec::{Decode, IoReader};
-use pallet_manta_pay::types::TransferPost;
+use pallet_manta_pay::{sdk, types::TransferPost};
 use rand::thread_rng;
 use scale_codec::Encode;
 use std::{
     env,
-    fs::{self, File, OpenOptions},
+    fs::{self, OpenOptions},
     io::Write,
-    path::{Path, PathBuf},
+    path::PathBuf,
 };
 
 /// UTXO Accumulator for Building Circuits
 type UtxoAccumulator =
     TreeArrayMerkleForest<MerkleTreeConfiguration, Full<MerkleTreeConfiguration>, 256>;
 
-/// Loads parameters from the SDK, using `directory` as a temporary directory to store files.
-#[inline]
-fn load_parameters(
-    directory: &Path,
-) -> Result<(
-    MultiProvingContext,
-    MultiVerifyingContext,
-    Parameters,
-    UtxoAccumulatorModel,
-)> {
-    let mint_path = directory.join("mint.dat");
-    manta_sdk::pay::testnet::proving::Mint::download(&mint_path)?;
-    let private_transfer_path = directory.join("private-transfer.dat");
-    manta_sdk::pay::testnet::proving::PrivateTransfer::download(&private_transfer_path)?;
-    let reclaim_path = directory.join("reclaim.dat");
-    manta_sdk::pay::testnet::proving::Reclaim::download(&reclaim_path)?;
-    let proving_context = MultiProvingContext {
-        mint: ProvingContext::decode(IoReader(File::open(mint_path)?))
-            .expect("Unable to decode MINT proving context."),
-        private_transfer: ProvingContext::decode(IoReader(File::open(private_transfer_path)?))
-            .expect("Unable to decode PRIVATE_TRANSFER proving context."),
-        reclaim: ProvingContext::decode(IoReader(File::open(reclaim_path)?))
-            .expect("Unable to decode RECLAIM proving context."),
-    };
-    let verifying_context = MultiVerifyingContext {
-        mint: VerifyingContext::decode(
-            manta_sdk::pay::testnet::verifying::Mint::get().expect("Checksum did not match."),
-        )
-        .expect("Unable to decode MINT verifying context."),
-        private_transfer: VerifyingContext::decode(
-            manta_sdk::pay::testnet::verifying::PrivateTransfer::get()
-                .expect("Checksum did not match."),
-        )
-        .expect("Unable to decode PRIVATE_TRANSFER verifying context."),
-        reclaim: VerifyingContext::decode(
-            manta_sdk::pay::testnet::verifying::Reclaim::get().expect("Checksum did not match."),
-        )
-        .expect("Unable to decode RECLAIM verifying context."),
-    };
-    let parameters = Parameters {
-        key_agreement: KeyAgreementScheme::decode(
-            manta_sdk::pay::testnet::parameters::KeyAgreement::get()
-                .expect("Checksum did not match."),
-        )
-        .expect("Unable to decode KEY_AGREEMENT parameters."),
-        utxo_commitment: UtxoCommitmentScheme::decode(
-            manta_sdk::pay::testnet::parameters::UtxoCommitmentScheme::get()
-                .expect("Checksum did not match."),
-        )
-        .expect("Unable to decode UTXO_COMMITMENT_SCHEME parameters."),
-        void_number_hash: VoidNumberHashFunction::decode(
-            manta_sdk::pay::testnet::parameters::VoidNumberHashFunction::get()
-                .expect("Checksum did not match."),
-        )
-        .expect("Unable to decode VOID_NUMBER_HASH_FUNCTION parameters."),
-    };
-    Ok((
-        proving_context,
-        verifying_context,
-        parameters,
-        UtxoAccumulatorModel::decode(
-            manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get()
-                .expect("Checksum did not match."),
-        )
-        .expect("Unable to decode UTXO_ACCUMULATOR_MODEL."),
-    ))
-}
-
 /// Asserts that `post` represents a valid `Transfer` verifying against `verifying_context`.
 #[inline]
 fn assert_valid_proof(verifying_context: &VerifyingContext, post: &config::TransferPost) {
@@ -322,8 +253,11 @@ fn main() -> Result<()> {
     println!("[INFO] Temporary Directory: {:?}", directory);
 
     let mut rng = thread_rng();
-    let (proving_context, verifying_context, parameters, utxo_accumulator_model) =
-        load_parameters(directory.path())?;
+    let proving_context = sdk::download_proving_context(directory.path())
+        .context("Unable to download the proving contexts.")?;
+    let verifying_context = sdk::load_verifying_context();
+    let parameters = sdk::load_parameters();
+    let utxo_accumulator_model = sdk::load_utxo_accumulator_model();
 
     let mint = sample_mint(
         &proving_context.mint,
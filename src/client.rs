@@ -0,0 +1,290 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Client Utilities
+
+use crate::types::{AssetId, AssetValue, EncryptedNote, NoteCursor, TransferPost};
+use manta_pay::config;
+use scale_codec::{Compact, Decode, DecodeAll, Encode, Error, Input};
+use sp_runtime::traits::Hash;
+use sp_state_machine::{read_proof_check, StorageProof};
+
+/// Pull Response Reader
+///
+/// Decodes an encoded [`NotePage`](crate::types::NotePage) from `input` one note at a time,
+/// without materializing the whole page in memory.
+pub struct PullResponseReader<I>
+where
+    I: Input,
+{
+    /// Encoded Input
+    input: I,
+
+    /// Cursor for the Next Page
+    next: Option<NoteCursor>,
+
+    /// Number of Chunks Left to Read
+    remaining_chunks: u32,
+
+    /// Position of the Next Note in the Current Chunk
+    cursor: NoteCursor,
+
+    /// Number of Notes Left to Read in the Current Chunk
+    remaining_notes: u32,
+
+    /// Decoding Failure Flag
+    failed: bool,
+}
+
+impl<I> PullResponseReader<I>
+where
+    I: Input,
+{
+    /// Builds a new [`PullResponseReader`] by decoding the page header from `input`.
+    #[inline]
+    pub fn new(mut input: I) -> Result<Self, Error> {
+        let next = Option::<NoteCursor>::decode(&mut input)?;
+        let remaining_chunks = Compact::<u32>::decode(&mut input)?.0;
+        Ok(Self {
+            input,
+            next,
+            remaining_chunks,
+            cursor: Default::default(),
+            remaining_notes: 0,
+            failed: false,
+        })
+    }
+
+    /// Returns the cursor for the next page, which is available before any notes are read.
+    #[inline]
+    pub fn next_cursor(&self) -> Option<NoteCursor> {
+        self.next
+    }
+
+    /// Decodes the next chunk header from the input.
+    #[inline]
+    fn read_chunk_header(&mut self) -> Result<(), Error> {
+        let shard_index = u8::decode(&mut self.input)?;
+        let start_index = u64::decode(&mut self.input)?;
+        self.remaining_notes = Compact::<u32>::decode(&mut self.input)?.0;
+        self.cursor = NoteCursor::new(shard_index, start_index);
+        self.remaining_chunks -= 1;
+        Ok(())
+    }
+
+    /// Decodes the next note in the current chunk from the input.
+    #[inline]
+    fn read_note(&mut self) -> Result<(NoteCursor, (config::Utxo, EncryptedNote)), Error> {
+        let entry = <(config::Utxo, EncryptedNote)>::decode(&mut self.input)?;
+        let cursor = self.cursor;
+        self.cursor.leaf_index += 1;
+        self.remaining_notes -= 1;
+        Ok((cursor, entry))
+    }
+}
+
+impl<I> Iterator for PullResponseReader<I>
+where
+    I: Input,
+{
+    type Item = Result<(NoteCursor, (config::Utxo, EncryptedNote)), Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        while self.remaining_notes == 0 {
+            if self.remaining_chunks == 0 {
+                return None;
+            }
+            if let Err(err) = self.read_chunk_header() {
+                self.failed = true;
+                return Some(Err(err));
+            }
+        }
+        let item = self.read_note();
+        self.failed = item.is_err();
+        Some(item)
+    }
+}
+
+/// Balance Proof Error
+#[derive(Debug)]
+pub enum BalanceProofError {
+    /// The proof does not match the state root or does not contain the requested key.
+    InvalidProof,
+
+    /// The proven value is not a valid balance.
+    Decode(Error),
+}
+
+/// Verifies that `proof` proves the value stored under `key` against `state_root`, returning the
+/// proven public balance.
+///
+/// # Note
+///
+/// The `key` should come from [`Pallet::balance_storage_key`](crate::Pallet::balance_storage_key).
+/// Since public balances are stored with zeroed entries removed, a proof of absence is a proof of
+/// a zero balance.
+#[inline]
+pub fn verify_balance_proof<H>(
+    state_root: H::Output,
+    proof: StorageProof,
+    key: &[u8],
+) -> Result<AssetValue, BalanceProofError>
+where
+    H: Hash,
+{
+    match read_proof_check::<H, _>(state_root, proof, [key])
+        .map_err(|_| BalanceProofError::InvalidProof)?
+        .remove(key)
+    {
+        Some(Some(value)) => {
+            AssetValue::decode(&mut value.as_slice()).map_err(BalanceProofError::Decode)
+        }
+        Some(None) => Ok(0),
+        None => Err(BalanceProofError::InvalidProof),
+    }
+}
+
+/// Shareable Post Error
+#[derive(Debug)]
+pub enum ShareablePostError {
+    /// The string is not valid base64.
+    Base64(base64::DecodeError),
+
+    /// The decoded bytes are not exactly one encoded [`TransferPost`].
+    Decode(Error),
+}
+
+impl TransferPost {
+    /// Encodes `self` as base64 over its SCALE encoding, so that it can be shared as text.
+    #[inline]
+    pub fn to_base64(&self) -> String {
+        base64::encode(self.encode())
+    }
+
+    /// Decodes a post encoded with [`to_base64`](Self::to_base64), rejecting trailing bytes.
+    #[inline]
+    pub fn from_base64(encoded: &str) -> Result<Self, ShareablePostError> {
+        let bytes = base64::decode(encoded.trim()).map_err(ShareablePostError::Base64)?;
+        Self::decode_all(&bytes).map_err(ShareablePostError::Decode)
+    }
+}
+
+/// Transfer Post Builder Error
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransferPostBuilderError {
+    /// No validity proof was set.
+    MissingProof,
+
+    /// The asset id and participant counts do not match any transfer shape.
+    InvalidShape,
+}
+
+/// Transfer Post Builder
+///
+/// Assembles a pallet [`TransferPost`] from SDK components, converting them with the same
+/// conversions used for [`config::TransferPost`].
+#[derive(Clone, Debug, Default)]
+pub struct TransferPostBuilder {
+    /// Asset Id
+    asset_id: Option<AssetId>,
+
+    /// Sources
+    sources: Vec<AssetValue>,
+
+    /// Sender Posts
+    sender_posts: Vec<config::SenderPost>,
+
+    /// Receiver Posts
+    receiver_posts: Vec<config::ReceiverPost>,
+
+    /// Sinks
+    sinks: Vec<AssetValue>,
+
+    /// Validity Proof
+    validity_proof: Option<config::Proof>,
+}
+
+impl TransferPostBuilder {
+    /// Builds a new empty [`TransferPostBuilder`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the public asset id of the post.
+    #[inline]
+    pub fn asset_id(mut self, asset_id: AssetId) -> Self {
+        self.asset_id = Some(asset_id);
+        self
+    }
+
+    /// Adds a public source of `value` to the post.
+    #[inline]
+    pub fn source(mut self, value: AssetValue) -> Self {
+        self.sources.push(value);
+        self
+    }
+
+    /// Adds `post` to the senders of the post.
+    #[inline]
+    pub fn add_sender(mut self, post: config::SenderPost) -> Self {
+        self.sender_posts.push(post);
+        self
+    }
+
+    /// Adds `post` to the receivers of the post.
+    #[inline]
+    pub fn add_receiver(mut self, post: config::ReceiverPost) -> Self {
+        self.receiver_posts.push(post);
+        self
+    }
+
+    /// Adds a public sink of `value` to the post.
+    #[inline]
+    pub fn sink(mut self, value: AssetValue) -> Self {
+        self.sinks.push(value);
+        self
+    }
+
+    /// Sets the validity proof of the post.
+    #[inline]
+    pub fn proof(mut self, proof: config::Proof) -> Self {
+        self.validity_proof = Some(proof);
+        self
+    }
+
+    /// Builds the [`TransferPost`], checking that a proof was set and that the post has one of
+    /// the transfer shapes.
+    #[inline]
+    pub fn build(self) -> Result<TransferPost, TransferPostBuilderError> {
+        let post = TransferPost {
+            asset_id: self.asset_id,
+            sources: self.sources,
+            sender_posts: self.sender_posts.into_iter().map(Into::into).collect(),
+            receiver_posts: self.receiver_posts.into_iter().map(Into::into).collect(),
+            sinks: self.sinks,
+            validity_proof: self
+                .validity_proof
+                .ok_or(TransferPostBuilderError::MissingProof)?,
+        };
+        post.shape().ok_or(TransferPostBuilderError::InvalidShape)?;
+        Ok(post)
+    }
+}
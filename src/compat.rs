@@ -0,0 +1,228 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Legacy Payload Compatibility
+//!
+//! Mirrors the payloads accepted by the pallet before the `manta-rs` rewrite and maps them onto
+//! the current [`TransferPost`] shapes for migration tooling.
+//!
+//! # Note
+//!
+//! Legacy mints were checked by opening their commitment and legacy transfers carried proofs for
+//! a different circuit, so no legacy payload converts into a [`TransferPost`] that verifies. The
+//! `convert_*` functions check every structural field and then fail with
+//! [`Unconvertible::IncompatibleProof`], while the `describe_*` functions return the shape and
+//! public parts that a re-proven post would have.
+
+use crate::types::{AssetId, AssetValue, PostDescription, PostShape, TransferPost};
+use alloc::vec;
+use core::convert::TryFrom;
+use scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+/// Legacy Asset Id Type
+pub type LegacyAssetId = u64;
+
+/// Legacy Asset Value Type
+pub type LegacyAssetValue = u64;
+
+/// Legacy Mint Payload
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, TypeInfo)]
+pub struct MintData {
+    /// Asset Id
+    pub asset_id: LegacyAssetId,
+
+    /// Minted Amount
+    pub amount: LegacyAssetValue,
+
+    /// Commitment
+    pub cm: [u8; 32],
+
+    /// Commitment Trapdoor `k`
+    pub k: [u8; 32],
+
+    /// Commitment Trapdoor `s`
+    pub s: [u8; 32],
+}
+
+/// Legacy Sender Payload
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, TypeInfo)]
+pub struct SenderData {
+    /// Commitment Trapdoor `k`
+    pub k: [u8; 32],
+
+    /// Void Number
+    pub void_number: [u8; 32],
+
+    /// Ledger Root
+    pub root: [u8; 32],
+}
+
+/// Legacy Receiver Payload
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, TypeInfo)]
+pub struct ReceiverData {
+    /// Commitment Trapdoor `k`
+    pub k: [u8; 32],
+
+    /// Commitment
+    pub cm: [u8; 32],
+
+    /// Encrypted Value
+    pub cipher: [u8; 16],
+
+    /// Sender Ephemeral Public Key
+    pub sender_pk: [u8; 32],
+}
+
+/// Legacy Private Transfer Payload
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, TypeInfo)]
+pub struct PrivateTransferData {
+    /// First Sender
+    pub sender_1: SenderData,
+
+    /// Second Sender
+    pub sender_2: SenderData,
+
+    /// First Receiver
+    pub receiver_1: ReceiverData,
+
+    /// Second Receiver
+    pub receiver_2: ReceiverData,
+
+    /// Legacy Validity Proof
+    pub proof: [u8; 192],
+}
+
+/// Legacy Reclaim Payload
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, TypeInfo)]
+pub struct ReclaimData {
+    /// Asset Id
+    pub asset_id: LegacyAssetId,
+
+    /// Reclaimed Amount
+    pub reclaim_amount: LegacyAssetValue,
+
+    /// First Sender
+    pub sender_1: SenderData,
+
+    /// Second Sender
+    pub sender_2: SenderData,
+
+    /// Change Receiver
+    pub receiver: ReceiverData,
+
+    /// Legacy Validity Proof
+    pub proof: [u8; 192],
+}
+
+/// Unconvertible Legacy Payload Error
+#[derive(Clone, Copy, Debug, Decode, Encode, Eq, PartialEq, TypeInfo)]
+pub enum Unconvertible {
+    /// Asset Id Out of Range
+    ///
+    /// The legacy asset id does not fit into an [`AssetId`].
+    AssetIdOutOfRange,
+
+    /// Incompatible Proof
+    ///
+    /// The legacy payload was authorized by a proof system that the current verifier does not
+    /// accept, so the post has to be proven again.
+    IncompatibleProof,
+}
+
+/// Converts a legacy asset id into an [`AssetId`].
+#[inline]
+fn convert_asset_id(id: LegacyAssetId) -> Result<AssetId, Unconvertible> {
+    AssetId::try_from(id).map_err(|_| Unconvertible::AssetIdOutOfRange)
+}
+
+/// Describes the mint post that would replace `data`.
+#[inline]
+pub fn describe_mint(data: &MintData) -> Result<PostDescription, Unconvertible> {
+    Ok(PostDescription {
+        shape: Some(PostShape::Mint),
+        asset_id: Some(convert_asset_id(data.asset_id)?),
+        sources: vec![AssetValue::from(data.amount)],
+        sinks: vec![],
+        sender_count: 0,
+        receiver_count: 1,
+    })
+}
+
+/// Describes the private transfer post that would replace a legacy private transfer.
+///
+/// # Note
+///
+/// Legacy private transfers have no public parts, so every one of them has the same description.
+#[inline]
+pub fn describe_private_transfer(_: &PrivateTransferData) -> PostDescription {
+    PostDescription {
+        shape: Some(PostShape::PrivateTransfer),
+        asset_id: None,
+        sources: vec![],
+        sinks: vec![],
+        sender_count: 2,
+        receiver_count: 2,
+    }
+}
+
+/// Describes the reclaim post that would replace `data`.
+#[inline]
+pub fn describe_reclaim(data: &ReclaimData) -> Result<PostDescription, Unconvertible> {
+    Ok(PostDescription {
+        shape: Some(PostShape::Reclaim),
+        asset_id: Some(convert_asset_id(data.asset_id)?),
+        sources: vec![],
+        sinks: vec![AssetValue::from(data.reclaim_amount)],
+        sender_count: 2,
+        receiver_count: 1,
+    })
+}
+
+/// Converts the legacy mint `data` into a [`TransferPost`].
+///
+/// # Note
+///
+/// Legacy mints carry no proof, but current mints must prove their receiver, so this always
+/// fails once the structural fields are checked. See the [module documentation](self).
+#[inline]
+pub fn convert_mint(data: &MintData) -> Result<TransferPost, Unconvertible> {
+    describe_mint(data)?;
+    Err(Unconvertible::IncompatibleProof)
+}
+
+/// Converts a legacy private transfer into a [`TransferPost`].
+///
+/// # Note
+///
+/// Legacy private transfers have no structural fields to check, so this always fails. See the
+/// [module documentation](self).
+#[inline]
+pub fn convert_private_transfer(_: &PrivateTransferData) -> Result<TransferPost, Unconvertible> {
+    Err(Unconvertible::IncompatibleProof)
+}
+
+/// Converts the legacy reclaim `data` into a [`TransferPost`].
+///
+/// # Note
+///
+/// This always fails once the structural fields are checked. See the
+/// [module documentation](self).
+#[inline]
+pub fn convert_reclaim(data: &ReclaimData) -> Result<TransferPost, Unconvertible> {
+    describe_reclaim(data)?;
+    Err(Unconvertible::IncompatibleProof)
+}
@@ -0,0 +1,198 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Mint, Private Transfer, and Reclaim Demo
+//!
+//! Walks a single asset through the full shielded cycle against the mock runtime, printing the
+//! public and private ledger state after every step.
+
+use crate::{
+    mock::{ExtBuilder, MantaPayPallet, Origin},
+    sdk,
+};
+use anyhow::{Context, Result};
+use manta_accounting::{
+    asset::{Asset, AssetId, AssetValue},
+    transfer::{self, Sender, SpendingKey},
+};
+use manta_crypto::{
+    accumulator::Accumulator,
+    merkle_tree::{forest::TreeArrayMerkleForest, full::Full},
+    rand::{CryptoRng, Rand, RngCore},
+};
+use manta_pay::config::{
+    Config, FullParameters, MerkleTreeConfiguration, MultiProvingContext, Parameters,
+    PrivateTransfer, Reclaim, TransferPost,
+};
+use rand::thread_rng;
+
+/// UTXO Accumulator for Building Circuits
+type UtxoAccumulator =
+    TreeArrayMerkleForest<MerkleTreeConfiguration, Full<MerkleTreeConfiguration>, 256>;
+
+/// Demo Account
+const ACCOUNT: u64 = 1;
+
+/// Demo Asset Id
+const ASSET_ID: u32 = 8;
+
+/// Total Supply of the Demo Asset
+const TOTAL_SUPPLY: AssetValue = AssetValue(1_000_000);
+
+/// Demo Context
+struct Demo {
+    /// Proving Contexts
+    proving_context: MultiProvingContext,
+
+    /// Protocol Parameters
+    parameters: Parameters,
+
+    /// UTXO Accumulator
+    ///
+    /// Mirrors the UTXOs registered on the ledger so that senders can prove membership.
+    utxo_accumulator: UtxoAccumulator,
+}
+
+impl Demo {
+    /// Builds a [`Mint`](manta_pay::config::Mint) of `asset` under `spending_key`, inserting its
+    /// UTXO into the local accumulator and returning the post and its upgraded sender.
+    #[inline]
+    fn mint<R>(
+        &mut self,
+        spending_key: &SpendingKey<Config>,
+        asset: Asset,
+        rng: &mut R,
+    ) -> (TransferPost, Sender<Config>)
+    where
+        R: CryptoRng + RngCore + ?Sized,
+    {
+        let (mint, pre_sender) = transfer::test::sample_mint(
+            &self.proving_context.mint,
+            FullParameters::new(&self.parameters, self.utxo_accumulator.model()),
+            spending_key,
+            asset,
+            rng,
+        )
+        .expect("Unable to build MINT proof.");
+        let sender = pre_sender
+            .insert_and_upgrade(&mut self.utxo_accumulator)
+            .expect("Just inserted so this should not fail.");
+        (mint, sender)
+    }
+
+    /// Inserts the receiver UTXOs of `post` into the local accumulator, in the same order as the
+    /// ledger registers them.
+    #[inline]
+    fn track_receivers(&mut self, post: &TransferPost) {
+        for receiver in &post.receiver_posts {
+            self.utxo_accumulator.insert(&receiver.utxo);
+        }
+    }
+}
+
+/// Prints the ledger state visible to `ACCOUNT` after the given `step`.
+#[inline]
+fn report(step: &str) {
+    println!(
+        "[{}] public balance: {}, total supply: {}, spent void numbers: {}",
+        step,
+        MantaPayPallet::balance(ACCOUNT, ASSET_ID),
+        MantaPayPallet::total_supply(ASSET_ID),
+        MantaPayPallet::void_number_count(),
+    );
+}
+
+/// Runs the demo, downloading the proving contexts into a temporary directory.
+#[inline]
+pub fn run() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    println!(
+        "[setup] loading parameters into {}",
+        directory.path().display()
+    );
+    let mut demo = Demo {
+        proving_context: sdk::download_proving_context(directory.path())
+            .context("Unable to download the proving contexts.")?,
+        parameters: sdk::load_parameters(),
+        utxo_accumulator: UtxoAccumulator::new(sdk::load_utxo_accumulator_model()),
+    };
+    let mut rng = thread_rng();
+    ExtBuilder::default()
+        .with_owner(ACCOUNT)
+        .with_assets(vec![(ASSET_ID, TOTAL_SUPPLY.0)])
+        .build()
+        .execute_with(|| {
+            report("genesis");
+
+            let spending_key_0 = SpendingKey::gen(&mut rng);
+            let spending_key_1 = SpendingKey::gen(&mut rng);
+            let asset_0 = AssetId(ASSET_ID).value(100);
+            let asset_1 = AssetId(ASSET_ID).value(250);
+            let (mint_0, sender_0) = demo.mint(&spending_key_0, asset_0, &mut rng);
+            let (mint_1, sender_1) = demo.mint(&spending_key_1, asset_1, &mut rng);
+            for mint in [mint_0, mint_1] {
+                MantaPayPallet::mint(Origin::signed(ACCOUNT), mint.into())
+                    .expect("Unable to mint.");
+            }
+            report("mint");
+
+            let private_transfer = PrivateTransfer::build(
+                [sender_0, sender_1],
+                [
+                    spending_key_0.receiver(&demo.parameters, rng.gen(), asset_1),
+                    spending_key_1.receiver(&demo.parameters, rng.gen(), asset_0),
+                ],
+            )
+            .into_post(
+                FullParameters::new(&demo.parameters, demo.utxo_accumulator.model()),
+                &demo.proving_context.private_transfer,
+                &mut rng,
+            )
+            .expect("Unable to build PRIVATE_TRANSFER proof.");
+            demo.track_receivers(&private_transfer);
+            MantaPayPallet::private_transfer(Origin::signed(ACCOUNT), private_transfer.into())
+                .expect("Unable to privately transfer.");
+            report("private transfer");
+
+            let asset_2 = AssetId(ASSET_ID).value(40);
+            let asset_3 = AssetId(ASSET_ID).value(60);
+            let (mint_2, sender_2) = demo.mint(&spending_key_0, asset_2, &mut rng);
+            let (mint_3, sender_3) = demo.mint(&spending_key_1, asset_3, &mut rng);
+            for mint in [mint_2, mint_3] {
+                MantaPayPallet::mint(Origin::signed(ACCOUNT), mint.into())
+                    .expect("Unable to mint.");
+            }
+            report("mint");
+
+            let reclaim = Reclaim::build(
+                [sender_2, sender_3],
+                [spending_key_0.receiver(&demo.parameters, rng.gen(), asset_3)],
+                asset_2,
+            )
+            .into_post(
+                FullParameters::new(&demo.parameters, demo.utxo_accumulator.model()),
+                &demo.proving_context.reclaim,
+                &mut rng,
+            )
+            .expect("Unable to build RECLAIM proof.");
+            demo.track_receivers(&reclaim);
+            MantaPayPallet::reclaim(Origin::signed(ACCOUNT), reclaim.into())
+                .expect("Unable to reclaim.");
+            report("reclaim");
+        });
+    directory.close()?;
+    Ok(())
+}
@@ -105,15 +105,25 @@
 
 extern crate alloc;
 
+use alloc::collections::BTreeMap;
 use core::marker::PhantomData;
-use frame_support::{ensure, require_transactional};
+use frame_support::{
+    dispatch::{DispatchResult, DispatchResultWithPostInfo},
+    ensure, log,
+    storage::{with_transaction, TransactionOutcome},
+    traits::Get,
+    transactional,
+    weights::Weight,
+    BoundedVec, PalletId,
+};
 use manta_accounting::{
     asset,
     transfer::{
-        canonical::TransferShape, AccountBalance, InvalidSinkAccount, InvalidSourceAccount, Proof,
+        canonical::{MintShape, PrivateTransferShape, ReclaimShape, Shape, TransferShape},
+        AccountBalance, InvalidSinkAccount, InvalidSourceAccount, Proof, ProofInput,
         ReceiverLedger, ReceiverPostError, ReceiverPostingKey, SenderLedger, SenderPostError,
         SenderPostingKey, SinkPostingKey, SourcePostingKey, TransferLedger,
-        TransferLedgerSuperPostingKey, TransferPostError,
+        TransferLedgerSuperPostingKey, TransferPostError, TransferPostingKey,
     },
 };
 use manta_crypto::{
@@ -124,21 +134,85 @@ use manta_pay::config;
 use manta_util::codec::Decode as _;
 use scale_codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
+use sp_runtime::traits::{AccountIdConversion, Saturating, TrailingZeroInput, Zero};
 use types::*;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "demo"))]
+#[cfg_attr(not(test), allow(dead_code))]
 mod mock;
 
+#[cfg(test)]
+mod mock_account32;
+
 #[cfg(test)]
 mod test;
 
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmark;
 
+#[cfg(feature = "client")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "client")))]
+pub mod client;
+
+#[cfg(feature = "demo")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "demo")))]
+pub mod demo;
+
+pub mod compat;
+pub mod migrations;
+
+#[cfg(any(feature = "std", feature = "runtime-benchmarks"))]
+#[cfg_attr(not(any(test, feature = "runtime-benchmarks")), allow(dead_code))]
+mod precomputed_coins;
+
+pub mod runtime;
+
+#[cfg(any(test, feature = "demo", feature = "precompute-coins"))]
+#[cfg_attr(
+    doc_cfg,
+    doc(cfg(any(feature = "demo", feature = "precompute-coins")))
+)]
+pub mod sdk;
+
 pub mod weights;
 
 pub use pallet::*;
 
+/// Commonly Needed Client Types
+///
+/// Re-exports the pallet types and the protocol types they are built from, so that clients can
+/// build and inspect posts with a single import.
+///
+/// ```
+/// use pallet_manta_pay::prelude::*;
+///
+/// fn reclaim_post(
+///     asset: Asset,
+///     sender_posts: Vec<SenderPost>,
+///     receiver_posts: Vec<ReceiverPost>,
+///     validity_proof: Proof,
+/// ) -> TransferPost {
+///     TransferPost {
+///         asset_id: Some(asset.id),
+///         sources: vec![],
+///         sender_posts,
+///         receiver_posts,
+///         sinks: vec![asset.value],
+///         validity_proof,
+///     }
+/// }
+/// ```
+pub mod prelude {
+    #[doc(inline)]
+    pub use crate::types::{
+        Asset, AssetId, AssetValue, BatchMode, EncryptedNote, PostDescription, PostShape,
+        ReceiverPost, SenderPost, TransferPost,
+    };
+
+    #[doc(inline)]
+    pub use manta_pay::config::{Proof, Utxo, UtxoAccumulatorOutput, VoidNumber};
+}
+
 /// Type Definitions for Protocol Structures
 pub mod types {
     use super::*;
@@ -147,8 +221,23 @@ pub mod types {
     pub type AssetId = asset::AssetIdType;
 
     /// Asset Value Type
+    ///
+    /// This is the integer wrapped by the protocol [`asset::AssetValue`], so converting with
+    /// `asset::AssetValue(value)` and `value.0` is lossless in both directions. Values should
+    /// never be cast into narrower integer types.
     pub type AssetValue = asset::AssetValueType;
 
+    /// Returns the sum of `values`, or `None` if it overflows [`AssetValue`].
+    ///
+    /// Totals of the sources or sinks of a post should always be computed with this function, so
+    /// that an overflowing post is rejected instead of panicking or wrapping.
+    #[inline]
+    pub fn checked_sum(values: &[AssetValue]) -> Option<AssetValue> {
+        values
+            .iter()
+            .try_fold(0, |sum: AssetValue, value| sum.checked_add(*value))
+    }
+
     /// Asset
     #[derive(
         Clone,
@@ -165,9 +254,9 @@ pub mod types {
         PartialOrd,
         TypeInfo,
     )]
-    pub struct Asset {
+    pub struct Asset<I = AssetId> {
         /// Asset Id
-        pub id: AssetId,
+        pub id: I,
 
         /// Asset Value
         pub value: AssetValue,
@@ -181,6 +270,34 @@ pub mod types {
         }
     }
 
+    impl From<asset::Asset> for Asset {
+        #[inline]
+        fn from(asset: asset::Asset) -> Self {
+            Self::new(asset.id.0, asset.value.0)
+        }
+    }
+
+    impl From<Asset> for asset::Asset {
+        #[inline]
+        fn from(asset: Asset) -> Self {
+            Self::new(asset::AssetId(asset.id), asset::AssetValue(asset.value))
+        }
+    }
+
+    impl<I> Asset<I> {
+        /// Converts the id of `self` into the asset id type `J`, keeping the same value.
+        #[inline]
+        pub fn convert_id<J>(self) -> Asset<J>
+        where
+            I: Into<J>,
+        {
+            Asset {
+                id: self.id.into(),
+                value: self.value,
+            }
+        }
+    }
+
     /// Encrypted Note
     #[derive(Clone, Debug, Decode, Encode, Eq, Hash, MaxEncodedLen, PartialEq, TypeInfo)]
     pub struct EncryptedNote {
@@ -201,6 +318,20 @@ pub mod types {
         }
     }
 
+    impl EncryptedNote {
+        /// Returns `true` if the ephemeral public key of `self` is not the identity element.
+        ///
+        /// # Note
+        ///
+        /// Bytes which are not a valid curve point are already rejected when the note is decoded.
+        /// The identity element decodes like any other curve point but derives the same shared
+        /// secret for every receiver, so no receiver could decrypt a note carrying it.
+        #[inline]
+        pub fn is_well_formed(&self) -> bool {
+            self.ephemeral_public_key != Default::default()
+        }
+    }
+
     impl From<config::EncryptedNote> for EncryptedNote {
         #[inline]
         fn from(note: config::EncryptedNote) -> Self {
@@ -303,6 +434,309 @@ pub mod types {
         pub validity_proof: config::Proof,
     }
 
+    impl TransferPost {
+        /// Returns the canonical hash of `self`, computed with BLAKE2b-256 over its SCALE encoding.
+        ///
+        /// Clients and the pallet should both use this function whenever posts need to be
+        /// identified, so that the identifiers agree.
+        #[inline]
+        pub fn hash(&self) -> [u8; 32] {
+            sp_io::hashing::blake2_256(&self.encode())
+        }
+
+        /// Returns the shape of `self` if its asset id and participant counts match one of the
+        /// transfer shapes.
+        #[inline]
+        pub fn shape(&self) -> Option<TransferShape> {
+            TransferShape::select(
+                self.asset_id.is_some(),
+                self.sources.len(),
+                self.sender_posts.len(),
+                self.receiver_posts.len(),
+                self.sinks.len(),
+            )
+        }
+
+        /// Returns the reason why `self` has no shape, or `None` if it has one, see
+        /// [`classify_shape_error`].
+        #[inline]
+        pub fn shape_error(&self) -> Option<ShapeError> {
+            classify_shape_error(
+                self.asset_id.is_some(),
+                self.sources.len(),
+                self.sender_posts.len(),
+                self.receiver_posts.len(),
+                self.sinks.len(),
+            )
+        }
+
+        /// Returns `true` if the encoded validity proof of `self` is degenerate, see
+        /// [`is_degenerate_proof`].
+        #[inline]
+        pub fn has_degenerate_proof(&self) -> bool {
+            is_degenerate_proof(&self.validity_proof.encode())
+        }
+
+        /// Describes the shape and public parts of `self`.
+        #[inline]
+        pub fn describe(&self) -> PostDescription {
+            PostDescription {
+                shape: self.shape().map(Into::into),
+                asset_id: self.asset_id,
+                sources: self.sources.clone(),
+                sinks: self.sinks.clone(),
+                sender_count: self.sender_posts.len() as u32,
+                receiver_count: self.receiver_posts.len() as u32,
+            }
+        }
+    }
+
+    /// Returns `true` if every byte of the `encoded` validity proof is the same, as in an all-zero
+    /// proof.
+    ///
+    /// # Note
+    ///
+    /// No honest prover produces such a proof, so it can be rejected without verification. This
+    /// is only a fast path for obvious garbage, and a proof which passes it still has to verify.
+    #[inline]
+    pub fn is_degenerate_proof(encoded: &[u8]) -> bool {
+        encoded.windows(2).all(|pair| pair[0] == pair[1])
+    }
+
+    /// Post Shape
+    #[derive(
+        Clone, Copy, Debug, Decode, Encode, Eq, MaxEncodedLen, Ord, PartialEq, PartialOrd, TypeInfo,
+    )]
+    pub enum PostShape {
+        /// Mint
+        Mint,
+
+        /// Private Transfer
+        PrivateTransfer,
+
+        /// Reclaim
+        Reclaim,
+    }
+
+    impl From<TransferShape> for PostShape {
+        #[inline]
+        fn from(shape: TransferShape) -> Self {
+            match shape {
+                TransferShape::Mint => Self::Mint,
+                TransferShape::PrivateTransfer => Self::PrivateTransfer,
+                TransferShape::Reclaim => Self::Reclaim,
+            }
+        }
+    }
+
+    /// Batch Mode
+    ///
+    /// Whether a failing leg of a batch call reverts the whole batch or only itself.
+    #[derive(Clone, Copy, Debug, Decode, Encode, Eq, MaxEncodedLen, PartialEq, TypeInfo)]
+    pub enum BatchMode {
+        /// Apply every leg or none of them.
+        Atomic,
+
+        /// Apply every leg which succeeds, skipping and reporting the ones which fail.
+        BestEffort,
+    }
+
+    /// Shape Error
+    ///
+    /// Reason why the asset id and participant counts of a post match none of the transfer
+    /// shapes.
+    #[derive(Clone, Copy, Debug, Decode, Encode, Eq, MaxEncodedLen, PartialEq, TypeInfo)]
+    pub enum ShapeError {
+        /// The post has an asset id where its shape forbids one, or the other way around.
+        AssetIdPresenceMismatch,
+
+        /// The post has no source where its shape requires one.
+        MissingSource,
+
+        /// The post has more sources than any shape allows.
+        TooManySources,
+
+        /// The post has a source where its shape forbids one.
+        UnexpectedSource,
+
+        /// The post has fewer senders than any shape with senders requires.
+        MissingSender,
+
+        /// The post has more senders than any shape allows.
+        TooManySenders,
+
+        /// The post has fewer receivers than its shape requires.
+        MissingReceiver,
+
+        /// The post has more receivers than its shape allows.
+        TooManyReceivers,
+
+        /// The post has more sinks than any shape allows.
+        TooManySinks,
+
+        /// The post has a sink where its shape forbids one.
+        UnexpectedSink,
+    }
+
+    /// Query Error
+    ///
+    /// Reason why a batched query was not answered.
+    #[derive(Clone, Copy, Debug, Decode, Encode, Eq, MaxEncodedLen, PartialEq, TypeInfo)]
+    pub enum QueryError {
+        /// The query holds more than the `max`-many items answered by a single call.
+        TooManyItems {
+            /// Maximum Number of Items per Query
+            max: u32,
+        },
+    }
+
+    /// Returns the reason why a post with the given asset id presence and participant counts
+    /// matches none of the transfer shapes, or `None` if [`TransferShape::select`] would accept
+    /// it.
+    ///
+    /// # Note
+    ///
+    /// The sender count picks the intended shape: posts without senders are taken as mints, and
+    /// posts with senders are taken as reclaims if they have a sink and as private transfers
+    /// otherwise. The first count of that shape which does not match is reported.
+    #[inline]
+    pub fn classify_shape_error(
+        asset_id_present: bool,
+        sources: usize,
+        senders: usize,
+        receivers: usize,
+        sinks: usize,
+    ) -> Option<ShapeError> {
+        if senders > PrivateTransferShape::SENDERS.max(ReclaimShape::SENDERS) {
+            return Some(ShapeError::TooManySenders);
+        }
+        if sources > MintShape::SOURCES {
+            return Some(ShapeError::TooManySources);
+        }
+        if sinks > ReclaimShape::SINKS {
+            return Some(ShapeError::TooManySinks);
+        }
+        let (expected_asset_id, expected_receivers) = if senders == MintShape::SENDERS {
+            if sources < MintShape::SOURCES {
+                return Some(ShapeError::MissingSource);
+            }
+            if sinks > MintShape::SINKS {
+                return Some(ShapeError::UnexpectedSink);
+            }
+            (true, MintShape::RECEIVERS)
+        } else if sinks == ReclaimShape::SINKS {
+            if senders < ReclaimShape::SENDERS {
+                return Some(ShapeError::MissingSender);
+            }
+            if sources > ReclaimShape::SOURCES {
+                return Some(ShapeError::UnexpectedSource);
+            }
+            (true, ReclaimShape::RECEIVERS)
+        } else {
+            if senders < PrivateTransferShape::SENDERS {
+                return Some(ShapeError::MissingSender);
+            }
+            if sources > PrivateTransferShape::SOURCES {
+                return Some(ShapeError::UnexpectedSource);
+            }
+            (false, PrivateTransferShape::RECEIVERS)
+        };
+        if asset_id_present != expected_asset_id {
+            return Some(ShapeError::AssetIdPresenceMismatch);
+        }
+        match receivers.cmp(&expected_receivers) {
+            core::cmp::Ordering::Less => Some(ShapeError::MissingReceiver),
+            core::cmp::Ordering::Greater => Some(ShapeError::TooManyReceivers),
+            core::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Shape Dimensions
+    ///
+    /// Asset id presence and participant counts which [`TransferShape::select`] expects of a
+    /// post with a given shape.
+    #[derive(Clone, Copy, Debug, Decode, Encode, Eq, MaxEncodedLen, PartialEq, TypeInfo)]
+    pub struct ShapeDims {
+        /// Whether the Post Reveals its Asset Id
+        pub asset_id: bool,
+
+        /// Number of Sources
+        pub sources: u32,
+
+        /// Number of Sender Posts
+        pub senders: u32,
+
+        /// Number of Receiver Posts
+        pub receivers: u32,
+
+        /// Number of Sinks
+        pub sinks: u32,
+    }
+
+    /// Returns the dimensions of every transfer shape which the pallet accepts.
+    #[inline]
+    pub fn shape_requirements() -> Vec<(PostShape, ShapeDims)> {
+        vec![
+            (
+                PostShape::Mint,
+                ShapeDims {
+                    asset_id: true,
+                    sources: MintShape::SOURCES as u32,
+                    senders: MintShape::SENDERS as u32,
+                    receivers: MintShape::RECEIVERS as u32,
+                    sinks: MintShape::SINKS as u32,
+                },
+            ),
+            (
+                PostShape::PrivateTransfer,
+                ShapeDims {
+                    asset_id: false,
+                    sources: PrivateTransferShape::SOURCES as u32,
+                    senders: PrivateTransferShape::SENDERS as u32,
+                    receivers: PrivateTransferShape::RECEIVERS as u32,
+                    sinks: PrivateTransferShape::SINKS as u32,
+                },
+            ),
+            (
+                PostShape::Reclaim,
+                ShapeDims {
+                    asset_id: true,
+                    sources: ReclaimShape::SOURCES as u32,
+                    senders: ReclaimShape::SENDERS as u32,
+                    receivers: ReclaimShape::RECEIVERS as u32,
+                    sinks: ReclaimShape::SINKS as u32,
+                },
+            ),
+        ]
+    }
+
+    /// Post Description
+    ///
+    /// Shape and public parts of a [`TransferPost`], for rendering posts without interpreting
+    /// them.
+    #[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, TypeInfo)]
+    pub struct PostDescription {
+        /// Detected Shape
+        ///
+        /// This is `None` when the post does not match any transfer shape.
+        pub shape: Option<PostShape>,
+
+        /// Asset Id, if Revealed
+        pub asset_id: Option<AssetId>,
+
+        /// Public Source Amounts
+        pub sources: Vec<AssetValue>,
+
+        /// Public Sink Amounts
+        pub sinks: Vec<AssetValue>,
+
+        /// Number of Sender Posts
+        pub sender_count: u32,
+
+        /// Number of Receiver Posts
+        pub receiver_count: u32,
+    }
+
     impl From<config::TransferPost> for TransferPost {
         #[inline]
         fn from(post: config::TransferPost) -> Self {
@@ -350,6 +784,28 @@ pub mod types {
         pub inner_path: Vec<InnerDigest>,
     }
 
+    impl CurrentPath {
+        /// Inner Path Length
+        ///
+        /// Every inner path of a populated Merkle tree of the configured height has exactly this
+        /// many inner digests.
+        pub const INNER_PATH_LENGTH: usize =
+            <config::MerkleTreeConfiguration as merkle_tree::Configuration>::HEIGHT - 2;
+
+        /// Returns `true` if the inner path of `self` matches the configured tree depth.
+        #[inline]
+        pub fn has_valid_depth(&self) -> bool {
+            self.inner_path.len() == Self::INNER_PATH_LENGTH
+        }
+    }
+
+    // NOTE: The inner path length is computed as `HEIGHT - 2`, so a tree with a smaller height
+    //       would underflow and silently produce an invalid path length.
+    const _: () = assert!(
+        <config::MerkleTreeConfiguration as merkle_tree::Configuration>::HEIGHT >= 2,
+        "The Merkle tree must have height at least two."
+    );
+
     impl MaxEncodedLen for CurrentPath {
         #[inline]
         fn max_encoded_len() -> usize {
@@ -358,9 +814,7 @@ pub mod types {
                 .saturating_add(u32::max_encoded_len())
                 .saturating_add(
                     // NOTE: We know that these paths don't exceed the path length.
-                    InnerDigest::max_encoded_len().saturating_mul(
-                        manta_crypto::merkle_tree::path_length::<config::MerkleTreeConfiguration>(),
-                    ),
+                    InnerDigest::max_encoded_len().saturating_mul(Self::INNER_PATH_LENGTH),
                 )
         }
     }
@@ -396,404 +850,3255 @@ pub mod types {
         /// Current Path
         pub current_path: CurrentPath,
     }
-}
 
-/// MantaPay Pallet
-#[frame_support::pallet]
-pub mod pallet {
-    use super::*;
-    use frame_support::pallet_prelude::*;
-    use frame_system::pallet_prelude::*;
-    use sp_runtime::traits::StaticLookup;
+    /// Note Cursor
+    ///
+    /// Position in the insertion-ordered stream of notes, pointing at the next entry to read.
+    #[derive(
+        Clone, Copy, Debug, Decode, Default, Encode, Eq, Hash, MaxEncodedLen, PartialEq, TypeInfo,
+    )]
+    pub struct NoteCursor {
+        /// Shard Index
+        pub shard_index: u8,
 
-    /// Extrinsic Weight Info
-    pub trait WeightInfo {
-        /// Returns the [`Weight`] of the [`Pallet::transfer`] extrinsic.
-        fn transfer() -> Weight;
+        /// Leaf Index
+        pub leaf_index: u64,
+    }
 
-        /// Returns the [`Weight`] of the [`Pallet::mint`] extrinsic.
-        fn mint() -> Weight;
+    impl NoteCursor {
+        /// Builds a new [`NoteCursor`] from `shard_index` and `leaf_index`.
+        #[inline]
+        pub fn new(shard_index: u8, leaf_index: u64) -> Self {
+            Self {
+                shard_index,
+                leaf_index,
+            }
+        }
+    }
 
-        /// Returns the [`Weight`] of the [`Pallet::private_transfer`] extrinsic.
-        fn private_transfer() -> Weight;
+    /// Shard Chunk
+    ///
+    /// Contiguous run of notes from a single shard, starting at `start_index`.
+    #[derive(Clone, Debug, Decode, Default, Encode, Eq, PartialEq, TypeInfo)]
+    pub struct ShardChunk {
+        /// Shard Index
+        pub shard_index: u8,
 
-        /// Returns the [`Weight`] of the [`Pallet::reclaim`] extrinsic.
-        fn reclaim() -> Weight;
-    }
+        /// Leaf Index of the First Note
+        pub start_index: u64,
 
-    /// Pallet
-    #[pallet::pallet]
-    #[pallet::generate_store(pub(super) trait Store)]
-    pub struct Pallet<T>(_);
+        /// UTXOs and Encrypted Notes in Insertion Order
+        pub notes: Vec<(config::Utxo, EncryptedNote)>,
+    }
 
-    /// The module configuration trait.
-    #[pallet::config]
-    pub trait Config: frame_system::Config {
-        /// The overarching event type.
-        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+    /// Note Page
+    ///
+    /// # Encoding
+    ///
+    /// The `next` cursor is encoded before the chunks, and each chunk is length-prefixed, so that
+    /// a client can decode a page incrementally without materializing all of its notes. See
+    /// `PullResponseReader` in the `client` module for a streaming decoder.
+    #[derive(Clone, Debug, Decode, Default, Encode, Eq, PartialEq, TypeInfo)]
+    pub struct NotePage {
+        /// Cursor for the Next Page
+        ///
+        /// This is `None` when there are no more notes to read in the requested shard range.
+        pub next: Option<NoteCursor>,
 
-        /// Weight information for extrinsics in this pallet.
-        type WeightInfo: WeightInfo;
+        /// Shard Chunks in Shard Order
+        pub chunks: Vec<ShardChunk>,
     }
 
-    #[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+    impl NotePage {
+        /// Returns the number of notes in `self`.
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.chunks.iter().map(|chunk| chunk.notes.len()).sum()
+        }
 
-    /// Public Balance State
-    #[pallet::storage]
-    pub(super) type Balances<T: Config> = StorageDoubleMap<
-        _,
-        Blake2_128Concat,
-        T::AccountId,
-        Blake2_128Concat,
-        AssetId,
-        AssetValue,
-        ValueQuery,
-    >;
+        /// Returns `true` if `self` has no notes.
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.chunks.iter().all(|chunk| chunk.notes.is_empty())
+        }
 
-    /// Total Supply per AssetId
-    #[pallet::storage]
-    pub(super) type TotalSupply<T: Config> =
-        StorageMap<_, Blake2_128Concat, AssetId, AssetValue, ValueQuery>;
+        /// Returns an iterator over all the notes in `self` with their position in the ledger.
+        #[inline]
+        pub fn entries(
+            &self,
+        ) -> impl Iterator<Item = (NoteCursor, &(config::Utxo, EncryptedNote))> {
+            self.chunks.iter().flat_map(|chunk| {
+                chunk.notes.iter().enumerate().map(move |(i, entry)| {
+                    (
+                        NoteCursor::new(chunk.shard_index, chunk.start_index + i as u64),
+                        entry,
+                    )
+                })
+            })
+        }
 
-    ///
-    #[pallet::storage]
-    pub(super) type Shards<T: Config> =
-        StorageDoubleMap<_, Identity, u8, Identity, u64, (config::Utxo, EncryptedNote), ValueQuery>;
+        /// Pushes `entry` located at `cursor` onto the end of `self`, opening a new chunk if
+        /// `cursor` is in a different shard than the last chunk.
+        #[inline]
+        pub(crate) fn push(&mut self, cursor: NoteCursor, entry: (config::Utxo, EncryptedNote)) {
+            match self.chunks.last_mut() {
+                Some(chunk) if chunk.shard_index == cursor.shard_index => chunk.notes.push(entry),
+                _ => self.chunks.push(ShardChunk {
+                    shard_index: cursor.shard_index,
+                    start_index: cursor.leaf_index,
+                    notes: vec![entry],
+                }),
+            }
+        }
+    }
 
+    /// Ledger Snapshot
     ///
-    #[pallet::storage]
-    pub(super) type ShardTrees<T: Config> =
-        StorageMap<_, Identity, u8, UtxoMerkleTreePath, ValueQuery>;
-
+    /// Canonical dump of the shielded ledger state, used to move the ledger between chains or to
+    /// back it up. Every list is sorted so that equal ledgers produce equal snapshots.
     ///
-    #[pallet::storage]
-    pub(super) type UtxoAccumulatorOutputs<T: Config> =
-        StorageMap<_, Identity, config::UtxoAccumulatorOutput, (), ValueQuery>;
-
+    /// # Encoding
     ///
-    #[pallet::storage]
-    pub(super) type UtxoSet<T: Config> = StorageMap<_, Identity, config::Utxo, (), ValueQuery>;
+    /// Snapshots always encode in the current format, but decode from the encoding of every known
+    /// version, which is selected by the version prefix. Version `1` snapshots encode their version
+    /// in a single byte, which is distinguished from the two bytes of later versions by its value.
+    #[derive(Clone, Debug, Encode, Eq, PartialEq, TypeInfo)]
+    pub struct LedgerSnapshot {
+        /// Snapshot Format Version
+        pub version: u16,
 
-    ///
-    #[pallet::storage]
-    pub(super) type VoidNumberSet<T: Config> =
-        StorageMap<_, Identity, config::VoidNumber, (), ValueQuery>;
+        /// Shard Entries as `(shard_index, leaf_index, utxo, note)`
+        pub shards: Vec<(u8, u64, config::Utxo, EncryptedNote)>,
 
-    ///
-    #[pallet::storage]
-    pub(super) type VoidNumberSetInsertionOrder<T: Config> =
-        StorageMap<_, Identity, u64, config::VoidNumber, ValueQuery>;
+        /// Shard Trees as `(shard_index, tree)`
+        pub shard_trees: Vec<(u8, UtxoMerkleTreePath)>,
 
-    ///
-    #[pallet::storage]
-    pub(super) type VoidNumberSetSize<T: Config> = StorageValue<_, u64, ValueQuery>;
+        /// Registered UTXOs
+        pub utxo_set: Vec<config::Utxo>,
 
-    /// Genesis Configuration
-    #[pallet::genesis_config]
-    pub struct GenesisConfig<T: Config> {
-        pub owner: T::AccountId,
-        pub assets: alloc::collections::btree_set::BTreeSet<(AssetId, AssetValue)>,
+        /// UTXO Accumulator Outputs
+        pub utxo_accumulator_outputs: Vec<config::UtxoAccumulatorOutput>,
+
+        /// Spent Void Numbers in Insertion Order
+        pub void_numbers: Vec<config::VoidNumber>,
+
+        /// Latest Roots as `(shard_index, root)`
+        pub latest_roots: Vec<(u8, config::UtxoAccumulatorOutput)>,
+
+        /// Total Value Minted into the Shielded Pool per Asset
+        pub total_minted: Vec<(AssetId, AssetValue)>,
+
+        /// Total Value Reclaimed from the Shielded Pool per Asset
+        pub total_reclaimed: Vec<(AssetId, AssetValue)>,
+
+        /// Smallest and Largest Value Minted in a Single Source per Asset
+        pub mint_value_ranges: Vec<(AssetId, (AssetValue, AssetValue))>,
+
+        /// Number of UTXOs Minted per Asset
+        pub utxo_counts: Vec<(AssetId, u64)>,
     }
 
-    #[cfg(feature = "std")]
-    impl<T: Config> Default for GenesisConfig<T> {
+    impl Decode for LedgerSnapshot {
         #[inline]
-        fn default() -> Self {
-            /* FIXME: `AccountId` does not implement default!
-            GenesisConfig {
-                owner: Default::default(),
-                assets: Default::default(),
+        fn decode<I>(input: &mut I) -> Result<Self, scale_codec::Error>
+        where
+            I: scale_codec::Input,
+        {
+            let first = input.read_byte()?;
+            let version = match first {
+                1 => 1,
+                _ => u16::from_le_bytes([first, input.read_byte()?]),
+            };
+            let shards = Decode::decode(input)?;
+            let shard_trees = Decode::decode(input)?;
+            let utxo_set = Decode::decode(input)?;
+            let utxo_accumulator_outputs = Decode::decode(input)?;
+            let void_numbers = Decode::decode(input)?;
+            if version < 3 {
+                return Ok(Self {
+                    version,
+                    shards,
+                    shard_trees,
+                    utxo_set,
+                    utxo_accumulator_outputs,
+                    void_numbers,
+                    latest_roots: Default::default(),
+                    total_minted: Default::default(),
+                    total_reclaimed: Default::default(),
+                    mint_value_ranges: Default::default(),
+                    utxo_counts: Default::default(),
+                });
             }
-            */
-            todo!()
+            Ok(Self {
+                version,
+                shards,
+                shard_trees,
+                utxo_set,
+                utxo_accumulator_outputs,
+                void_numbers,
+                latest_roots: Decode::decode(input)?,
+                total_minted: Decode::decode(input)?,
+                total_reclaimed: Decode::decode(input)?,
+                mint_value_ranges: Decode::decode(input)?,
+                utxo_counts: Decode::decode(input)?,
+            })
         }
     }
 
-    #[pallet::genesis_build]
-    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+    impl LedgerSnapshot {
+        /// Current Snapshot Format Version
+        pub const VERSION: u16 = 3;
+
+        /// Upgrades `self` to the current [`VERSION`](Self::VERSION), returning `None` if its
+        /// version is unknown.
+        ///
+        /// # Note
+        ///
+        /// Version `1` and `2` snapshots do not carry the latest roots or the per-asset accounting
+        /// of version `3`, which are left empty when decoding them, so they are upgraded by
+        /// relabeling them.
         #[inline]
-        fn build(&self) {
-            for (id, value) in &self.assets {
-                Pallet::<T>::init_asset(&self.owner, *id, *value);
+        pub fn migrate(mut self) -> Option<Self> {
+            match self.version {
+                1 | 2 => {
+                    self.version = Self::VERSION;
+                    Some(self)
+                }
+                Self::VERSION => Some(self),
+                _ => None,
             }
         }
-    }
 
-    #[pallet::call]
-    impl<T: Config> Pallet<T> {
-        /// Transfers public `asset` from `origin` to `target`.
-        #[pallet::weight(T::WeightInfo::transfer())]
-        #[require_transactional]
-        pub fn transfer(
-            origin: OriginFor<T>,
-            target: <T::Lookup as StaticLookup>::Source,
-            asset: Asset,
-        ) -> DispatchResultWithPostInfo {
-            let origin = ensure_signed(origin)?;
-            let target = T::Lookup::lookup(target)?;
-            ensure!(
-                TotalSupply::<T>::contains_key(&asset.id),
-                Error::<T>::UninitializedSupply
-            );
-            let origin_balance = Balances::<T>::get(&origin, asset.id);
-            ensure!(asset.value > 0, Error::<T>::ZeroTransfer);
-            ensure!(origin_balance >= asset.value, Error::<T>::BalanceLow);
-            Balances::<T>::mutate(&origin, asset.id, |balance| *balance -= asset.value);
-            Balances::<T>::mutate(&target, asset.id, |balance| *balance += asset.value);
-            Self::deposit_event(Event::Transfer {
-                asset,
-                source: origin,
-                sink: target,
-            });
-            Ok(().into())
+        /// Returns the number of per-asset entries of `self`, each of which takes one storage
+        /// write on import.
+        #[inline]
+        pub fn asset_entry_count(&self) -> u64 {
+            (self.total_minted.len()
+                + self.total_reclaimed.len()
+                + self.mint_value_ranges.len()
+                + self.utxo_counts.len()) as u64
         }
+    }
 
-        /// Mints some assets encoded in `post` to the `origin` account.
-        #[pallet::weight(T::WeightInfo::mint())]
-        #[require_transactional]
-        pub fn mint(origin: OriginFor<T>, post: TransferPost) -> DispatchResultWithPostInfo {
-            let origin = ensure_signed(origin)?;
-            let mut ledger = Self::ledger();
-            Self::deposit_event(
-                config::TransferPost::from(post)
-                    .post(vec![origin], vec![], &(), &mut ledger)
-                    .map_err(Error::<T>::from)?
-                    .convert(None),
-            );
-            Ok(().into())
+    /// Pallet Limits
+    ///
+    /// Configured maximums and minimums of the pallet, so that wallets can learn what the pallet
+    /// accepts in a single query.
+    #[derive(Clone, Copy, Debug, Decode, Encode, Eq, PartialEq, TypeInfo)]
+    pub struct PalletLimits {
+        /// Maximum Number of Posts in a Private Batch
+        pub max_private_batch: u32,
+
+        /// Maximum Number of Transfers in a Public Batch
+        pub max_public_batch: u32,
+
+        /// Maximum Number of Queued Private Transfers
+        pub max_pending_posts: u32,
+
+        /// Maximum Number of Sender Posts in a Transfer
+        pub max_senders: u32,
+
+        /// Maximum Number of Receiver Posts in a Transfer
+        pub max_receivers: u32,
+
+        /// Maximum Encoded Size of an Encrypted Note
+        pub max_note_size: u32,
+
+        /// Maximum Encoded Size of a Validity Proof
+        pub max_proof_size: u32,
+
+        /// Maximum Number of Void Numbers per Query
+        pub max_void_number_query: u32,
+
+        /// Maximum Number of Roots per Query
+        pub max_root_query: u32,
+
+        /// Maximum Number of Registered UTXOs
+        pub max_utxo_count: u64,
+
+        /// Maximum Number of Distinct Assets per Account
+        pub max_assets_per_account: Option<u32>,
+
+        /// Minimum Value of a Reclaim Sink
+        pub min_reclaim_value: AssetValue,
+
+        /// Minimum Number of UTXOs Minted of an Asset before it can be Reclaimed
+        pub min_anonymity_set: u64,
+
+        /// Maximum Number of Void Numbers per Export Page
+        pub max_void_number_export: u32,
+
+        /// Maximum Number of Notes per Pulled Page
+        pub max_note_page: u32,
+    }
+
+    /// Storage Statistics
+    ///
+    /// Sizes of the shielded ledger storage items, read from counters maintained on every
+    /// insertion so that monitoring does not iterate the sets themselves.
+    #[derive(Clone, Debug, Decode, Default, Encode, Eq, PartialEq, TypeInfo)]
+    pub struct StorageStats {
+        /// Number of Registered UTXOs
+        pub utxo_count: u64,
+
+        /// Number of Spent Void Numbers
+        pub void_number_count: u64,
+
+        /// Number of Known UTXO Accumulator Outputs
+        pub root_count: u64,
+
+        /// Number of UTXOs per Non-Empty Shard, in Shard Order
+        pub shard_sizes: Vec<(u8, u64)>,
+    }
+
+    /// Parameters Source
+    ///
+    /// Location of one encoded parameter set that the genesis configuration stores in place of the
+    /// one shipped with the SDK, so that development chains can point at local parameters.
+    #[cfg_attr(
+        feature = "std",
+        derive(frame_support::Serialize, frame_support::Deserialize),
+        serde(crate = "frame_support::serde")
+    )]
+    #[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, TypeInfo)]
+    pub enum ParametersSource {
+        /// Encoded Parameters Embedded in the Chain Specification
+        Embedded(Vec<u8>),
+
+        /// Path of a File Containing the Encoded Parameters
+        File(alloc::string::String),
+
+        /// Parameters Shipped with the SDK
+        Sdk,
+    }
+
+    impl Default for ParametersSource {
+        #[inline]
+        fn default() -> Self {
+            Self::Sdk
         }
+    }
 
-        /// Transfers private assets encoded in `post`.
-        ///
-        /// # Note
-        ///
-        /// In this transaction, `origin` is just signing the `post` and is not necessarily related
-        /// to any of the participants in the transaction itself.
-        #[pallet::weight(T::WeightInfo::private_transfer())]
-        #[require_transactional]
-        pub fn private_transfer(
-            origin: OriginFor<T>,
-            post: TransferPost,
-        ) -> DispatchResultWithPostInfo {
-            let origin = ensure_signed(origin)?;
-            let mut ledger = Self::ledger();
-            Self::deposit_event(
-                config::TransferPost::from(post)
-                    .post(vec![], vec![], &(), &mut ledger)
-                    .map_err(Error::<T>::from)?
-                    .convert(Some(origin)),
-            );
-            Ok(().into())
+    impl ParametersSource {
+        /// Loads the encoded parameters from `self`, returning `None` for [`Self::Sdk`] since the
+        /// SDK parameters are used when no override is stored.
+        #[cfg(feature = "std")]
+        #[inline]
+        pub fn load(&self) -> std::io::Result<Option<Vec<u8>>> {
+            match self {
+                Self::Embedded(parameters) => Ok(Some(parameters.clone())),
+                Self::File(path) => std::fs::read(path).map(Some),
+                Self::Sdk => Ok(None),
+            }
         }
+    }
 
-        /// Transforms some private assets into public ones using `post`, sending the public assets
-        /// to the `origin` account.
-        #[pallet::weight(T::WeightInfo::reclaim())]
-        #[require_transactional]
-        pub fn reclaim(origin: OriginFor<T>, post: TransferPost) -> DispatchResultWithPostInfo {
-            let origin = ensure_signed(origin)?;
-            let mut ledger = Self::ledger();
-            Self::deposit_event(
-                config::TransferPost::from(post)
-                    .post(vec![], vec![origin], &(), &mut ledger)
-                    .map_err(Error::<T>::from)?
-                    .convert(None),
-            );
-            Ok(().into())
+    /// Genesis Parameters
+    ///
+    /// Sources of the UTXO accumulator model and of the verifying context of every transfer shape
+    /// that the genesis configuration stores in place of the ones shipped with the SDK.
+    ///
+    /// # Note
+    ///
+    /// Proving contexts never reach the chain, so provers of a development chain must load the
+    /// proving contexts generated together with the verifying contexts configured here.
+    #[cfg_attr(
+        feature = "std",
+        derive(frame_support::Serialize, frame_support::Deserialize),
+        serde(crate = "frame_support::serde")
+    )]
+    #[derive(Clone, Debug, Decode, Default, Encode, Eq, PartialEq, TypeInfo)]
+    pub struct GenesisParameters {
+        /// UTXO Accumulator Model Source
+        pub utxo_accumulator_model: ParametersSource,
+
+        /// Mint Verifying Context Source
+        pub mint: ParametersSource,
+
+        /// Private Transfer Verifying Context Source
+        pub private_transfer: ParametersSource,
+
+        /// Reclaim Verifying Context Source
+        pub reclaim: ParametersSource,
+    }
+
+    impl GenesisParameters {
+        /// Returns the verifying context sources of `self` together with the shape they verify.
+        #[inline]
+        pub fn verifying_contexts(&self) -> [(PostShape, &ParametersSource); 3] {
+            [
+                (PostShape::Mint, &self.mint),
+                (PostShape::PrivateTransfer, &self.private_transfer),
+                (PostShape::Reclaim, &self.reclaim),
+            ]
         }
     }
+}
 
-    /// Event
-    #[pallet::event]
-    #[pallet::generate_deposit(fn deposit_event)]
-    pub enum Event<T: Config> {
-        /// Transfer Event
-        Transfer {
-            /// Asset Transfered
-            asset: Asset,
+/// MantaPay Pallet
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_support::{pallet_prelude::*, weights::constants::RocksDbWeight};
+    use frame_system::pallet_prelude::*;
+    use sp_runtime::{traits::StaticLookup, DispatchError};
 
-            /// Source Account
-            source: T::AccountId,
+    /// Extrinsic Weight Info
+    ///
+    /// # Note
+    ///
+    /// Only the weights generated into [`weights`](crate::weights) by the benchmark CLI are
+    /// required. Every other weight defaults to a hand estimate against [`RocksDbWeight`] until
+    /// the weights are regenerated with the `runtime-benchmarks` feature:
+    ///
+    /// ```text
+    /// benchmark --chain=dev --pallet=pallet_manta_pay --extrinsic='*' --steps=50 --repeat=20 \
+    ///     --execution=wasm --wasm-execution=compiled --heap-pages=4096 --output=src/weights.rs
+    /// ```
+    pub trait WeightInfo {
+        /// Returns the [`Weight`] of the [`Pallet::transfer`] extrinsic.
+        fn transfer() -> Weight;
 
-            /// Sink Account
-            sink: T::AccountId,
-        },
+        /// Returns the [`Weight`] of the [`Pallet::mint`] extrinsic.
+        fn mint() -> Weight;
 
-        /// Mint Event
-        Mint {
-            /// Asset Minted
-            asset: Asset,
+        /// Returns the [`Weight`] of the [`Pallet::private_transfer`] extrinsic.
+        fn private_transfer() -> Weight;
 
-            /// Source Account
-            source: T::AccountId,
-        },
+        /// Returns the [`Weight`] of the [`Pallet::reclaim`] extrinsic.
+        fn reclaim() -> Weight;
 
-        /// Private Transfer Event
-        PrivateTransfer {
-            /// Origin Account
-            origin: T::AccountId,
-        },
+        /// Returns the [`Weight`] of the [`Pallet::initialize_asset`] extrinsic.
+        #[inline]
+        fn initialize_asset() -> Weight {
+            (32_000_000 as Weight)
+                .saturating_add(RocksDbWeight::get().reads(1))
+                .saturating_add(RocksDbWeight::get().writes(2))
+        }
 
-        /// Reclaim Event
-        Reclaim {
-            /// Asset Reclaimed
-            asset: Asset,
+        /// Returns the [`Weight`] of the [`Pallet::batch_transfer`] extrinsic with `n` transfers.
+        #[inline]
+        fn batch_transfer(n: u32) -> Weight {
+            (14_000_000 as Weight)
+                .saturating_add((94_000_000 as Weight).saturating_mul(n as Weight))
+                .saturating_add(RocksDbWeight::get().reads_writes(1, 1))
+                .saturating_add(
+                    RocksDbWeight::get()
+                        .reads_writes(3, 2)
+                        .saturating_mul(n as Weight),
+                )
+        }
 
-            /// Sink Account
-            sink: T::AccountId,
-        },
+        /// Returns the [`Weight`] of the [`Pallet::batch_mint`] extrinsic with `n` posts.
+        #[inline]
+        fn batch_mint(n: u32) -> Weight {
+            (1_412_000_000 as Weight)
+                .saturating_add((101_939_000_000 as Weight).saturating_mul(n as Weight))
+                .saturating_add(RocksDbWeight::get().reads_writes(2, 1))
+                .saturating_add(
+                    RocksDbWeight::get()
+                        .reads_writes(9, 11)
+                        .saturating_mul(n as Weight),
+                )
+        }
+
+        /// Returns the [`Weight`] of the [`Pallet::set_utxo_accumulator_model`] extrinsic.
+        #[inline]
+        fn set_utxo_accumulator_model() -> Weight {
+            (6_412_000_000 as Weight).saturating_add(RocksDbWeight::get().reads_writes(1, 1))
+        }
+
+        /// Returns the [`Weight`] of the [`Pallet::destroy_asset`] extrinsic.
+        #[inline]
+        fn destroy_asset() -> Weight {
+            (41_000_000 as Weight).saturating_add(RocksDbWeight::get().reads_writes(5, 8))
+        }
+
+        /// Returns the [`Weight`] of [`Pallet::quick_invariant_check`].
+        #[inline]
+        fn quick_invariant_check() -> Weight {
+            (9_000_000 as Weight).saturating_add(RocksDbWeight::get().reads(8))
+        }
+
+        /// Returns the [`Weight`] of checking a [`LedgerSnapshot`] with `n` shard entries when it
+        /// is imported by [`Pallet::import_ledger`] with `verify_roots` set.
+        #[inline]
+        fn verify_snapshot(n: u32) -> Weight {
+            (12_000_000 as Weight)
+                .saturating_add((1_150_000_000 as Weight).saturating_mul(n as Weight))
+                .saturating_add(RocksDbWeight::get().reads(1))
+        }
+
+        /// Returns the [`Weight`] of the [`Pallet::import_ledger`] extrinsic with `u` shard entries
+        /// and `v` void numbers, excluding the per-asset entries.
+        #[inline]
+        fn import_ledger(u: u32, v: u32) -> Weight {
+            (25_000_000 as Weight)
+                .saturating_add((31_000_000 as Weight).saturating_mul(u as Weight))
+                .saturating_add((9_000_000 as Weight).saturating_mul(v as Weight))
+                .saturating_add(RocksDbWeight::get().reads_writes(7, 2))
+                .saturating_add(
+                    RocksDbWeight::get()
+                        .reads_writes(2, 7)
+                        .saturating_mul(u as Weight),
+                )
+                .saturating_add(RocksDbWeight::get().writes(2).saturating_mul(v as Weight))
+        }
+
+        /// Returns the [`Weight`] of the [`Pallet::allow_asset_id_reuse`] extrinsic.
+        #[inline]
+        fn allow_asset_id_reuse() -> Weight {
+            (5_000_000 as Weight).saturating_add(RocksDbWeight::get().writes(1))
+        }
+
+        /// Returns the [`Weight`] of the [`Pallet::force_set_balance`] extrinsic.
+        #[inline]
+        fn force_set_balance() -> Weight {
+            (33_000_000 as Weight).saturating_add(RocksDbWeight::get().reads_writes(4, 4))
+        }
     }
 
-    /// Error
-    #[pallet::error]
-    pub enum Error<T> {
-        /// Uninitialized Supply
+    /// Storage Version
+    ///
+    /// Version `1` has the counters populated by
+    /// [`PopulateCounters`](crate::migrations::PopulateCounters).
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+    /// Pallet
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::storage_version(STORAGE_VERSION)]
+    pub struct Pallet<T>(_);
+
+    /// The module configuration trait.
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching event type.
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
+
+        /// Runtime Asset Id Type
         ///
-        /// Supply of the given Asset Id has not yet been initialized.
-        UninitializedSupply,
+        /// Public balances, total supplies, and asset events are keyed by this type. Every
+        /// protocol [`AssetId`] must map into it, while runtime ids which have no protocol
+        /// counterpart cannot be used with private transfers.
+        type AssetId: Parameter
+            + Member
+            + Copy
+            + Ord
+            + MaxEncodedLen
+            + From<types::AssetId>
+            + TryInto<types::AssetId>;
 
-        /// Zero Transfer
+        /// Maximum number of private transfers waiting for delayed verification.
         ///
-        /// Public transfers cannot include amounts equal to zero.
-        ZeroTransfer,
+        /// Setting this to zero disables delayed verification, so that [`Pallet::private_transfer`]
+        /// verifies and posts every transfer immediately.
+        ///
+        /// Delayed transfers are posted whole or not at all: a transfer with a receiver which no
+        /// longer fits into its shard is dropped with [`Event::PendingPostDropped`], so that no
+        /// void number is spent without registering every receiver.
+        #[pallet::constant]
+        type MaxPendingPosts: Get<u32>;
 
-        /// Balance Low
+        /// Maximum number of posts in a [`Pallet::batch_private_transfer`] or
+        /// [`Pallet::batch_mint`] call.
+        #[pallet::constant]
+        type MaxPrivateBatch: Get<u32>;
+
+        /// Maximum number of public transfers in a [`Pallet::batch_transfer`] call.
+        #[pallet::constant]
+        type MaxPublicBatch: Get<u32>;
+
+        /// Number of most recent batch idempotency keys remembered per account in
+        /// [`ProcessedBatches`].
         ///
-        /// Attempted to withdraw from balance which was smaller than the withdrawl amount.
-        BalanceLow,
+        /// # Note
+        ///
+        /// A resubmitted batch is only rejected while its key is remembered, so this should cover
+        /// the number of keyed batches an account submits within the retry window of its client.
+        /// Setting it to zero disables idempotency keys.
+        #[pallet::constant]
+        type MaxProcessedBatches: Get<u32>;
 
-        /// Invalid Shape
+        /// Allows [`Pallet::transfer`] and [`Pallet::batch_transfer`] when `true`, leaving mints
+        /// and reclaims as the only way to move public balances when `false`.
+        #[pallet::constant]
+        type PublicTransfersEnabled: Get<bool>;
+
+        /// Rejects mints of uninitialized assets with [`Error::UninitializedSupply`] when `true`,
+        /// instead of relying on the empty balance of the signer to reject them.
+        #[pallet::constant]
+        type RequireAssetInitForMint: Get<bool>;
+
+        /// Only accepts sender posts built against the latest root of some shard when `true`,
+        /// instead of against any historical root.
         ///
-        /// The transfer had an invalid shape.
-        InvalidShape,
+        /// # Note
+        ///
+        /// Latest roots are only recorded when UTXOs are inserted, so after
+        /// [`Pallet::import_ledger`] no root is accepted in this mode until each shard receives a
+        /// new UTXO.
+        #[pallet::constant]
+        type StrictRootMode: Get<bool>;
 
-        /// Asset Spent
+        /// Maximum number of void numbers answered by a single [`Pallet::are_spent`] query.
+        #[pallet::constant]
+        type MaxVoidNumberQuery: Get<u32>;
+
+        /// Maximum number of roots answered by a single [`Pallet::known_roots`] query.
+        #[pallet::constant]
+        type MaxRootQuery: Get<u32>;
+
+        /// Maximum number of void numbers exported by a single [`Pallet::export_void_numbers`]
+        /// page.
+        #[pallet::constant]
+        type MaxVoidNumberExport: Get<u32>;
+
+        /// Maximum number of notes returned by a single [`Pallet::pull_notes`] page.
+        #[pallet::constant]
+        type MaxNotePage: Get<u32>;
+
+        /// Maximum encoded size in bytes of an encrypted note accepted in a receiver post.
         ///
-        /// An asset present in this transfer has already been spent.
-        AssetSpent,
+        /// # Note
+        ///
+        /// Encrypted notes have a fixed size, so no note is ever larger than that size and this is
+        /// only checked to cover it in
+        /// [`integrity_test`](frame_support::traits::Hooks::integrity_test).
+        #[pallet::constant]
+        type MaxNoteSize: Get<u32>;
 
-        /// Invalid UTXO Accumulator Output
+        /// Maximum encoded size in bytes of the validity proof accepted in a post.
         ///
-        /// The sender was constructed on an invalid version of the ledger state.
-        InvalidUtxoAccumulatorOutput,
+        /// # Note
+        ///
+        /// Groth16 proofs have a fixed size, so no proof is ever larger than that size and this is
+        /// only checked to cover it in
+        /// [`integrity_test`](frame_support::traits::Hooks::integrity_test).
+        #[pallet::constant]
+        type MaxProofSize: Get<u32>;
+
+        /// Maximum number of UTXOs registered across all shards.
+        ///
+        /// Transfers which would register more UTXOs are rejected before any insertion is
+        /// attempted, so this should stay below the capacity of the forest.
+        #[pallet::constant]
+        type MaxUtxoCount: Get<u64>;
+
+        /// Emits [`Event::Mint`], [`Event::PrivateTransfer`], and [`Event::Reclaim`] for every
+        /// post when `true`, or only the minimal [`Event::Posted`] marker when `false`.
+        #[pallet::constant]
+        type EmitDetailedEvents: Get<bool>;
+
+        /// Maximum number of distinct assets a single account can hold a public balance of, or
+        /// `None` for no limit.
+        #[pallet::constant]
+        type MaxAssetsPerAccount: Get<Option<u32>>;
+
+        /// Protocol fee charged per shielded post from the public balance of the signer, or
+        /// `None` for no fee.
+        ///
+        /// # Note
+        ///
+        /// Queued private transfers are charged when they are queued, so the fee is kept even if
+        /// their delayed verification fails.
+        #[pallet::constant]
+        type ShieldedFee: Get<Option<AssetValue>>;
+
+        /// Minimum value of each sink of a [`Pallet::reclaim`], so that reclaims cannot leave
+        /// dust public balances behind.
+        ///
+        /// # Note
+        ///
+        /// Zero-value reclaims are rejected with [`Error::ZeroReclaim`] regardless of this
+        /// minimum, so setting it to zero disables the check.
+        #[pallet::constant]
+        type MinReclaimValue: Get<AssetValue>;
+
+        /// Number of blocks an account has to wait after a [`Pallet::reclaim`] before it can
+        /// reclaim again, or zero for no cooldown.
+        #[pallet::constant]
+        type ReclaimCooldown: Get<Self::BlockNumber>;
+
+        /// Minimum number of UTXOs minted of an asset before any of it can be reclaimed, so that
+        /// early reclaims cannot be linked to the few mints preceding them. Zero disables the
+        /// check.
+        ///
+        /// # Note
+        ///
+        /// Private transfers hide the asset of the UTXOs they register, so only mints are counted
+        /// towards [`UtxoCountPerAsset`].
+        #[pallet::constant]
+        type MinAnonymitySet: Get<u64>;
+
+        /// Low watermarks of the shielded pool per asset, below which a [`Pallet::reclaim`]
+        /// emits [`Event::PoolBalanceLow`].
+        ///
+        /// Use `()` for no watermarks.
+        type PoolLowWatermark: PoolWatermark<Self::AssetId>;
+
+        /// Pallet Id from which the account controlled by the pallet is derived
+        ///
+        /// See [`Pallet::account_id`] and [`PalletAccount`].
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+
+        /// Asset in which the [`Config::ShieldedFee`] is paid.
+        #[pallet::constant]
+        type FeeAssetId: Get<Self::AssetId>;
+
+        /// Account which receives the [`Config::ShieldedFee`].
+        type FeeDestination: Get<Self::AccountId>;
+
+        /// Generator of the public input that transfer proofs are verified against.
+        ///
+        /// Use `()` for the public input layout of the SDK circuits.
+        type ProofInput: GenerateProofInput;
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        #[inline]
+        fn on_idle(_: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            Self::process_pending_posts(remaining_weight)
+        }
+
+        #[cfg(feature = "std")]
+        #[inline]
+        fn integrity_test() {
+            let proof = TransferPost::decode(&mut &*precomputed_coins::MINT)
+                .expect("Sample mint should decode.")
+                .validity_proof;
+            assert!(
+                proof.encoded_size() <= T::MaxProofSize::get() as usize,
+                "`MaxProofSize` should cover the encoded size of a validity proof."
+            );
+            assert!(
+                EncryptedNote::max_encoded_len() <= T::MaxNoteSize::get() as usize,
+                "`MaxNoteSize` should cover the encoded size of an encrypted note."
+            );
+        }
+    }
+
+    /// Public Balance State
+    #[pallet::storage]
+    pub(super) type Balances<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        T::AssetId,
+        AssetValue,
+        ValueQuery,
+    >;
+
+    /// Number of Distinct Assets Held per Account
+    #[pallet::storage]
+    pub(super) type AccountAssetCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Block of the Latest Reclaim per Account
+    ///
+    /// Only recorded while [`Config::ReclaimCooldown`] is nonzero.
+    #[pallet::storage]
+    pub(super) type LastReclaimBlock<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber, OptionQuery>;
+
+    /// Number of Accounts with a Nonzero Public Balance per AssetId
+    #[pallet::storage]
+    pub(super) type HoldersCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AssetId, u32, ValueQuery>;
+
+    /// Total Supply per AssetId
+    #[pallet::storage]
+    pub(super) type TotalSupply<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AssetId, AssetValue, ValueQuery>;
+
+    /// Owner Account per AssetId
+    ///
+    /// Account which was credited with the total supply when the asset was initialized, either at
+    /// genesis or by [`Pallet::initialize_asset`].
+    #[pallet::storage]
+    pub(super) type AssetOwner<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AssetId, T::AccountId, OptionQuery>;
+
+    /// Ids of Destroyed Assets which cannot be Initialized Again
+    #[pallet::storage]
+    pub(super) type RetiredAssetIds<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AssetId, (), ValueQuery>;
+
+    /// Total Value Minted into the Shielded Pool per AssetId
+    #[pallet::storage]
+    pub(super) type TotalMinted<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AssetId, AssetValue, ValueQuery>;
+
+    /// Total Value Reclaimed from the Shielded Pool per AssetId
+    #[pallet::storage]
+    pub(super) type TotalReclaimed<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AssetId, AssetValue, ValueQuery>;
+
+    /// Smallest and Largest Value Minted in a Single Source per AssetId
+    ///
+    /// Mint values are public, so this reveals nothing that [`Event::Mint`] does not.
+    #[pallet::storage]
+    pub(super) type MintValueRange<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AssetId, (AssetValue, AssetValue), OptionQuery>;
+
+    /// Number of UTXOs Minted per AssetId
+    #[pallet::storage]
+    pub(super) type UtxoCountPerAsset<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AssetId, u64, ValueQuery>;
+
+    ///
+    #[pallet::storage]
+    pub(super) type Shards<T: Config> =
+        StorageDoubleMap<_, Identity, u8, Identity, u64, (config::Utxo, EncryptedNote), ValueQuery>;
+
+    /// Shard Trees
+    ///
+    /// # Note
+    ///
+    /// Shards which were never written to read as the default tree, whose `leaf_digest` is
+    /// `None`. A shard is empty exactly when it has no leaf digest, see [`Pallet::shard_is_empty`].
+    #[pallet::storage]
+    pub(super) type ShardTrees<T: Config> =
+        StorageMap<_, Identity, u8, UtxoMerkleTreePath, ValueQuery>;
+
+    ///
+    #[pallet::storage]
+    pub(super) type UtxoAccumulatorOutputs<T: Config> =
+        StorageMap<_, Identity, config::UtxoAccumulatorOutput, (), ValueQuery>;
+
+    /// Latest Root per Shard
+    #[pallet::storage]
+    pub(super) type LatestRoot<T: Config> =
+        StorageMap<_, Identity, u8, config::UtxoAccumulatorOutput, OptionQuery>;
+
+    /// Roots which are the Latest Root of some Shard
+    ///
+    /// Consulted instead of [`UtxoAccumulatorOutputs`] when [`Config::StrictRootMode`] is on.
+    #[pallet::storage]
+    pub(super) type LatestRootSet<T: Config> =
+        StorageMap<_, Identity, config::UtxoAccumulatorOutput, (), ValueQuery>;
+
+    ///
+    #[pallet::storage]
+    pub(super) type UtxoSet<T: Config> = StorageMap<_, Identity, config::Utxo, (), ValueQuery>;
+
+    ///
+    #[pallet::storage]
+    pub(super) type VoidNumberSet<T: Config> =
+        StorageMap<_, Identity, config::VoidNumber, (), ValueQuery>;
+
+    ///
+    #[pallet::storage]
+    pub(super) type VoidNumberSetInsertionOrder<T: Config> =
+        StorageMap<_, Identity, u64, config::VoidNumber, ValueQuery>;
+
+    ///
+    #[pallet::storage]
+    pub(super) type VoidNumberSetSize<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Number of UTXOs Registered across All Shards
+    #[pallet::storage]
+    pub(super) type TotalUtxoCount<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Number of Entries in [`UtxoAccumulatorOutputs`]
+    #[pallet::storage]
+    pub(super) type RootCount<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// UTXO Accumulator Model Override
+    ///
+    /// Encoded UTXO accumulator model used instead of the one shipped with the SDK, if set.
+    #[pallet::storage]
+    pub(super) type UtxoAccumulatorModelOverride<T: Config> = StorageValue<_, Vec<u8>>;
+
+    /// Verifying Context Overrides
+    ///
+    /// Encoded verifying context of each transfer shape used instead of the one shipped with the
+    /// SDK, if set.
+    #[pallet::storage]
+    pub(super) type VerifyingContextOverrides<T: Config> =
+        StorageMap<_, Twox64Concat, PostShape, Vec<u8>>;
+
+    /// Idempotency Keys of the Most Recent Keyed Batches of each Account, Oldest First
+    ///
+    /// Keys are remembered per account, so that accounts can neither block nor evict the keys of
+    /// one another.
+    #[pallet::storage]
+    pub(super) type ProcessedBatches<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<[u8; 32], T::MaxProcessedBatches>,
+        ValueQuery,
+    >;
+
+    /// Private Transfers Waiting for Delayed Verification
+    #[pallet::storage]
+    pub(super) type PendingPosts<T: Config> =
+        StorageValue<_, BoundedVec<(T::AccountId, TransferPost), T::MaxPendingPosts>, ValueQuery>;
+
+    /// Genesis Configuration
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        pub owner: T::AccountId,
+        pub assets: alloc::collections::btree_set::BTreeSet<(T::AssetId, AssetValue)>,
+        pub parameters: GenesisParameters,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        #[inline]
+        fn default() -> Self {
+            /* FIXME: `AccountId` does not implement default!
+            GenesisConfig {
+                owner: Default::default(),
+                assets: Default::default(),
+                parameters: Default::default(),
+            }
+            */
+            todo!()
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        #[inline]
+        fn build(&self) {
+            for (id, value) in &self.assets {
+                assert!(
+                    Pallet::<T>::protocol_asset_id(*id).is_some(),
+                    "Genesis asset ids should fit into the protocol asset id type."
+                );
+                Pallet::<T>::init_asset(&self.owner, *id, *value);
+            }
+            #[cfg(feature = "std")]
+            {
+                let model = self
+                    .parameters
+                    .utxo_accumulator_model
+                    .load()
+                    .expect("The genesis UTXO accumulator model source should be readable.");
+                if let Some(model) = &model {
+                    Pallet::<T>::decode_utxo_accumulator_model(model)
+                        .expect("The genesis UTXO accumulator model should decode.");
+                    UtxoAccumulatorModelOverride::<T>::put(model);
+                }
+                let mut contexts = Vec::new();
+                for (shape, source) in self.parameters.verifying_contexts() {
+                    if let Some(context) = source
+                        .load()
+                        .expect("The genesis verifying context sources should be readable.")
+                    {
+                        Pallet::<T>::decode_verifying_context(&context)
+                            .expect("The genesis verifying contexts should decode.");
+                        VerifyingContextOverrides::<T>::insert(shape, &context);
+                        contexts.push((shape, context));
+                    }
+                }
+                // NOTE: Genesis storage does not support transactions, so the check replays the
+                //       samples in scratch externalities holding the same parameters.
+                sp_io::TestExternalities::new_empty().execute_with(|| {
+                    if let Some(model) = model {
+                        UtxoAccumulatorModelOverride::<T>::put(model);
+                    }
+                    for (shape, context) in contexts {
+                        VerifyingContextOverrides::<T>::insert(shape, context);
+                    }
+                    Pallet::<T>::check_parameters().expect(
+                        "The genesis parameters should be consistent with the verifying contexts.",
+                    );
+                });
+            }
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Transfers public `asset` from `origin` to `target`.
+        #[pallet::weight(T::WeightInfo::transfer())]
+        #[transactional]
+        pub fn transfer(
+            origin: OriginFor<T>,
+            target: <T::Lookup as StaticLookup>::Source,
+            asset: Asset<T::AssetId>,
+        ) -> DispatchResultWithPostInfo {
+            let origin = ensure_signed(origin)?;
+            let target = T::Lookup::lookup(target)?;
+            Self::transfer_public(origin, target, asset)?;
+            Ok(().into())
+        }
+
+        /// Mints some assets encoded in `post` to the `origin` account.
+        #[pallet::weight(T::WeightInfo::mint())]
+        #[transactional]
+        pub fn mint(origin: OriginFor<T>, post: TransferPost) -> DispatchResultWithPostInfo {
+            let origin = ensure_signed(origin)?;
+            Self::post_mint(&origin, post, &mut Self::ledger()?)?;
+            Ok(().into())
+        }
+
+        /// Transfers private assets encoded in `post`.
+        ///
+        /// # Note
+        ///
+        /// In this transaction, `origin` is just signing the `post` and is not necessarily related
+        /// to any of the participants in the transaction itself.
+        #[pallet::weight(T::WeightInfo::private_transfer())]
+        #[transactional]
+        pub fn private_transfer(
+            origin: OriginFor<T>,
+            post: TransferPost,
+        ) -> DispatchResultWithPostInfo {
+            let origin = ensure_signed(origin)?;
+            ensure!(post.asset_id.is_none(), Error::<T>::InvalidShape);
+            Self::ensure_well_formed_notes(&post)?;
+            Self::ensure_well_formed_proof(&post)?;
+            Self::ensure_utxo_capacity(post.receiver_posts.len())?;
+            Self::ensure_shard_capacity(&post)?;
+            Self::charge_shielded_fee(&origin, 1)?;
+            if T::MaxPendingPosts::get() > 0 {
+                return Self::enqueue_post(origin, post);
+            }
+            let mut ledger = Self::ledger()?;
+            let event = ledger.apply_post(vec![], vec![], post)?;
+            Self::deposit_event(event.convert(Some(origin)));
+            Ok(Some(Self::private_transfer_weight(1, ledger.touched_shards)).into())
+        }
+
+        /// Transforms some private assets into public ones using `post`, sending the public assets
+        /// to the `origin` account.
+        #[pallet::weight(T::WeightInfo::reclaim())]
+        #[transactional]
+        pub fn reclaim(origin: OriginFor<T>, post: TransferPost) -> DispatchResultWithPostInfo {
+            let origin = ensure_signed(origin)?;
+            ensure!(post.asset_id.is_some(), Error::<T>::InvalidShape);
+            Self::ensure_can_receive_reclaim(&origin, &post)?;
+            ensure!(
+                post.sinks.iter().all(|value| *value > 0),
+                Error::<T>::ZeroReclaim
+            );
+            ensure!(
+                post.sinks
+                    .iter()
+                    .all(|value| *value >= T::MinReclaimValue::get()),
+                Error::<T>::ReclaimTooSmall
+            );
+            Self::ensure_reclaim_cooled_down(&origin)?;
+            Self::ensure_anonymity_set(&post)?;
+            Self::ensure_well_formed_notes(&post)?;
+            Self::ensure_well_formed_proof(&post)?;
+            Self::ensure_utxo_capacity(post.receiver_posts.len())?;
+            Self::ensure_shard_capacity(&post)?;
+            Self::charge_shielded_fee(&origin, 1)?;
+            Self::record_reclaim(&origin);
+            let id = post.asset_id.map(T::AssetId::from);
+            let pool_before = id.map(Self::pool_balance);
+            let event = Self::ledger()?.apply_post(vec![], vec![origin], post)?;
+            Self::deposit_event(event.convert(None));
+            if let (Some(id), Some(pool_before)) = (id, pool_before) {
+                Self::check_pool_watermark(id, pool_before);
+            }
+            Ok(().into())
+        }
+
+        /// Initializes the asset with the given `id` with a supply of `total`, giving control to
+        /// `owner`.
+        #[pallet::weight(T::WeightInfo::initialize_asset())]
+        #[transactional]
+        pub fn initialize_asset(
+            origin: OriginFor<T>,
+            owner: <T::Lookup as StaticLookup>::Source,
+            id: T::AssetId,
+            total: AssetValue,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            let owner = T::Lookup::lookup(owner)?;
+            ensure!(
+                Self::protocol_asset_id(id).is_some(),
+                Error::<T>::AssetIdOutOfRange
+            );
+            ensure!(
+                !TotalSupply::<T>::contains_key(&id),
+                Error::<T>::AssetInitialized
+            );
+            ensure!(
+                !RetiredAssetIds::<T>::contains_key(&id),
+                Error::<T>::AssetIdRetired
+            );
+            Self::ensure_can_hold(&owner, id)?;
+            Self::init_asset(&owner, id, total);
+            Self::deposit_event(Event::AssetInitialized { id, owner, total });
+            Ok(().into())
+        }
+
+        /// Transfers private assets encoded in each of the `posts`, reverting all of them if any
+        /// one of them fails in [`BatchMode::Atomic`] or skipping the failed ones in
+        /// [`BatchMode::BestEffort`].
+        ///
+        /// # Note
+        ///
+        /// The `posts` are posted in order against the ledger as updated by the previous posts in
+        /// the batch, so they must be independent of one another: no two posts may spend the same
+        /// asset or register the same UTXO. Batched posts are always verified immediately, even
+        /// when delayed verification is enabled.
+        ///
+        /// A batch with an `idempotency_key` is rejected with [`Error::BatchAlreadyProcessed`] if
+        /// the same account submitted a batch with the same key recently, see
+        /// [`Config::MaxProcessedBatches`].
+        #[pallet::weight(
+            T::WeightInfo::private_transfer()
+                .saturating_mul(posts.len() as Weight)
+                .saturating_add(T::DbWeight::get().reads_writes(1, 1))
+        )]
+        #[transactional]
+        pub fn batch_private_transfer(
+            origin: OriginFor<T>,
+            posts: Vec<TransferPost>,
+            mode: BatchMode,
+            idempotency_key: Option<[u8; 32]>,
+        ) -> DispatchResultWithPostInfo {
+            let origin = ensure_signed(origin)?;
+            ensure!(
+                posts.len() <= T::MaxPrivateBatch::get() as usize,
+                Error::<T>::BatchTooLarge
+            );
+            Self::record_batch(&origin, idempotency_key)?;
+            let count = posts.len() as u32;
+            let mut ledger = Self::ledger()?;
+            Self::run_batch(mode, posts, |post| {
+                Self::post_private_transfer(&origin, post, &mut ledger)
+            })?;
+            match mode {
+                BatchMode::Atomic => {
+                    Ok(Some(Self::private_transfer_weight(count, ledger.touched_shards)).into())
+                }
+                BatchMode::BestEffort => Ok(().into()),
+            }
+        }
+
+        /// Transfers each public asset in `transfers` from `origin` to its target, reverting all
+        /// of them if any one of them fails in [`BatchMode::Atomic`] or skipping the failed ones
+        /// in [`BatchMode::BestEffort`].
+        ///
+        /// # Note
+        ///
+        /// As with [`Pallet::batch_private_transfer`], a resubmitted batch with the same
+        /// `idempotency_key` is rejected before any transfer applies.
+        #[pallet::weight(T::WeightInfo::batch_transfer(transfers.len() as u32))]
+        #[transactional]
+        pub fn batch_transfer(
+            origin: OriginFor<T>,
+            transfers: Vec<(<T::Lookup as StaticLookup>::Source, Asset<T::AssetId>)>,
+            mode: BatchMode,
+            idempotency_key: Option<[u8; 32]>,
+        ) -> DispatchResultWithPostInfo {
+            let origin = ensure_signed(origin)?;
+            ensure!(
+                transfers.len() <= T::MaxPublicBatch::get() as usize,
+                Error::<T>::BatchTooLarge
+            );
+            Self::record_batch(&origin, idempotency_key)?;
+            Self::run_batch(mode, transfers, |(target, asset)| {
+                let target = T::Lookup::lookup(target)?;
+                Self::transfer_public(origin.clone(), target, asset)
+            })?;
+            Ok(().into())
+        }
+
+        /// Mints the assets encoded in each of the `posts` to the `origin` account, reverting all
+        /// of them if any one of them fails in [`BatchMode::Atomic`] or skipping the failed ones
+        /// in [`BatchMode::BestEffort`].
+        ///
+        /// # Note
+        ///
+        /// As with [`Pallet::batch_private_transfer`], the `posts` must be independent of one
+        /// another since each is posted against the ledger as updated by the previous ones, and a
+        /// resubmitted batch with the same `idempotency_key` is rejected.
+        #[pallet::weight(T::WeightInfo::batch_mint(posts.len() as u32))]
+        #[transactional]
+        pub fn batch_mint(
+            origin: OriginFor<T>,
+            posts: Vec<TransferPost>,
+            mode: BatchMode,
+            idempotency_key: Option<[u8; 32]>,
+        ) -> DispatchResultWithPostInfo {
+            let origin = ensure_signed(origin)?;
+            ensure!(
+                posts.len() <= T::MaxPrivateBatch::get() as usize,
+                Error::<T>::BatchTooLarge
+            );
+            Self::record_batch(&origin, idempotency_key)?;
+            let mut ledger = Self::ledger()?;
+            Self::run_batch(mode, posts, |post| {
+                Self::post_mint(&origin, post, &mut ledger)
+            })?;
+            Ok(().into())
+        }
+
+        /// Restores the shielded ledger from `snapshot` into an empty ledger, recomputing every
+        /// shard tree from the snapshot entries first if `verify_roots` is set.
+        ///
+        /// # Note
+        ///
+        /// Recomputing the shard trees hashes every imported UTXO, which makes verified imports
+        /// much heavier, so `verify_roots` is only meant for snapshots from untrusted sources.
+        #[pallet::weight(
+            T::WeightInfo::import_ledger(
+                snapshot.shards.len() as u32,
+                snapshot.void_numbers.len() as u32
+            )
+            .saturating_add(T::DbWeight::get().writes(snapshot.asset_entry_count() as Weight))
+            .saturating_add(if *verify_roots {
+                T::WeightInfo::verify_snapshot(snapshot.shards.len() as u32)
+            } else {
+                0
+            })
+        )]
+        #[transactional]
+        pub fn import_ledger(
+            origin: OriginFor<T>,
+            snapshot: LedgerSnapshot,
+            verify_roots: bool,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            let snapshot = snapshot
+                .migrate()
+                .ok_or(Error::<T>::UnsupportedSnapshotVersion)?;
+            ensure!(Self::is_ledger_empty(), Error::<T>::LedgerNotEmpty);
+            if verify_roots {
+                Self::ensure_consistent_snapshot(&snapshot)?;
+            }
+            for (shard_index, leaf_index, utxo, note) in snapshot.shards {
+                Shards::<T>::insert(shard_index, leaf_index, (utxo, note));
+            }
+            for (shard_index, tree) in snapshot.shard_trees {
+                ShardTrees::<T>::insert(shard_index, tree);
+            }
+            TotalUtxoCount::<T>::put(snapshot.utxo_set.len() as u64);
+            for utxo in snapshot.utxo_set {
+                UtxoSet::<T>::insert(utxo, ());
+            }
+            for output in snapshot.utxo_accumulator_outputs {
+                if !UtxoAccumulatorOutputs::<T>::contains_key(output) {
+                    UtxoAccumulatorOutputs::<T>::insert(output, ());
+                    RootCount::<T>::mutate(|count| *count = count.saturating_add(1));
+                }
+            }
+            VoidNumberSetSize::<T>::put(snapshot.void_numbers.len() as u64);
+            for (index, void_number) in snapshot.void_numbers.into_iter().enumerate() {
+                VoidNumberSet::<T>::insert(void_number, ());
+                VoidNumberSetInsertionOrder::<T>::insert(index as u64, void_number);
+            }
+            for (shard_index, root) in snapshot.latest_roots {
+                LatestRoot::<T>::insert(shard_index, root);
+                LatestRootSet::<T>::insert(root, ());
+            }
+            for (id, value) in snapshot.total_minted {
+                TotalMinted::<T>::insert(Self::runtime_asset_id(id)?, value);
+            }
+            for (id, value) in snapshot.total_reclaimed {
+                TotalReclaimed::<T>::insert(Self::runtime_asset_id(id)?, value);
+            }
+            for (id, range) in snapshot.mint_value_ranges {
+                MintValueRange::<T>::insert(Self::runtime_asset_id(id)?, range);
+            }
+            for (id, count) in snapshot.utxo_counts {
+                UtxoCountPerAsset::<T>::insert(Self::runtime_asset_id(id)?, count);
+            }
+            Self::deposit_event(Event::LedgerImported);
+            Ok(().into())
+        }
+
+        /// Replaces the UTXO accumulator model used for verification with the encoded `model`.
+        ///
+        /// # Note
+        ///
+        /// Coins registered under the previous model can no longer be spent, so this is only meant
+        /// for coordinated parameter upgrades.
+        #[pallet::weight(T::WeightInfo::set_utxo_accumulator_model())]
+        #[transactional]
+        pub fn set_utxo_accumulator_model(
+            origin: OriginFor<T>,
+            model: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Self::decode_utxo_accumulator_model(&model)?;
+            UtxoAccumulatorModelOverride::<T>::put(model);
+            Self::deposit_event(Event::ParametersUpdated {
+                fingerprint: Self::parameters_fingerprint(),
+            });
+            Ok(().into())
+        }
+
+        /// Destroys the asset with the given `id`, whose whole supply must be held publicly by
+        /// `owner`, and retires the `id` so that it cannot be initialized again.
+        ///
+        /// # Note
+        ///
+        /// Retired ids can only be reused after [`Pallet::allow_asset_id_reuse`], so that events
+        /// and client caches referring to the destroyed asset are not conflated with a new one.
+        #[pallet::weight(T::WeightInfo::destroy_asset())]
+        #[transactional]
+        pub fn destroy_asset(
+            origin: OriginFor<T>,
+            owner: <T::Lookup as StaticLookup>::Source,
+            id: T::AssetId,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            let owner = T::Lookup::lookup(owner)?;
+            let supply =
+                TotalSupply::<T>::try_get(&id).map_err(|_| Error::<T>::UninitializedSupply)?;
+            ensure!(
+                Balances::<T>::get(&owner, id) == supply,
+                Error::<T>::SupplyOutstanding
+            );
+            Self::adjust_balance(&owner, id, BalanceDelta::Debit(supply))
+                .ok_or(Error::<T>::BalanceLow)?;
+            TotalSupply::<T>::remove(id);
+            TotalMinted::<T>::remove(id);
+            TotalReclaimed::<T>::remove(id);
+            MintValueRange::<T>::remove(id);
+            UtxoCountPerAsset::<T>::remove(id);
+            AssetOwner::<T>::remove(id);
+            RetiredAssetIds::<T>::insert(id, ());
+            Self::deposit_event(Event::AssetDestroyed { id, owner });
+            Ok(().into())
+        }
+
+        /// Allows the retired asset `id` to be initialized again.
+        #[pallet::weight(T::WeightInfo::allow_asset_id_reuse())]
+        #[transactional]
+        pub fn allow_asset_id_reuse(
+            origin: OriginFor<T>,
+            id: T::AssetId,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            RetiredAssetIds::<T>::remove(id);
+            Self::deposit_event(Event::AssetIdReuseAllowed { id });
+            Ok(().into())
+        }
+
+        /// Sets the public balance of `who` for the asset with the given `id` to `value`,
+        /// adjusting [`TotalSupply`] by the difference.
+        ///
+        /// # Note
+        ///
+        /// The supply is adjusted with checked arithmetic, so a balance change which would move
+        /// the total supply out of range is rejected instead of wrapping.
+        #[pallet::weight(T::WeightInfo::force_set_balance())]
+        #[transactional]
+        pub fn force_set_balance(
+            origin: OriginFor<T>,
+            who: <T::Lookup as StaticLookup>::Source,
+            id: T::AssetId,
+            value: AssetValue,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            let who = T::Lookup::lookup(who)?;
+            let supply =
+                TotalSupply::<T>::try_get(&id).map_err(|_| Error::<T>::UninitializedSupply)?;
+            let balance = Balances::<T>::get(&who, id);
+            if value >= balance {
+                let delta = value - balance;
+                let supply = supply
+                    .checked_add(delta)
+                    .ok_or(Error::<T>::SupplyAdjustmentOverflow)?;
+                Self::ensure_can_hold(&who, id)?;
+                Self::adjust_balance(&who, id, BalanceDelta::Credit(delta));
+                TotalSupply::<T>::insert(id, supply);
+            } else {
+                let delta = balance - value;
+                let supply = supply
+                    .checked_sub(delta)
+                    .ok_or(Error::<T>::SupplyAdjustmentUnderflow)?;
+                Self::adjust_balance(&who, id, BalanceDelta::Debit(delta))
+                    .ok_or(Error::<T>::BalanceLow)?;
+                TotalSupply::<T>::insert(id, supply);
+            }
+            Self::deposit_event(Event::BalanceSet { id, who, value });
+            Ok(().into())
+        }
+
+        /// Recomputes the total supply of the asset with the given `id` as the sum of its public
+        /// balances and its shielded pool, overwriting the stored total.
+        ///
+        /// # Note
+        ///
+        /// Public balances are keyed by account first, so this scans every entry of [`Balances`].
+        /// The caller declares an upper bound on the number of entries as `max_balances`, which
+        /// prices the call, and the call fails with [`Error::BalanceWitnessTooLow`] if there are
+        /// more entries than declared.
+        #[pallet::weight(T::DbWeight::get().reads_writes(*max_balances as Weight + 3, 1))]
+        #[transactional]
+        pub fn reconcile_total_supply(
+            origin: OriginFor<T>,
+            id: T::AssetId,
+            max_balances: u32,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            let previous =
+                TotalSupply::<T>::try_get(&id).map_err(|_| Error::<T>::UninitializedSupply)?;
+            let mut scanned = 0u32;
+            let mut public: AssetValue = 0;
+            for (_, asset_id, balance) in Balances::<T>::iter() {
+                scanned += 1;
+                ensure!(scanned <= max_balances, Error::<T>::BalanceWitnessTooLow);
+                if asset_id == id {
+                    public = public
+                        .checked_add(balance)
+                        .ok_or(Error::<T>::SupplyAdjustmentOverflow)?;
+                }
+            }
+            let shielded = TotalMinted::<T>::get(id)
+                .checked_sub(TotalReclaimed::<T>::get(id))
+                .ok_or(Error::<T>::SupplyAdjustmentUnderflow)?;
+            let total = public
+                .checked_add(shielded)
+                .ok_or(Error::<T>::SupplyAdjustmentOverflow)?;
+            TotalSupply::<T>::insert(id, total);
+            Self::deposit_event(Event::SupplyReconciled {
+                id,
+                previous,
+                total,
+            });
+            Ok(Some(T::DbWeight::get().reads_writes(scanned as Weight + 3, 1)).into())
+        }
+    }
+
+    // NOTE: Events and errors are encoded by variant index, so new variants are appended to keep
+    //       the encoding of existing variants stable for clients and indexers.
+
+    /// Event
+    #[pallet::event]
+    #[pallet::generate_deposit(fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Transfer Event
+        Transfer {
+            /// Asset Transfered
+            asset: Asset<T::AssetId>,
+
+            /// Source Account
+            source: T::AccountId,
+
+            /// Sink Account
+            sink: T::AccountId,
+        },
+
+        /// Mint Event
+        Mint {
+            /// Asset Minted
+            asset: Asset<T::AssetId>,
+
+            /// Source Account
+            source: T::AccountId,
+        },
+
+        /// Private Transfer Event
+        PrivateTransfer {
+            /// Origin Account
+            origin: T::AccountId,
+        },
+
+        /// Reclaim Event
+        Reclaim {
+            /// Asset Reclaimed
+            asset: Asset<T::AssetId>,
+
+            /// Sink Account
+            sink: T::AccountId,
+        },
+
+        /// Asset Initialized Event
+        AssetInitialized {
+            /// Asset Id
+            id: T::AssetId,
+
+            /// Owner Account
+            owner: T::AccountId,
+
+            /// Total Supply
+            total: AssetValue,
+        },
+
+        /// Post Queued Event
+        ///
+        /// A private transfer was accepted for delayed verification.
+        PostQueued {
+            /// Origin Account
+            origin: T::AccountId,
+        },
+
+        /// Pending Post Dropped Event
+        ///
+        /// A private transfer waiting for delayed verification failed to post.
+        PendingPostDropped {
+            /// Origin Account
+            origin: T::AccountId,
+
+            /// Posting Error
+            error: DispatchError,
+        },
+
+        /// Posted Event
+        ///
+        /// A transfer was posted while detailed events are disabled by
+        /// [`Config::EmitDetailedEvents`].
+        Posted,
+
+        /// Parameters Updated Event
+        ParametersUpdated {
+            /// New Parameters Fingerprint
+            fingerprint: [u8; 32],
+        },
+
+        /// Ledger Imported Event
+        LedgerImported,
+
+        /// Asset Destroyed Event
+        AssetDestroyed {
+            /// Asset Id
+            id: T::AssetId,
+
+            /// Owner Account which Held the Supply
+            owner: T::AccountId,
+        },
+
+        /// Balance Set Event
+        BalanceSet {
+            /// Asset Id
+            id: T::AssetId,
+
+            /// Account whose Balance was Set
+            who: T::AccountId,
+
+            /// New Public Balance
+            value: AssetValue,
+        },
+
+        /// Supply Reconciled Event
+        SupplyReconciled {
+            /// Asset Id
+            id: T::AssetId,
+
+            /// Total Supply before Reconciliation
+            previous: AssetValue,
+
+            /// Reconciled Total Supply
+            total: AssetValue,
+        },
+
+        /// Pool Balance Low Event
+        ///
+        /// A reclaim took the shielded pool of an asset below its [`Config::PoolLowWatermark`].
+        PoolBalanceLow {
+            /// Asset Id
+            id: T::AssetId,
+
+            /// Shielded Pool Balance after the Reclaim
+            balance: AssetValue,
+        },
+
+        /// Batch Leg Outcome Event
+        ///
+        /// The leg of a [`BatchMode::BestEffort`] batch with the given `index` was applied, or was
+        /// skipped and reverted with the error in `result`.
+        BatchLegOutcome {
+            /// Index of the Leg in the Batch
+            index: u32,
+
+            /// Outcome of the Leg
+            result: DispatchResult,
+        },
+
+        /// Asset Id Reuse Allowed Event
+        AssetIdReuseAllowed {
+            /// Asset Id
+            id: T::AssetId,
+        },
+    }
+
+    /// Error
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Uninitialized Supply
+        ///
+        /// Supply of the given Asset Id has not yet been initialized.
+        UninitializedSupply,
+
+        /// Zero Transfer
+        ///
+        /// Public transfers cannot include amounts equal to zero.
+        ZeroTransfer,
+
+        /// Balance Low
+        ///
+        /// Attempted to withdraw from balance which was smaller than the withdrawl amount.
+        BalanceLow,
+
+        /// Invalid Shape
+        ///
+        /// The transfer had an invalid shape.
+        InvalidShape,
+
+        /// Asset Spent
+        ///
+        /// An asset present in this transfer has already been spent.
+        AssetSpent,
+
+        /// Invalid UTXO Accumulator Output
+        ///
+        /// The sender was constructed on an invalid version of the ledger state.
+        InvalidUtxoAccumulatorOutput,
+
+        /// Asset Registered
+        ///
+        /// An asset present in this transfer has already been registered to the ledger.
+        AssetRegistered,
+
+        /// Duplicate Spend
+        ///
+        /// There were multiple spend entries for the same underlying asset in this transfer.
+        DuplicateSpend,
+
+        /// Duplicate Register
+        ///
+        /// There were multiple register entries for the same underlying asset in this transfer.
+        DuplicateRegister,
+
+        /// Invalid Proof
+        ///
+        /// The submitted proof did not pass validation, or errored during validation.
+        InvalidProof,
+
+        /// Parameters Unavailable
+        ///
+        /// The protocol parameters required by the ledger could not be decoded.
+        ParametersUnavailable,
+
+        /// Asset Initialized
+        ///
+        /// Supply of the given Asset Id has already been initialized.
+        AssetInitialized,
+
+        /// Zero Mint
+        ///
+        /// Mints cannot include amounts equal to zero.
+        ZeroMint,
+
+        /// Pending Posts Full
+        ///
+        /// The delayed verification queue has reached [`Config::MaxPendingPosts`].
+        PendingPostsFull,
+
+        /// Batch Too Large
+        ///
+        /// The batch contained more elements than [`Config::MaxPrivateBatch`] or
+        /// [`Config::MaxPublicBatch`] allow.
+        BatchTooLarge,
+
+        /// Ledger Inconsistent
+        ///
+        /// Posting a validated transfer found the ledger in a state that validation should have
+        /// ruled out. The transfer is reverted and the context is logged.
+        LedgerInconsistent,
+
+        /// Ledger Full
+        ///
+        /// Registering the UTXOs of this transfer would exceed [`Config::MaxUtxoCount`].
+        LedgerFull,
+
+        /// Zero Reclaim
+        ///
+        /// Reclaims cannot include amounts equal to zero.
+        ZeroReclaim,
+
+        /// Too Many Assets
+        ///
+        /// Crediting the account would exceed [`Config::MaxAssetsPerAccount`].
+        TooManyAssets,
+
+        /// Unsupported Snapshot Version
+        ///
+        /// The ledger snapshot was written in a format this pallet cannot read.
+        UnsupportedSnapshotVersion,
+
+        /// Ledger Not Empty
+        ///
+        /// Ledger snapshots can only be imported into an empty ledger.
+        LedgerNotEmpty,
+
+        /// Malformed Note
+        ///
+        /// An encrypted note in one of the receiver posts has an ephemeral public key that no
+        /// receiver could use to decrypt it.
+        MalformedNote,
+
+        /// Cannot Pay Fee
+        ///
+        /// The signer does not have enough public balance of the fee asset to pay the shielded
+        /// fee.
+        CannotPayFee,
+
+        /// Inconsistent Parameters
+        ///
+        /// The precomputed sample transfers do not verify against the protocol parameters, so
+        /// the parameters and the verifying contexts were not generated together.
+        InconsistentParameters,
+
+        /// Public Transfers Disabled
+        ///
+        /// Public transfers are disabled in this runtime, so assets can only move through the
+        /// shielded pool.
+        PublicTransfersDisabled,
+
+        /// Supply Outstanding
+        ///
+        /// Assets can only be destroyed once their whole supply is held publicly by one account.
+        SupplyOutstanding,
+
+        /// Asset Id Retired
+        ///
+        /// The asset id belonged to a destroyed asset and has not been allowed for reuse.
+        AssetIdRetired,
+
+        /// Supply Adjustment Overflow
+        ///
+        /// Raising the balance or reconciling the supply would increase the total supply beyond
+        /// the maximum asset value.
+        SupplyAdjustmentOverflow,
+
+        /// Supply Adjustment Underflow
+        ///
+        /// Lowering the balance would decrease the total supply below zero, or more value was
+        /// reclaimed from the shielded pool than was minted into it.
+        SupplyAdjustmentUnderflow,
+
+        /// Reclaim Too Small
+        ///
+        /// A reclaim sink is below [`Config::MinReclaimValue`].
+        ReclaimTooSmall,
+
+        /// Snapshot Inconsistent
+        ///
+        /// The shard trees of an imported ledger snapshot do not match its shard entries, or their
+        /// roots are missing from its UTXO accumulator outputs.
+        SnapshotInconsistent,
+
+        /// Reclaim Cooldown Active
+        ///
+        /// The signer reclaimed less than [`Config::ReclaimCooldown`] blocks ago.
+        ReclaimCooldownActive,
+
+        /// Balance Witness Too Low
+        ///
+        /// There are more public balances than the caller declared.
+        BalanceWitnessTooLow,
+
+        /// Anonymity Set Too Small
+        ///
+        /// Fewer than [`Config::MinAnonymitySet`] UTXOs of the reclaimed asset have been minted.
+        AnonymitySetTooSmall,
+
+        /// Malformed Proof
+        ///
+        /// The validity proof is degenerate, so it was rejected without being verified.
+        MalformedProof,
+
+        /// Batch Already Processed
+        ///
+        /// The signer recently submitted a batch with the same idempotency key.
+        BatchAlreadyProcessed,
+
+        /// Asset Id Out of Range
+        ///
+        /// The asset id does not convert between the runtime and protocol asset id types without
+        /// changing its value.
+        AssetIdOutOfRange,
+
+        /// Shard Full
+        ///
+        /// The shard of one of the receivers has no room left for the receivers placed into it.
+        ShardFull,
+    }
+
+    impl<T> From<InvalidSourceAccount<T::AccountId>> for Error<T>
+    where
+        T: Config,
+    {
+        #[inline]
+        fn from(err: InvalidSourceAccount<T::AccountId>) -> Self {
+            match err.balance {
+                AccountBalance::Known(_) => Self::BalanceLow,
+                AccountBalance::UnknownAccount => {
+                    unreachable!("Accounts are checked before reaching this point.")
+                }
+            }
+        }
+    }
+
+    impl<T> From<InvalidSinkAccount<T::AccountId>> for Error<T>
+    where
+        T: Config,
+    {
+        #[inline]
+        fn from(err: InvalidSinkAccount<T::AccountId>) -> Self {
+            let _ = err;
+            unimplemented!("Accounts are checked before reaching this point.")
+        }
+    }
+
+    impl<T> From<SenderPostError> for Error<T> {
+        #[inline]
+        fn from(err: SenderPostError) -> Self {
+            match err {
+                SenderPostError::AssetSpent => Self::AssetSpent,
+                SenderPostError::InvalidUtxoAccumulatorOutput => Self::InvalidUtxoAccumulatorOutput,
+            }
+        }
+    }
+
+    impl<T> From<ReceiverPostError> for Error<T> {
+        #[inline]
+        fn from(err: ReceiverPostError) -> Self {
+            match err {
+                ReceiverPostError::AssetRegistered => Self::AssetRegistered,
+            }
+        }
+    }
+
+    impl<T> From<TransferPostError<T::AccountId>> for Error<T>
+    where
+        T: Config,
+    {
+        #[inline]
+        fn from(err: TransferPostError<T::AccountId>) -> Self {
+            match err {
+                TransferPostError::InvalidShape => Self::InvalidShape,
+                TransferPostError::InvalidSourceAccount(err) => err.into(),
+                TransferPostError::InvalidSinkAccount(err) => err.into(),
+                TransferPostError::Sender(err) => err.into(),
+                TransferPostError::Receiver(err) => err.into(),
+                TransferPostError::DuplicateSpend => Self::DuplicateSpend,
+                TransferPostError::DuplicateRegister => Self::DuplicateRegister,
+                TransferPostError::InvalidProof => Self::InvalidProof,
+            }
+        }
+    }
+}
+
+impl<T> Pallet<T>
+where
+    T: Config,
+{
+    /// Initializes `asset_id` with a supply of `total`, giving control to `owner`.
+    ///
+    /// # Note
+    ///
+    /// This function does not emit any events since it is also called during genesis. The
+    /// [`Pallet::initialize_asset`] extrinsic emits [`Event::AssetInitialized`] instead.
+    #[inline]
+    fn init_asset(owner: &T::AccountId, asset_id: T::AssetId, total: AssetValue) {
+        TotalSupply::<T>::insert(asset_id, total);
+        AssetOwner::<T>::insert(asset_id, owner);
+        Self::adjust_balance(owner, asset_id, BalanceDelta::Credit(total));
+    }
+
+    /// Ensures that `account` can be credited with the asset `id` without exceeding
+    /// [`Config::MaxAssetsPerAccount`]. Assets already held by `account` can always be credited.
+    #[inline]
+    fn ensure_can_hold(account: &T::AccountId, id: T::AssetId) -> Result<(), Error<T>> {
+        if let Some(max) = T::MaxAssetsPerAccount::get() {
+            ensure!(
+                Balances::<T>::contains_key(account, id)
+                    || AccountAssetCount::<T>::get(account) < max,
+                Error::<T>::TooManyAssets
+            );
+        }
+        Ok(())
+    }
+
+    /// Ensures that `account` can be credited with the asset reclaimed by `post`, so that doomed
+    /// reclaims are rejected before their proofs are verified.
+    ///
+    /// # Note
+    ///
+    /// The asset must be initialized, since no value of an uninitialized asset can be in the
+    /// shielded pool, and crediting it must not exceed [`Config::MaxAssetsPerAccount`].
+    #[inline]
+    fn ensure_can_receive_reclaim(
+        account: &T::AccountId,
+        post: &TransferPost,
+    ) -> Result<(), Error<T>> {
+        if let Some(id) = post.asset_id {
+            let id = Self::runtime_asset_id(id)?;
+            ensure!(
+                TotalSupply::<T>::contains_key(id),
+                Error::<T>::UninitializedSupply
+            );
+            Self::ensure_can_hold(account, id)?;
+        }
+        Ok(())
+    }
+
+    /// Ensures that `account` did not reclaim within the last [`Config::ReclaimCooldown`] blocks.
+    #[inline]
+    fn ensure_reclaim_cooled_down(account: &T::AccountId) -> Result<(), Error<T>> {
+        if let Some(last) = LastReclaimBlock::<T>::get(account) {
+            ensure!(
+                frame_system::Pallet::<T>::block_number()
+                    >= last.saturating_add(T::ReclaimCooldown::get()),
+                Error::<T>::ReclaimCooldownActive
+            );
+        }
+        Ok(())
+    }
+
+    /// Runs `leg` on each of the `legs` of a batch in order.
+    ///
+    /// # Note
+    ///
+    /// In [`BatchMode::Atomic`] the first failing leg fails the batch, which the caller reverts.
+    /// In [`BatchMode::BestEffort`] every leg runs in its own storage transaction, so a failing
+    /// leg is reverted on its own, and the outcome of every leg is reported with
+    /// [`Event::BatchLegOutcome`].
+    #[inline]
+    fn run_batch<L, F>(mode: BatchMode, legs: Vec<L>, mut leg: F) -> DispatchResult
+    where
+        F: FnMut(L) -> DispatchResult,
+    {
+        for (index, item) in legs.into_iter().enumerate() {
+            match mode {
+                BatchMode::Atomic => leg(item)?,
+                BatchMode::BestEffort => {
+                    let result = with_transaction(|| {
+                        let result = leg(item);
+                        if result.is_ok() {
+                            TransactionOutcome::Commit(result)
+                        } else {
+                            TransactionOutcome::Rollback(result)
+                        }
+                    });
+                    Self::deposit_event(Event::BatchLegOutcome {
+                        index: index as u32,
+                        result,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks and posts the mint `post` funded by `origin` to `ledger`.
+    #[inline]
+    fn post_mint(
+        origin: &T::AccountId,
+        post: TransferPost,
+        ledger: &mut Ledger<T>,
+    ) -> DispatchResult {
+        Self::ensure_valid_mint(&post)?;
+        Self::ensure_mintable_asset(&post)?;
+        Self::ensure_well_formed_notes(&post)?;
+        Self::ensure_well_formed_proof(&post)?;
+        Self::ensure_utxo_capacity(post.receiver_posts.len())?;
+        Self::ensure_shard_capacity(&post)?;
+        Self::charge_shielded_fee(origin, 1)?;
+        let event = ledger.apply_post(vec![origin.clone()], vec![], post)?;
+        Self::deposit_event(event.convert(None));
+        Ok(())
+    }
+
+    /// Checks and posts the private transfer `post` submitted by `origin` to `ledger`, verifying
+    /// it immediately even when delayed verification is enabled.
+    #[inline]
+    fn post_private_transfer(
+        origin: &T::AccountId,
+        post: TransferPost,
+        ledger: &mut Ledger<T>,
+    ) -> DispatchResult {
+        ensure!(post.asset_id.is_none(), Error::<T>::InvalidShape);
+        Self::ensure_well_formed_notes(&post)?;
+        Self::ensure_well_formed_proof(&post)?;
+        Self::ensure_utxo_capacity(post.receiver_posts.len())?;
+        Self::ensure_shard_capacity(&post)?;
+        Self::charge_shielded_fee(origin, 1)?;
+        let event = ledger.apply_post(vec![], vec![], post)?;
+        Self::deposit_event(event.convert(Some(origin.clone())));
+        Ok(())
+    }
+
+    /// Records the `idempotency_key` of a batch submitted by `account` in [`ProcessedBatches`],
+    /// forgetting the oldest key of `account` if its keys are full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::BatchAlreadyProcessed`] if `account` submitted a batch with the same key
+    /// which is still remembered. Batches without a key are never rejected.
+    #[inline]
+    fn record_batch(account: &T::AccountId, idempotency_key: Option<[u8; 32]>) -> DispatchResult {
+        let key = match idempotency_key {
+            Some(key) if T::MaxProcessedBatches::get() > 0 => key,
+            _ => return Ok(()),
+        };
+        ProcessedBatches::<T>::try_mutate(account, |processed| -> DispatchResult {
+            ensure!(!processed.contains(&key), Error::<T>::BatchAlreadyProcessed);
+            if processed.len() >= T::MaxProcessedBatches::get() as usize {
+                processed.remove(0);
+            }
+            processed
+                .try_push(key)
+                .expect("A slot was freed if the keys were full.");
+            Ok(())
+        })
+    }
+
+    /// Ensures that at least [`Config::MinAnonymitySet`] UTXOs of the asset reclaimed by `post`
+    /// have been minted.
+    #[inline]
+    fn ensure_anonymity_set(post: &TransferPost) -> Result<(), Error<T>> {
+        if let Some(id) = post.asset_id {
+            ensure!(
+                UtxoCountPerAsset::<T>::get(T::AssetId::from(id)) >= T::MinAnonymitySet::get(),
+                Error::<T>::AnonymitySetTooSmall
+            );
+        }
+        Ok(())
+    }
+
+    /// Records the current block as the latest reclaim of `account` if
+    /// [`Config::ReclaimCooldown`] is nonzero.
+    #[inline]
+    fn record_reclaim(account: &T::AccountId) {
+        if !T::ReclaimCooldown::get().is_zero() {
+            LastReclaimBlock::<T>::insert(account, frame_system::Pallet::<T>::block_number());
+        }
+    }
+
+    /// Applies `delta` to the public balance of `account` for the asset `id`.
+    ///
+    /// # Note
+    ///
+    /// This is the only place where [`Balances`] entries are created or removed, so that
+    /// [`AccountAssetCount`] and [`HoldersCount`] count an asset and its holder exactly when the
+    /// entry is created and uncount them exactly when a debit drains it. Zero deltas are skipped,
+    /// so `Balances` never holds zero entries. Returns `None` without changing anything if a
+    /// debit exceeds the balance. Credits saturate instead of wrapping, although they cannot
+    /// overflow while the balances of an asset sum to at most its [`TotalSupply`].
+    #[inline]
+    fn adjust_balance(account: &T::AccountId, id: T::AssetId, delta: BalanceDelta) -> Option<()> {
+        match delta {
+            BalanceDelta::Credit(0) | BalanceDelta::Debit(0) => {}
+            BalanceDelta::Credit(value) => {
+                Balances::<T>::mutate_exists(account, id, |balance| {
+                    if balance.is_none() {
+                        AccountAssetCount::<T>::mutate(account, |count| {
+                            *count = count.saturating_add(1)
+                        });
+                        HoldersCount::<T>::mutate(id, |count| *count = count.saturating_add(1));
+                    }
+                    *balance = Some(balance.unwrap_or_default().saturating_add(value));
+                });
+            }
+            BalanceDelta::Debit(value) => {
+                let remaining = Balances::<T>::get(account, id).checked_sub(value)?;
+                if remaining != 0 {
+                    Balances::<T>::insert(account, id, remaining);
+                } else if Balances::<T>::take(account, id) != 0 {
+                    AccountAssetCount::<T>::mutate_exists(account, |count| {
+                        *count = count
+                            .map(|count| count.saturating_sub(1))
+                            .filter(|count| *count != 0);
+                    });
+                    HoldersCount::<T>::mutate_exists(id, |count| {
+                        *count = count
+                            .map(|count| count.saturating_sub(1))
+                            .filter(|count| *count != 0);
+                    });
+                }
+            }
+        }
+        Some(())
+    }
+
+    /// Transfers public `asset` from `source` to `target`, emitting [`Event::Transfer`].
+    #[inline]
+    fn transfer_public(
+        source: T::AccountId,
+        target: T::AccountId,
+        asset: Asset<T::AssetId>,
+    ) -> DispatchResult {
+        ensure!(
+            T::PublicTransfersEnabled::get(),
+            Error::<T>::PublicTransfersDisabled
+        );
+        let id = asset.id;
+        ensure!(
+            TotalSupply::<T>::contains_key(&id),
+            Error::<T>::UninitializedSupply
+        );
+        ensure!(asset.value > 0, Error::<T>::ZeroTransfer);
+        Self::ensure_can_hold(&target, id)?;
+        Self::adjust_balance(&source, id, BalanceDelta::Debit(asset.value))
+            .ok_or(Error::<T>::BalanceLow)?;
+        Self::adjust_balance(&target, id, BalanceDelta::Credit(asset.value));
+        Self::deposit_event(Event::Transfer {
+            asset,
+            source,
+            sink: target,
+        });
+        Ok(())
+    }
+
+    /// Returns the account controlled by the pallet, derived from [`Config::PalletId`].
+    #[inline]
+    pub fn account_id() -> T::AccountId {
+        T::PalletId::get().into_account()
+    }
+
+    /// Returns the balance of `account` for the asset with the given `id`.
+    #[inline]
+    pub fn balance(account: T::AccountId, id: T::AssetId) -> AssetValue {
+        Balances::<T>::get(account, id)
+    }
+
+    /// Returns the total supply of the asset with the given `id`.
+    #[inline]
+    pub fn total_supply(id: T::AssetId) -> AssetValue {
+        TotalSupply::<T>::get(id)
+    }
+
+    /// Returns the account which owned the asset with the given `id` when it was initialized.
+    #[inline]
+    pub fn asset_owner(id: T::AssetId) -> Option<T::AccountId> {
+        AssetOwner::<T>::get(id)
+    }
+
+    /// Returns the number of accounts with a nonzero public balance of the asset with the given
+    /// `id`.
+    #[inline]
+    pub fn total_accounts_holding(id: T::AssetId) -> u32 {
+        HoldersCount::<T>::get(id)
+    }
+
+    /// Returns the total value of the asset with the given `id` ever minted into the shielded
+    /// pool.
+    #[inline]
+    pub fn total_minted(id: T::AssetId) -> AssetValue {
+        TotalMinted::<T>::get(id)
+    }
+
+    /// Returns the total value of the asset with the given `id` ever reclaimed from the shielded
+    /// pool.
+    #[inline]
+    pub fn total_reclaimed(id: T::AssetId) -> AssetValue {
+        TotalReclaimed::<T>::get(id)
+    }
+
+    /// Returns the value of the asset with the given `id` held in the shielded pool, which is the
+    /// value minted minus the value reclaimed.
+    #[inline]
+    pub fn pool_balance(id: T::AssetId) -> AssetValue {
+        TotalMinted::<T>::get(id).saturating_sub(TotalReclaimed::<T>::get(id))
+    }
+
+    /// Emits [`Event::PoolBalanceLow`] if the shielded pool of the asset `id` was at or above its
+    /// [`Config::PoolLowWatermark`] at `pool_before` and is now below it, so that the event fires
+    /// once per crossing instead of on every reclaim below the watermark.
+    #[inline]
+    fn check_pool_watermark(id: T::AssetId, pool_before: AssetValue) {
+        if let Some(watermark) = T::PoolLowWatermark::low_watermark(id) {
+            let balance = Self::pool_balance(id);
+            if pool_before >= watermark && balance < watermark {
+                Self::deposit_event(Event::PoolBalanceLow { id, balance });
+            }
+        }
+    }
+
+    /// Returns the smallest and largest value of the asset with the given `id` minted by a
+    /// single source, or `None` if it was never minted.
+    #[inline]
+    pub fn mint_value_range(id: T::AssetId) -> Option<(AssetValue, AssetValue)> {
+        MintValueRange::<T>::get(id)
+    }
+
+    /// Checks that the supply of every asset is split between the public balances and the
+    /// shielded pool, whose balance is the value minted minus the value reclaimed.
+    ///
+    /// # Note
+    ///
+    /// This iterates over every public balance, so it is only meant for `try-runtime` checks and
+    /// tests.
+    #[inline]
+    pub fn do_try_state() -> Result<(), &'static str> {
+        for (id, supply) in TotalSupply::<T>::iter() {
+            let public = Balances::<T>::iter()
+                .filter(|(_, asset_id, _)| *asset_id == id)
+                .try_fold(0, |sum: AssetValue, (_, _, balance)| {
+                    sum.checked_add(balance)
+                })
+                .ok_or("Public balances overflow the asset value type.")?;
+            let shielded = TotalMinted::<T>::get(id)
+                .checked_sub(TotalReclaimed::<T>::get(id))
+                .ok_or("More value was reclaimed than was minted.")?;
+            ensure!(
+                public.checked_add(shielded) == Some(supply),
+                "Public and shielded balances do not add up to the total supply."
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks the invariants of the ledger counters for the asset with the given `id`, reading a
+    /// constant number of storage items.
+    ///
+    /// # Note
+    ///
+    /// Unlike [`do_try_state`](Self::do_try_state), this does not compare the counters with the
+    /// items they count, so it only catches counters which contradict each other. Its cost is
+    /// [`WeightInfo::quick_invariant_check`], so it can be run periodically in production.
+    #[inline]
+    pub fn quick_invariant_check(id: T::AssetId) -> Result<(), &'static str> {
+        let shielded = TotalMinted::<T>::get(id)
+            .checked_sub(TotalReclaimed::<T>::get(id))
+            .ok_or("More value was reclaimed than was minted.")?;
+        ensure!(
+            shielded <= TotalSupply::<T>::get(id),
+            "The shielded pool exceeds the total supply."
+        );
+        let void_number_count = VoidNumberSetSize::<T>::get();
+        ensure!(
+            !VoidNumberSetInsertionOrder::<T>::contains_key(void_number_count),
+            "The void number set is larger than its recorded size."
+        );
+        ensure!(
+            void_number_count == 0
+                || VoidNumberSetInsertionOrder::<T>::contains_key(void_number_count - 1),
+            "The void number set is smaller than its recorded size."
+        );
+        ensure!(
+            TotalUtxoCount::<T>::get() == 0 || RootCount::<T>::get() > 0,
+            "UTXOs are registered without any accumulator output."
+        );
+        Ok(())
+    }
+
+    /// Returns the storage key of the public balance of `account` for the asset with the given
+    /// `id`.
+    ///
+    /// # Note
+    ///
+    /// Light clients request a read proof for this key from a full node and check it against a
+    /// trusted state root, see `client::verify_balance_proof` with the `client` feature.
+    #[inline]
+    pub fn balance_storage_key(account: T::AccountId, id: T::AssetId) -> Vec<u8> {
+        Balances::<T>::hashed_key_for(account, id)
+    }
+
+    /// Returns the protocol [`AssetId`] corresponding to the runtime asset `id`, if it exists.
+    #[inline]
+    pub fn protocol_asset_id(id: T::AssetId) -> Option<AssetId> {
+        id.try_into().ok()
+    }
+
+    /// Returns the runtime asset id corresponding to the protocol asset `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AssetIdOutOfRange`] if the runtime id does not convert back into `id`,
+    /// so that a lossy [`Config::AssetId`] conversion never silently moves another asset.
+    #[inline]
+    pub fn runtime_asset_id(id: AssetId) -> Result<T::AssetId, Error<T>> {
+        let runtime_id = T::AssetId::from(id);
+        ensure!(
+            Self::protocol_asset_id(runtime_id) == Some(id),
+            Error::<T>::AssetIdOutOfRange
+        );
+        Ok(runtime_id)
+    }
+
+    /// Describes the shape and public parts of `post` for block explorers.
+    #[inline]
+    pub fn describe_post(post: TransferPost) -> PostDescription {
+        post.describe()
+    }
+
+    /// Returns the reason why `post` would be rejected with [`Error::InvalidShape`] for matching
+    /// none of the transfer shapes, or `None` if it matches one.
+    #[inline]
+    pub fn shape_error(post: TransferPost) -> Option<ShapeError> {
+        post.shape_error()
+    }
+
+    /// Returns the asset id presence and participant counts expected of every transfer shape.
+    #[inline]
+    pub fn shape_requirements() -> Vec<(PostShape, ShapeDims)> {
+        types::shape_requirements()
+    }
+
+    /// Returns the configured limits of the pallet.
+    #[inline]
+    pub fn limits() -> PalletLimits {
+        PalletLimits {
+            max_private_batch: T::MaxPrivateBatch::get(),
+            max_public_batch: T::MaxPublicBatch::get(),
+            max_pending_posts: T::MaxPendingPosts::get(),
+            max_senders: PrivateTransferShape::SENDERS.max(ReclaimShape::SENDERS) as u32,
+            max_receivers: PrivateTransferShape::RECEIVERS.max(ReclaimShape::RECEIVERS) as u32,
+            max_note_size: T::MaxNoteSize::get(),
+            max_proof_size: T::MaxProofSize::get(),
+            max_void_number_query: T::MaxVoidNumberQuery::get(),
+            max_root_query: T::MaxRootQuery::get(),
+            max_utxo_count: T::MaxUtxoCount::get(),
+            max_assets_per_account: T::MaxAssetsPerAccount::get(),
+            min_reclaim_value: T::MinReclaimValue::get(),
+            min_anonymity_set: T::MinAnonymitySet::get(),
+            max_void_number_export: T::MaxVoidNumberExport::get(),
+            max_note_page: T::MaxNotePage::get(),
+        }
+    }
+
+    /// Splits `total` into the power-of-two denominations of its binary expansion, in descending
+    /// order, so that wallets can mint it as a batch of common coin values.
+    ///
+    /// # Note
+    ///
+    /// Every denomination is distinct, so the split has at most one coin per bit of
+    /// [`AssetValue`] and a total of zero splits into no coins.
+    #[inline]
+    pub fn suggest_mint_denominations(total: AssetValue) -> Vec<AssetValue> {
+        (0..AssetValue::BITS)
+            .rev()
+            .map(|bit| 1 << bit)
+            .filter(|denomination| total & denomination != 0)
+            .collect()
+    }
+
+    /// Returns `true` if `root` is a UTXO accumulator output that senders may build proofs
+    /// against, which is only the latest root of each shard in [`Config::StrictRootMode`].
+    #[inline]
+    pub fn is_known_root(root: config::UtxoAccumulatorOutput) -> bool {
+        if T::StrictRootMode::get() {
+            LatestRootSet::<T>::contains_key(root)
+        } else {
+            UtxoAccumulatorOutputs::<T>::contains_key(root)
+        }
+    }
+
+    /// Returns whether each of the `roots` is known, in query order.
+    ///
+    /// # Note
+    ///
+    /// Queries of more than [`Config::MaxRootQuery`]-many roots are rejected with
+    /// [`QueryError::TooManyItems`], so clients should split larger queries.
+    #[inline]
+    pub fn known_roots(roots: Vec<config::UtxoAccumulatorOutput>) -> Result<Vec<bool>, QueryError> {
+        let max = T::MaxRootQuery::get();
+        if roots.len() > max as usize {
+            return Err(QueryError::TooManyItems { max });
+        }
+        Ok(roots.into_iter().map(Self::is_known_root).collect())
+    }
+
+    /// Returns `true` if `post` would be accepted when submitted by `origin` and at least one of
+    /// its sender posts was built against `root`.
+    ///
+    /// # Note
+    ///
+    /// This runs the full validation of `post`, including proof verification, without changing
+    /// the ledger. Historical roots are never pruned, so a post built against an old root remains
+    /// valid as long as its assets are unspent.
+    #[inline]
+    pub fn dry_run_against_root(
+        origin: T::AccountId,
+        post: TransferPost,
+        root: config::UtxoAccumulatorOutput,
+    ) -> bool {
+        if !post
+            .sender_posts
+            .iter()
+            .any(|sender_post| sender_post.utxo_accumulator_output == root)
+        {
+            return false;
+        }
+        let (sources, sinks) = match post.shape() {
+            Some(TransferShape::Mint) => (vec![origin], vec![]),
+            Some(TransferShape::PrivateTransfer) => (vec![], vec![]),
+            Some(TransferShape::Reclaim) => (vec![], vec![origin]),
+            None => return false,
+        };
+        match Self::ledger_for(&post) {
+            Ok(ledger) => config::TransferPost::from(post)
+                .validate(sources, sinks, &ledger)
+                .is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the UTXO of the mint `post` commits to the asset id and value that it
+    /// declares publicly.
+    ///
+    /// # Note
+    ///
+    /// The UTXO commitment hides its trapdoor, so the commitment cannot be recomputed from the
+    /// public parts. Instead, this verifies the validity proof of `post`, which proves the
+    /// commitment opens to the declared asset, as if the mint were funded by a placeholder
+    /// account. A mint whose UTXO is already registered is reported as inconsistent.
+    #[inline]
+    pub fn is_consistent_mint(post: TransferPost) -> bool {
+        if !matches!(post.shape(), Some(TransferShape::Mint))
+            || Self::ensure_valid_mint(&post).is_err()
+            || Self::ensure_well_formed_notes(&post).is_err()
+            || Self::ensure_well_formed_proof(&post).is_err()
+        {
+            return false;
+        }
+        let total = match checked_sum(&post.sources) {
+            Some(total) => total,
+            _ => return false,
+        };
+        with_transaction(|| {
+            let account = T::AccountId::decode(&mut TrailingZeroInput::zeroes())
+                .expect("Account ids should decode from zeroes.");
+            if let Some(id) = post.asset_id {
+                Self::adjust_balance(&account, T::AssetId::from(id), BalanceDelta::Credit(total));
+            }
+            let is_consistent = match Self::ledger_for(&post) {
+                Ok(ledger) => config::TransferPost::from(post)
+                    .validate(vec![account], vec![], &ledger)
+                    .is_ok(),
+                _ => false,
+            };
+            TransactionOutcome::Rollback(is_consistent)
+        })
+    }
+
+    /// Returns the number of void numbers that have been spent.
+    #[inline]
+    pub fn void_number_count() -> u64 {
+        VoidNumberSetSize::<T>::get()
+    }
+
+    /// Returns the sizes of the shielded ledger storage items.
+    ///
+    /// # Note
+    ///
+    /// The totals are read from counters, while the shard sizes are read from the shard trees,
+    /// so this reads at most one tree per shard and never iterates the UTXO or void number sets.
+    #[inline]
+    pub fn storage_stats() -> StorageStats {
+        let mut shard_sizes = ShardTrees::<T>::iter()
+            .filter(|(_, tree)| tree.leaf_digest.is_some())
+            .map(|(shard_index, tree)| (shard_index, tree.current_path.leaf_index as u64 + 1))
+            .collect::<Vec<_>>();
+        shard_sizes.sort_unstable_by_key(|(shard_index, _)| *shard_index);
+        StorageStats {
+            utxo_count: TotalUtxoCount::<T>::get(),
+            void_number_count: VoidNumberSetSize::<T>::get(),
+            root_count: RootCount::<T>::get(),
+            shard_sizes,
+        }
+    }
+
+    /// Returns whether each of the `void_numbers` has been spent, in query order.
+    ///
+    /// # Note
+    ///
+    /// Queries of more than [`Config::MaxVoidNumberQuery`]-many void numbers are rejected with
+    /// [`QueryError::TooManyItems`], so clients should split larger queries.
+    #[inline]
+    pub fn are_spent(void_numbers: Vec<config::VoidNumber>) -> Result<Vec<bool>, QueryError> {
+        let max = T::MaxVoidNumberQuery::get();
+        if void_numbers.len() > max as usize {
+            return Err(QueryError::TooManyItems { max });
+        }
+        Ok(void_numbers
+            .into_iter()
+            .map(|void_number| VoidNumberSet::<T>::contains_key(void_number))
+            .collect())
+    }
+
+    /// Returns the index and UTXO of the first receiver post of `post` whose UTXO is already
+    /// registered, or `None` if every receiver post is new.
+    ///
+    /// # Note
+    ///
+    /// Transfers rejected with [`Error::AssetRegistered`] do not say which receiver collided, so
+    /// clients use this to regenerate only the conflicting coin.
+    #[inline]
+    pub fn find_registered_receiver(post: &TransferPost) -> Option<(u32, config::Utxo)> {
+        post.receiver_posts
+            .iter()
+            .enumerate()
+            .find(|(_, receiver_post)| UtxoSet::<T>::contains_key(&receiver_post.utxo))
+            .map(|(index, receiver_post)| (index as u32, receiver_post.utxo))
+    }
+
+    /// Returns `true` if no UTXO has been registered, no void number has been spent, and no value
+    /// has been minted into the shielded pool.
+    #[inline]
+    fn is_ledger_empty() -> bool {
+        VoidNumberSetSize::<T>::get() == 0
+            && Shards::<T>::iter_keys().next().is_none()
+            && ShardTrees::<T>::iter_keys().next().is_none()
+            && LatestRoot::<T>::iter_keys().next().is_none()
+            && TotalMinted::<T>::iter_keys().next().is_none()
+            && UtxoSet::<T>::iter_keys().next().is_none()
+            && UtxoAccumulatorOutputs::<T>::iter_keys().next().is_none()
+    }
+
+    /// Exports the shielded ledger into a [`LedgerSnapshot`] which [`Pallet::import_ledger`] can
+    /// restore.
+    #[inline]
+    pub fn export_ledger() -> LedgerSnapshot {
+        let mut shards = Shards::<T>::iter()
+            .map(|(shard_index, leaf_index, (utxo, note))| (shard_index, leaf_index, utxo, note))
+            .collect::<Vec<_>>();
+        shards.sort_by_key(|(shard_index, leaf_index, _, _)| (*shard_index, *leaf_index));
+        let mut shard_trees = ShardTrees::<T>::iter().collect::<Vec<_>>();
+        shard_trees.sort_by_key(|(shard_index, _)| *shard_index);
+        let mut utxo_set = UtxoSet::<T>::iter_keys().collect::<Vec<_>>();
+        utxo_set.sort_by_cached_key(|utxo| utxo.encode());
+        let mut utxo_accumulator_outputs =
+            UtxoAccumulatorOutputs::<T>::iter_keys().collect::<Vec<_>>();
+        utxo_accumulator_outputs.sort_by_cached_key(|output| output.encode());
+        let mut latest_roots = LatestRoot::<T>::iter().collect::<Vec<_>>();
+        latest_roots.sort_by_key(|(shard_index, _)| *shard_index);
+        LedgerSnapshot {
+            version: LedgerSnapshot::VERSION,
+            shards,
+            shard_trees,
+            utxo_set,
+            utxo_accumulator_outputs,
+            void_numbers: (0..VoidNumberSetSize::<T>::get())
+                .map(|index| VoidNumberSetInsertionOrder::<T>::get(index))
+                .collect(),
+            latest_roots,
+            total_minted: Self::export_asset_map(TotalMinted::<T>::iter()),
+            total_reclaimed: Self::export_asset_map(TotalReclaimed::<T>::iter()),
+            mint_value_ranges: Self::export_asset_map(MintValueRange::<T>::iter()),
+            utxo_counts: Self::export_asset_map(UtxoCountPerAsset::<T>::iter()),
+        }
+    }
+
+    /// Converts the runtime asset ids of `entries` into protocol asset ids, sorted by id.
+    #[inline]
+    fn export_asset_map<V>(entries: impl Iterator<Item = (T::AssetId, V)>) -> Vec<(AssetId, V)> {
+        let mut entries = entries
+            .filter_map(|(id, value)| Some((Self::protocol_asset_id(id)?, value)))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(id, _)| *id);
+        entries
+    }
+
+    /// Exports at most `limit`-many spent void numbers in insertion order, starting at the
+    /// insertion index `cursor`, together with the cursor of the next page.
+    ///
+    /// # Note
+    ///
+    /// Void numbers are only ever appended, so backup tooling can checkpoint the returned cursor
+    /// and resume from it later. The next cursor is `None` once every void number spent so far
+    /// has been exported. The `limit` is clamped to [`Config::MaxVoidNumberExport`], so a single
+    /// call never scans more than one bounded page.
+    #[inline]
+    pub fn export_void_numbers(cursor: u64, limit: u32) -> (Vec<config::VoidNumber>, Option<u64>) {
+        let size = VoidNumberSetSize::<T>::get();
+        let limit = limit.min(T::MaxVoidNumberExport::get());
+        let end = cursor.saturating_add(limit as u64).min(size);
+        let void_numbers = (cursor..end)
+            .map(|index| VoidNumberSetInsertionOrder::<T>::get(index))
+            .collect();
+        (void_numbers, (end < size).then(|| end))
+    }
+
+    /// Ensures that the UTXO set of `snapshot` holds exactly the UTXOs of its shard entries, that
+    /// its shard trees are the trees obtained by inserting its shard entries in leaf order into
+    /// empty shards, and that the root of every shard is its latest root and one of its UTXO
+    /// accumulator outputs.
+    #[inline]
+    fn ensure_consistent_snapshot(snapshot: &LedgerSnapshot) -> Result<(), Error<T>> {
+        let model = Self::ledger()?.utxo_accumulator_model;
+        let mut registered = snapshot
+            .utxo_set
+            .iter()
+            .map(Encode::encode)
+            .collect::<Vec<_>>();
+        registered.sort_unstable();
+        let mut inserted = snapshot
+            .shards
+            .iter()
+            .map(|(_, _, utxo, _)| utxo.encode())
+            .collect::<Vec<_>>();
+        inserted.sort_unstable();
+        ensure!(registered == inserted, Error::<T>::SnapshotInconsistent);
+        let mut entries = snapshot.shards.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(shard_index, leaf_index, _, _)| (*shard_index, *leaf_index));
+        let mut trees = BTreeMap::new();
+        for (shard_index, leaf_index, utxo, _) in entries {
+            ensure!(
+                Self::utxo_shard(utxo) == *shard_index,
+                Error::<T>::SnapshotInconsistent
+            );
+            let (leaf_digest, current_path, root) =
+                trees.entry(*shard_index).or_insert_with(|| {
+                    let tree = UtxoMerkleTreePath::default();
+                    (
+                        tree.leaf_digest,
+                        merkle_tree::CurrentPath::from(tree.current_path),
+                        None,
+                    )
+                });
+            *root = merkle_tree::single_path::raw::insert(&model, leaf_digest, current_path, *utxo);
+            ensure!(
+                root.is_some() && current_path.leaf_index().0 as u64 == *leaf_index,
+                Error::<T>::SnapshotInconsistent
+            );
+        }
+        ensure!(
+            snapshot.shard_trees.len() == trees.len() && snapshot.latest_roots.len() == trees.len(),
+            Error::<T>::SnapshotInconsistent
+        );
+        for (shard_index, tree) in &snapshot.shard_trees {
+            let (leaf_digest, current_path, root) = trees
+                .remove(shard_index)
+                .ok_or(Error::<T>::SnapshotInconsistent)?;
+            let is_known_root = root.map_or(false, |root| {
+                snapshot.utxo_accumulator_outputs.contains(&root)
+                    && snapshot.latest_roots.contains(&(*shard_index, root))
+            });
+            ensure!(
+                tree.leaf_digest == leaf_digest
+                    && tree.current_path == CurrentPath::from(current_path)
+                    && is_known_root,
+                Error::<T>::SnapshotInconsistent
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if no UTXO has been inserted into the shard with the given `shard_index`.
+    ///
+    /// # Note
+    ///
+    /// The current path of an empty shard is meaningless, so readers must check this before
+    /// treating the leaf index of [`ShardTrees`] as the index of the latest leaf.
+    #[inline]
+    pub fn shard_is_empty(shard_index: u8) -> bool {
+        ShardTrees::<T>::get(shard_index).leaf_digest.is_none()
+    }
+
+    /// Returns the index of the shard which `utxo` is inserted into.
+    #[inline]
+    pub fn utxo_shard(utxo: &config::Utxo) -> u8 {
+        config::MerkleTreeConfiguration::tree_index(utxo)
+    }
+
+    /// Returns `true` if `root` is known, see [`Pallet::is_known_root`], and `path` proves that
+    /// `utxo` is a leaf of the shard tree with the given `root`.
+    ///
+    /// # Note
+    ///
+    /// Wallets use this to check that a path they cached is still valid without downloading the
+    /// shard again, so a path against a root which senders can no longer use never verifies.
+    /// Paths with an inner path of the wrong depth never verify either.
+    #[inline]
+    pub fn verify_membership(
+        utxo: config::Utxo,
+        path: CurrentPath,
+        root: config::UtxoAccumulatorOutput,
+    ) -> bool {
+        if !path.has_valid_depth() || !Self::is_known_root(root) {
+            return false;
+        }
+        let path = merkle_tree::Path::<config::MerkleTreeConfiguration>::from(
+            merkle_tree::CurrentPath::from(path),
+        );
+        match Self::ledger() {
+            Ok(ledger) => path.verify(&ledger.utxo_accumulator_model, &root, &utxo),
+            _ => false,
+        }
+    }
+
+    /// Returns the UTXO and encrypted note most recently inserted into the shard with the given
+    /// `shard_index`, together with its leaf index, or `None` if the shard is empty.
+    #[inline]
+    pub fn latest_utxo(shard_index: u8) -> Option<(config::Utxo, EncryptedNote, u64)> {
+        let tree = ShardTrees::<T>::get(shard_index);
+        if tree.leaf_digest.is_none() {
+            return None;
+        }
+        let leaf_index = tree.current_path.leaf_index as u64;
+        Shards::<T>::try_get(shard_index, leaf_index)
+            .ok()
+            .map(|(utxo, note)| (utxo, note, leaf_index))
+    }
+
+    /// Pulls at most `limit`-many notes from the shards in the inclusive `shard_range`, starting
+    /// at `cursor`. Notes are returned in insertion order within each shard, and shards are
+    /// visited in increasing order, so that a single cursor can scan the whole range.
+    ///
+    /// # Note
+    ///
+    /// The `limit` is clamped to [`Config::MaxNotePage`], so a single call never reads more than
+    /// one bounded page of notes.
+    #[inline]
+    pub fn pull_notes(shard_range: (u8, u8), cursor: NoteCursor, limit: u32) -> NotePage {
+        let limit = limit.min(T::MaxNotePage::get());
+        let (start, end) = shard_range;
+        let mut page = NotePage::default();
+        if cursor.shard_index > end || start > end {
+            return page;
+        }
+        let (mut shard_index, mut leaf_index) = if cursor.shard_index < start {
+            (start, 0)
+        } else {
+            (cursor.shard_index, cursor.leaf_index)
+        };
+        let mut count = 0;
+        loop {
+            while let Ok(entry) = Shards::<T>::try_get(shard_index, leaf_index) {
+                if count >= limit {
+                    page.next = Some(NoteCursor::new(shard_index, leaf_index));
+                    return page;
+                }
+                page.push(NoteCursor::new(shard_index, leaf_index), entry);
+                count += 1;
+                leaf_index += 1;
+            }
+            if shard_index == end {
+                return page;
+            }
+            shard_index += 1;
+            leaf_index = 0;
+        }
+    }
+
+    /// Runs the cheap shape and double-spend checks on the private transfer `post` and places it
+    /// in [`PendingPosts`] for verification in [`Hooks::on_idle`](frame_support::traits::Hooks).
+    ///
+    /// # Note
+    ///
+    /// The full [`WeightInfo::private_transfer`] weight is charged without a refund, since the
+    /// proof of every queued post is verified later at the cost of the block.
+    #[inline]
+    fn enqueue_post(origin: T::AccountId, post: TransferPost) -> DispatchResultWithPostInfo {
+        ensure!(
+            matches!(post.shape(), Some(TransferShape::PrivateTransfer)),
+            Error::<T>::InvalidShape
+        );
+        for sender_post in &post.sender_posts {
+            ensure!(
+                !VoidNumberSet::<T>::contains_key(&sender_post.void_number),
+                Error::<T>::AssetSpent
+            );
+        }
+        for receiver_post in &post.receiver_posts {
+            ensure!(
+                !UtxoSet::<T>::contains_key(&receiver_post.utxo),
+                Error::<T>::AssetRegistered
+            );
+        }
+        PendingPosts::<T>::try_mutate(|pending| pending.try_push((origin.clone(), post)))
+            .map_err(|_| Error::<T>::PendingPostsFull)?;
+        Self::deposit_event(Event::PostQueued { origin });
+        Ok(().into())
+    }
+
+    /// Ensures that `post` has the public asset id, the single source, the single receiver post,
+    /// and no sender posts of a mint, that its asset id is in the runtime range, and that it does
+    /// not mint zero value.
+    #[inline]
+    fn ensure_valid_mint(post: &TransferPost) -> Result<(), Error<T>> {
+        ensure!(
+            post.asset_id.is_some()
+                && post.sources.len() == 1
+                && post.sender_posts.is_empty()
+                && post.receiver_posts.len() == 1,
+            Error::<T>::InvalidShape
+        );
+        if let Some(id) = post.asset_id {
+            Self::runtime_asset_id(id)?;
+        }
+        ensure!(
+            post.sources.iter().all(|value| *value > 0),
+            Error::<T>::ZeroMint
+        );
+        Ok(())
+    }
+
+    /// Ensures that the asset minted by `post` is initialized if
+    /// [`Config::RequireAssetInitForMint`] is set.
+    #[inline]
+    fn ensure_mintable_asset(post: &TransferPost) -> Result<(), Error<T>> {
+        if T::RequireAssetInitForMint::get() {
+            if let Some(id) = post.asset_id {
+                ensure!(
+                    TotalSupply::<T>::contains_key(T::AssetId::from(id)),
+                    Error::<T>::UninitializedSupply
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the actual weight of posting `count`-many private transfers which updated
+    /// `touched_shards`-many shard trees.
+    ///
+    /// # Note
+    ///
+    /// [`WeightInfo::private_transfer`] charges one shard tree read and write per receiver, so
+    /// every transfer whose receivers share a shard is refunded one shard tree access.
+    #[inline]
+    fn private_transfer_weight(count: u32, touched_shards: u32) -> Weight {
+        let unused_shard_accesses = (2 * count).saturating_sub(touched_shards);
+        T::WeightInfo::private_transfer()
+            .saturating_mul(count as Weight)
+            .saturating_sub(
+                T::DbWeight::get()
+                    .reads_writes(1, 1)
+                    .saturating_mul(unused_shard_accesses as Weight),
+            )
+    }
+
+    /// Charges the [`Config::ShieldedFee`] for `count`-many posts from the public balance of
+    /// `account`, crediting it to [`Config::FeeDestination`].
+    #[inline]
+    fn charge_shielded_fee(account: &T::AccountId, count: u32) -> DispatchResult {
+        let fee = match T::ShieldedFee::get() {
+            Some(fee) if fee > 0 => fee.saturating_mul(count as AssetValue),
+            _ => return Ok(()),
+        };
+        let id = T::FeeAssetId::get();
+        let destination = T::FeeDestination::get();
+        Self::ensure_can_hold(&destination, id)?;
+        Self::adjust_balance(account, id, BalanceDelta::Debit(fee))
+            .ok_or(Error::<T>::CannotPayFee)?;
+        Self::adjust_balance(&destination, id, BalanceDelta::Credit(fee));
+        Ok(())
+    }
 
-        /// Asset Registered
-        ///
-        /// An asset present in this transfer has already been registered to the ledger.
-        AssetRegistered,
+    /// Ensures that the validity proof of `post` is not degenerate, so that obviously malformed
+    /// posts are rejected before paying for verification.
+    #[inline]
+    fn ensure_well_formed_proof(post: &TransferPost) -> Result<(), Error<T>> {
+        ensure!(!post.has_degenerate_proof(), Error::<T>::MalformedProof);
+        Ok(())
+    }
 
-        /// Duplicate Spend
-        ///
-        /// There were multiple spend entries for the same underlying asset in this transfer.
-        DuplicateSpend,
+    /// Ensures that every encrypted note in the receiver posts of `post` is well-formed, so that
+    /// no undecryptable note is ever registered.
+    #[inline]
+    fn ensure_well_formed_notes(post: &TransferPost) -> Result<(), Error<T>> {
+        ensure!(
+            post.receiver_posts
+                .iter()
+                .all(|receiver_post| receiver_post.note.is_well_formed()),
+            Error::<T>::MalformedNote
+        );
+        Ok(())
+    }
 
-        /// Duplicate Register
-        ///
-        /// There were multiple register entries for the same underlying asset in this transfer.
-        DuplicateRegister,
+    /// Ensures that `count`-many more UTXOs can be registered without exceeding
+    /// [`Config::MaxUtxoCount`].
+    #[inline]
+    fn ensure_utxo_capacity(count: usize) -> Result<(), Error<T>> {
+        ensure!(
+            TotalUtxoCount::<T>::get().saturating_add(count as u64) <= T::MaxUtxoCount::get(),
+            Error::<T>::LedgerFull
+        );
+        Ok(())
+    }
 
-        /// Invalid Proof
-        ///
-        /// The submitted proof did not pass validation, or errored during validation.
-        InvalidProof,
+    /// Ensures that the shard of every receiver of `post` has room for all the receivers of
+    /// `post` placed into it, so that a full shard fails the post with [`Error::ShardFull`]
+    /// instead of reporting an inconsistent ledger.
+    ///
+    /// # Note
+    ///
+    /// Batches must run this right before applying each post, since the previous posts of the
+    /// batch may have filled the shard.
+    #[inline]
+    fn ensure_shard_capacity(post: &TransferPost) -> Result<(), Error<T>> {
+        let capacity =
+            1u64 << (<config::MerkleTreeConfiguration as merkle_tree::Configuration>::HEIGHT - 1);
+        let mut counts = BTreeMap::<u8, u64>::new();
+        for receiver_post in &post.receiver_posts {
+            *counts
+                .entry(Self::utxo_shard(&receiver_post.utxo))
+                .or_default() += 1;
+        }
+        for (shard_index, count) in counts {
+            let tree = ShardTrees::<T>::get(shard_index);
+            let used = match tree.leaf_digest {
+                Some(_) => tree.current_path.leaf_index as u64 + 1,
+                _ => 0,
+            };
+            ensure!(used + count <= capacity, Error::<T>::ShardFull);
+        }
+        Ok(())
     }
 
-    impl<T> From<InvalidSourceAccount<T::AccountId>> for Error<T>
-    where
-        T: Config,
-    {
-        #[inline]
-        fn from(err: InvalidSourceAccount<T::AccountId>) -> Self {
-            match err.balance {
-                AccountBalance::Known(_) => Self::BalanceLow,
-                AccountBalance::UnknownAccount => {
-                    unreachable!("Accounts are checked before reaching this point.")
+    /// Verifies and posts the private transfers in [`PendingPosts`] in queue order, for as long
+    /// as `remaining_weight` can pay for a full verification. Returns the consumed weight.
+    #[inline]
+    fn process_pending_posts(remaining_weight: Weight) -> Weight {
+        let queue_weight = T::DbWeight::get().reads_writes(1, 1);
+        let post_weight =
+            T::WeightInfo::private_transfer().saturating_add(T::DbWeight::get().reads_writes(1, 1));
+        if remaining_weight < queue_weight.saturating_add(post_weight) {
+            return 0;
+        }
+        let mut pending = PendingPosts::<T>::get().into_inner();
+        if pending.is_empty() {
+            return T::DbWeight::get().reads(1);
+        }
+        let mut ledger = Self::ledger().ok();
+        let mut consumed = queue_weight;
+        let mut processed = 0;
+        for (origin, post) in &pending {
+            if consumed.saturating_add(post_weight) > remaining_weight {
+                break;
+            }
+            consumed = consumed.saturating_add(post_weight);
+            processed += 1;
+            match with_transaction(|| {
+                let result = Self::ensure_utxo_capacity(post.receiver_posts.len())
+                    .and_then(|_| Self::ensure_shard_capacity(post))
+                    .and_then(|_| ledger.as_mut().ok_or(Error::<T>::ParametersUnavailable))
+                    .and_then(|ledger| ledger.apply_post(vec![], vec![], post.clone()));
+                if result.is_ok() {
+                    TransactionOutcome::Commit(result)
+                } else {
+                    TransactionOutcome::Rollback(result)
                 }
+            }) {
+                Ok(event) => Self::deposit_event(event.convert(Some(origin.clone()))),
+                Err(err) => Self::deposit_event(Event::PendingPostDropped {
+                    origin: origin.clone(),
+                    error: err.into(),
+                }),
             }
         }
+        pending.drain(..processed);
+        PendingPosts::<T>::put(
+            BoundedVec::try_from(pending).expect("Removing posts cannot exceed the bound."),
+        );
+        consumed
     }
 
-    impl<T> From<InvalidSinkAccount<T::AccountId>> for Error<T>
-    where
-        T: Config,
-    {
-        #[inline]
-        fn from(err: InvalidSinkAccount<T::AccountId>) -> Self {
-            let _ = err;
-            unimplemented!("Accounts are checked before reaching this point.")
+    /// Fills the shard with the given `shard_index` with `count`-many placeholder UTXOs so that
+    /// insertion can be benchmarked against a populated shard.
+    #[cfg(feature = "runtime-benchmarks")]
+    #[inline]
+    pub(crate) fn prefill_shard(shard_index: u8, count: u32) {
+        Self::ledger()
+            .expect("Parameters should be available in benchmarks.")
+            .insert_into_shard(
+                shard_index,
+                (0..count).map(|_| (Default::default(), Default::default())),
+            )
+            .expect("Benchmarks should not exceed the shard capacity.");
+    }
+
+    /// Registers `count`-many distinct placeholder UTXOs one at a time so that snapshots of a
+    /// populated ledger can be benchmarked with one root per UTXO, as if every UTXO was minted.
+    #[cfg(feature = "runtime-benchmarks")]
+    #[inline]
+    pub(crate) fn prefill_utxos(count: u32) {
+        let ledger = Self::ledger().expect("Parameters should be available in benchmarks.");
+        for n in 0..count {
+            let mut bytes = [0; 32];
+            bytes[..4].copy_from_slice(&n.to_le_bytes());
+            let utxo = <config::Utxo as Decode>::decode(&mut bytes.as_slice())
+                .expect("Small field elements are valid.");
+            ledger
+                .insert_into_shard(Self::utxo_shard(&utxo), [(utxo, Default::default())])
+                .expect("Benchmarks should not exceed the shard capacity.");
         }
     }
 
-    impl<T> From<SenderPostError> for Error<T> {
-        #[inline]
-        fn from(err: SenderPostError) -> Self {
-            match err {
-                SenderPostError::AssetSpent => Self::AssetSpent,
-                SenderPostError::InvalidUtxoAccumulatorOutput => Self::InvalidUtxoAccumulatorOutput,
-            }
+    /// Removes every UTXO, root, and void number from the ledger so that a snapshot of it can be
+    /// imported again in benchmarks.
+    #[cfg(feature = "runtime-benchmarks")]
+    #[inline]
+    pub(crate) fn clear_ledger() {
+        let _ = Shards::<T>::remove_all(None);
+        let _ = ShardTrees::<T>::remove_all(None);
+        let _ = UtxoSet::<T>::remove_all(None);
+        let _ = UtxoAccumulatorOutputs::<T>::remove_all(None);
+        let _ = LatestRoot::<T>::remove_all(None);
+        let _ = LatestRootSet::<T>::remove_all(None);
+        let _ = VoidNumberSet::<T>::remove_all(None);
+        let _ = VoidNumberSetInsertionOrder::<T>::remove_all(None);
+        VoidNumberSetSize::<T>::kill();
+        TotalUtxoCount::<T>::kill();
+        RootCount::<T>::kill();
+    }
+
+    /// Inserts `count`-many distinct placeholder void numbers into the void number set so that
+    /// the spend check can be benchmarked against a mature chain.
+    #[cfg(feature = "runtime-benchmarks")]
+    #[inline]
+    pub(crate) fn prefill_void_numbers(count: u32) {
+        let start = VoidNumberSetSize::<T>::get();
+        for n in 0..count {
+            let mut bytes = [0; 32];
+            bytes[..4].copy_from_slice(&n.to_le_bytes());
+            let void_number = <config::VoidNumber as Decode>::decode(&mut bytes.as_slice())
+                .expect("Small field elements are valid.");
+            VoidNumberSet::<T>::insert(void_number, ());
+            VoidNumberSetInsertionOrder::<T>::insert(start + n as u64, void_number);
         }
+        VoidNumberSetSize::<T>::set(start + count as u64);
     }
 
-    impl<T> From<ReceiverPostError> for Error<T> {
-        #[inline]
-        fn from(err: ReceiverPostError) -> Self {
-            match err {
-                ReceiverPostError::AssetRegistered => Self::AssetRegistered,
-            }
+    /// Marks `void_number` as spent without posting a transfer.
+    ///
+    /// # Note
+    ///
+    /// This is only meant for setting up double-spend scenarios in tests and must never be
+    /// compiled into a production runtime.
+    #[cfg(feature = "test-helpers")]
+    #[inline]
+    pub fn force_insert_void_number(void_number: config::VoidNumber) {
+        let index = VoidNumberSetSize::<T>::get();
+        VoidNumberSet::<T>::insert(void_number, ());
+        VoidNumberSetInsertionOrder::<T>::insert(index, void_number);
+        VoidNumberSetSize::<T>::set(index + 1);
+    }
+
+    /// Checks that the protocol parameters and the verifying contexts are consistent by replaying
+    /// the precomputed sample transfers against the ledger, rolling back every change.
+    ///
+    /// # Note
+    ///
+    /// The samples were built against an empty ledger, so this check is only meaningful before
+    /// any UTXO is registered. The genesis build runs it against the parameters in the genesis
+    /// storage, whether they are overridden or shipped with the SDK.
+    ///
+    /// The samples were proven with the SDK circuits, so the check passes without replaying them
+    /// when [`Config::ProofInput`] does not have their public input layout.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn check_parameters() -> Result<(), Error<T>> {
+        if !T::ProofInput::is_sdk_layout() {
+            return Ok(());
         }
+        with_transaction(|| TransactionOutcome::Rollback(Self::replay_samples()))
     }
 
-    impl<T> From<TransferPostError<T::AccountId>> for Error<T>
-    where
-        T: Config,
-    {
-        #[inline]
-        fn from(err: TransferPostError<T::AccountId>) -> Self {
-            match err {
-                TransferPostError::InvalidShape => Self::InvalidShape,
-                TransferPostError::InvalidSourceAccount(err) => err.into(),
-                TransferPostError::InvalidSinkAccount(err) => err.into(),
-                TransferPostError::Sender(err) => err.into(),
-                TransferPostError::Receiver(err) => err.into(),
-                TransferPostError::DuplicateSpend => Self::DuplicateSpend,
-                TransferPostError::DuplicateRegister => Self::DuplicateRegister,
-                TransferPostError::InvalidProof => Self::InvalidProof,
+    /// Posts the precomputed sample mints and the private transfer spending them, funding the
+    /// mints from a placeholder account.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn replay_samples() -> Result<(), Error<T>> {
+        let account = T::AccountId::decode(&mut TrailingZeroInput::zeroes())
+            .expect("Account ids should decode from zeroes.");
+        let mut ledger = Self::ledger()?;
+        for coin in precomputed_coins::PRIVATE_TRANSFER_INPUT {
+            let post = TransferPost::decode(&mut &**coin).expect("Sample mints should decode.");
+            if let Some(id) = post.asset_id {
+                let total = checked_sum(&post.sources).ok_or(Error::<T>::InconsistentParameters)?;
+                Self::adjust_balance(&account, T::AssetId::from(id), BalanceDelta::Credit(total));
             }
+            ledger.load_verifying_context_for(&post)?;
+            config::TransferPost::from(post)
+                .post(vec![account.clone()], vec![], &(), &mut ledger)
+                .map_err(|_| Error::<T>::InconsistentParameters)?;
         }
+        let post = TransferPost::decode(&mut &*precomputed_coins::PRIVATE_TRANSFER)
+            .expect("Sample private transfer should decode.");
+        ledger.load_verifying_context_for(&post)?;
+        config::TransferPost::from(post)
+            .post(vec![], vec![], &(), &mut ledger)
+            .map_err(|_| Error::<T>::InconsistentParameters)?;
+        ledger.check_consistency()
+    }
+
+    /// Decodes the UTXO accumulator model from `bytes`, returning
+    /// [`Error::ParametersUnavailable`] if the bytes are corrupted.
+    #[inline]
+    fn decode_utxo_accumulator_model(
+        bytes: &[u8],
+    ) -> Result<config::UtxoAccumulatorModel, Error<T>> {
+        config::UtxoAccumulatorModel::decode(bytes).map_err(|_| Error::<T>::ParametersUnavailable)
+    }
+
+    /// Returns the encoded UTXO accumulator model, preferring [`UtxoAccumulatorModelOverride`]
+    /// over the one shipped with the SDK.
+    #[inline]
+    fn utxo_accumulator_model_bytes() -> Option<Vec<u8>> {
+        UtxoAccumulatorModelOverride::<T>::get().or_else(|| {
+            manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get().map(Vec::from)
+        })
+    }
+
+    /// Decodes a verifying context from `bytes`, returning [`Error::ParametersUnavailable`] if
+    /// the bytes are corrupted.
+    #[inline]
+    fn decode_verifying_context(bytes: &[u8]) -> Result<config::VerifyingContext, Error<T>> {
+        config::VerifyingContext::decode(bytes).map_err(|_| Error::<T>::ParametersUnavailable)
+    }
+
+    /// Returns the encoded verifying context of `shape`, preferring
+    /// [`VerifyingContextOverrides`] over the one shipped with the SDK.
+    #[inline]
+    fn verifying_context_bytes(shape: PostShape) -> Option<Vec<u8>> {
+        VerifyingContextOverrides::<T>::get(shape).or_else(|| {
+            match shape {
+                PostShape::Mint => manta_sdk::pay::testnet::verifying::Mint::get(),
+                PostShape::PrivateTransfer => {
+                    manta_sdk::pay::testnet::verifying::PrivateTransfer::get()
+                }
+                PostShape::Reclaim => manta_sdk::pay::testnet::verifying::Reclaim::get(),
+            }
+            .map(Vec::from)
+        })
+    }
+
+    /// Returns a fingerprint of the protocol parameters used for verification: the UTXO
+    /// accumulator model and the verifying contexts of every transfer shape.
+    ///
+    /// # Note
+    ///
+    /// Wallets compare this against the fingerprint of their local parameters to detect a
+    /// mismatch before building any proofs. Missing parameters are fingerprinted as empty.
+    #[inline]
+    pub fn parameters_fingerprint() -> [u8; 32] {
+        sp_io::hashing::blake2_256(
+            &(
+                Self::utxo_accumulator_model_bytes().unwrap_or_default(),
+                Self::verifying_context_bytes(PostShape::Mint).unwrap_or_default(),
+                Self::verifying_context_bytes(PostShape::PrivateTransfer).unwrap_or_default(),
+                Self::verifying_context_bytes(PostShape::Reclaim).unwrap_or_default(),
+            )
+                .encode(),
+        )
+    }
+
+    /// Returns the ledger implementation for this pallet, decoding the UTXO accumulator model up
+    /// front so that corrupted parameters are reported before any proof is verified.
+    ///
+    /// # Note
+    ///
+    /// The verifying contexts are decoded on first use by [`Ledger::apply_post`], so callers
+    /// posting several transfers should share one ledger to decode the parameters once.
+    #[inline]
+    fn ledger() -> Result<Ledger<T>, Error<T>> {
+        Ok(Ledger {
+            utxo_accumulator_model: Self::decode_utxo_accumulator_model(
+                &Self::utxo_accumulator_model_bytes().ok_or(Error::<T>::ParametersUnavailable)?,
+            )?,
+            verifying_contexts: BTreeMap::new(),
+            inconsistency: None,
+            touched_shards: 0,
+            __: PhantomData,
+        })
+    }
+
+    /// Returns the ledger implementation for this pallet with the verifying context for the shape
+    /// of `post` decoded, for validating `post` without posting it.
+    #[inline]
+    fn ledger_for(post: &TransferPost) -> Result<Ledger<T>, Error<T>> {
+        let mut ledger = Self::ledger()?;
+        ledger.load_verifying_context_for(post)?;
+        Ok(ledger)
     }
 }
 
-impl<T> Pallet<T>
-where
-    T: Config,
-{
-    /// Initializes `asset_id` with a supply of `total`, giving control to `owner`.
+/// Shielded Pool Watermark
+pub trait PoolWatermark<AssetId> {
+    /// Returns the low watermark of the shielded pool of the asset with the given `id`, if any.
+    fn low_watermark(id: AssetId) -> Option<AssetValue>;
+}
+
+impl<AssetId> PoolWatermark<AssetId> for () {
     #[inline]
-    fn init_asset(owner: &T::AccountId, asset_id: AssetId, total: AssetValue) {
-        TotalSupply::<T>::insert(asset_id, total);
-        Balances::<T>::insert(owner, asset_id, total);
+    fn low_watermark(_: AssetId) -> Option<AssetValue> {
+        None
     }
+}
 
-    /// Returns the balance of `account` for the asset with the given `id`.
+/// Proof Input Generator
+///
+/// # Note
+///
+/// Proofs only verify against the public input that their circuit was built for, so a custom
+/// generator must be deployed together with verifying contexts generated for its layout.
+pub trait GenerateProofInput {
+    /// Returns `true` if the generated public input has the layout of the SDK circuits.
+    ///
+    /// # Note
+    ///
+    /// [`Pallet::check_parameters`] replays samples proven with the SDK circuits, so it is skipped
+    /// for generators returning `false`.
     #[inline]
-    pub fn balance(account: T::AccountId, id: AssetId) -> AssetValue {
-        Balances::<T>::get(account, id)
+    fn is_sdk_layout() -> bool {
+        false
     }
 
-    /// Returns the total supply of the asset with the given `id`.
+    /// Generates the public input of a transfer with the given `asset_id` and posting keys.
+    fn generate_proof_input<L>(
+        asset_id: Option<asset::AssetId>,
+        sources: &[SourcePostingKey<config::Config, L>],
+        senders: &[SenderPostingKey<config::Config, L>],
+        receivers: &[ReceiverPostingKey<config::Config, L>],
+        sinks: &[SinkPostingKey<config::Config, L>],
+    ) -> ProofInput<config::Config>
+    where
+        L: TransferLedger<config::Config>;
+}
+
+impl GenerateProofInput for () {
     #[inline]
-    pub fn total_supply(id: AssetId) -> AssetValue {
-        TotalSupply::<T>::get(id)
+    fn is_sdk_layout() -> bool {
+        true
+    }
+
+    #[inline]
+    fn generate_proof_input<L>(
+        asset_id: Option<asset::AssetId>,
+        sources: &[SourcePostingKey<config::Config, L>],
+        senders: &[SenderPostingKey<config::Config, L>],
+        receivers: &[ReceiverPostingKey<config::Config, L>],
+        sinks: &[SinkPostingKey<config::Config, L>],
+    ) -> ProofInput<config::Config>
+    where
+        L: TransferLedger<config::Config>,
+    {
+        TransferPostingKey::generate_proof_input(asset_id, sources, senders, receivers, sinks)
     }
+}
 
-    /// Returns the ledger implementation for this pallet.
+/// Pallet Account
+///
+/// Returns [`Pallet::account_id`], so that runtimes can use the account controlled by the pallet
+/// as [`Config::FeeDestination`] or wherever pooled public funds need a holder.
+pub struct PalletAccount<T>(PhantomData<T>);
+
+impl<T> Get<T::AccountId> for PalletAccount<T>
+where
+    T: Config,
+{
     #[inline]
-    fn ledger() -> Ledger<T> {
-        Ledger(PhantomData)
+    fn get() -> T::AccountId {
+        Pallet::<T>::account_id()
     }
 }
 
+/// Public Balance Change
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum BalanceDelta {
+    /// Credits the value to the balance.
+    Credit(AssetValue),
+
+    /// Debits the value from the balance.
+    Debit(AssetValue),
+}
+
 /// Preprocessed Event
 pub enum PreprocessedEvent<T>
 where
@@ -826,43 +4131,116 @@ where
     T: Config,
 {
     /// Converts a [`PreprocessedEvent`] with into an [`Event`] using the given `origin` for
-    /// [`PreprocessedEvent::PrivateTransfer`].
+    /// [`PreprocessedEvent::PrivateTransfer`], reporting asset ids as [`Config::AssetId`].
+    ///
+    /// # Note
+    ///
+    /// This returns [`Event::Posted`] instead when [`Config::EmitDetailedEvents`] is `false`.
     #[inline]
     pub fn convert(self, origin: Option<T::AccountId>) -> Event<T> {
+        if !T::EmitDetailedEvents::get() {
+            return Event::Posted;
+        }
         match self {
-            Self::Mint { asset, source } => Event::Mint { asset, source },
+            Self::Mint { asset, source } => Event::Mint {
+                asset: asset.convert_id(),
+                source,
+            },
             Self::PrivateTransfer => Event::PrivateTransfer {
                 origin: origin.unwrap(),
             },
-            Self::Reclaim { asset, sink } => Event::Reclaim { asset, sink },
+            Self::Reclaim { asset, sink } => Event::Reclaim {
+                asset: asset.convert_id(),
+                sink,
+            },
         }
     }
 }
 
 /// Ledger
-pub struct Ledger<T>(PhantomData<T>)
+pub struct Ledger<T>
 where
-    T: Config;
+    T: Config,
+{
+    /// UTXO Accumulator Model
+    utxo_accumulator_model: config::UtxoAccumulatorModel,
 
-/// Wrap Type
-#[derive(Clone, Copy)]
-pub struct Wrap<T>(T);
+    /// Decoded Verifying Contexts by Post Shape
+    verifying_contexts: BTreeMap<PostShape, config::VerifyingContext>,
 
-impl<T> AsRef<T> for Wrap<T> {
-    #[inline]
-    fn as_ref(&self) -> &T {
-        &self.0
-    }
+    /// First Inconsistency Found while Posting
+    inconsistency: Option<&'static str>,
+
+    /// Number of Shard Tree Updates while Posting
+    ///
+    /// Every registration touching a shard counts once, even if an earlier registration with the
+    /// same ledger touched the same shard.
+    touched_shards: u32,
+
+    /// Type Parameter Marker
+    __: PhantomData<T>,
 }
 
-/// Wrap Pair Type
-#[derive(Clone, Copy)]
-pub struct WrapPair<L, R>(L, R);
+pub use witness::{Wrap, WrapPair};
 
-impl<L, R> AsRef<R> for WrapPair<L, R> {
-    #[inline]
-    fn as_ref(&self) -> &R {
-        &self.1
+/// Ledger Validity Witnesses
+///
+/// The [`Ledger`] hands these types to the transfer protocol as proof that a value was checked
+/// against the ledger state. Their fields are private to this module so that a witness can only
+/// be built through the explicit constructors, which must only be called after the corresponding
+/// check has succeeded.
+mod witness {
+    /// Wrap Type
+    ///
+    /// # Invariant
+    ///
+    /// A [`Wrap`] value has been validated by the [`Ledger`](crate::Ledger) that built it.
+    #[derive(Clone, Copy)]
+    pub struct Wrap<T>(T);
+
+    impl<T> Wrap<T> {
+        /// Wraps `value` which the ledger has just validated.
+        #[inline]
+        pub(crate) fn new(value: T) -> Self {
+            Self(value)
+        }
+    }
+
+    impl<T> AsRef<T> for Wrap<T> {
+        #[inline]
+        fn as_ref(&self) -> &T {
+            &self.0
+        }
+    }
+
+    /// Wrap Pair Type
+    ///
+    /// # Invariant
+    ///
+    /// The right value of a [`WrapPair`] has been validated against the account on the left by the
+    /// [`Ledger`](crate::Ledger) that built it.
+    #[derive(Clone, Copy)]
+    pub struct WrapPair<L, R>(L, R);
+
+    impl<L, R> WrapPair<L, R> {
+        /// Pairs `left` with `right` which the ledger has just validated against `left`.
+        #[inline]
+        pub(crate) fn new(left: L, right: R) -> Self {
+            Self(left, right)
+        }
+
+        /// Returns the left and right values of `self`.
+        #[inline]
+        pub(crate) fn into_inner(self) -> (L, R) {
+            (self.0, self.1)
+        }
+    }
+
+    impl<L, R> AsRef<R> for WrapPair<L, R> {
+        #[inline]
+        fn as_ref(&self) -> &R {
+            &self.1
+        }
     }
 }
 
@@ -879,7 +4257,7 @@ where
         if VoidNumberSet::<T>::contains_key(&void_number) {
             None
         } else {
-            Some(Wrap(void_number))
+            Some(Wrap::new(void_number))
         }
     }
 
@@ -888,8 +4266,8 @@ where
         &self,
         output: config::UtxoAccumulatorOutput,
     ) -> Option<Self::ValidUtxoAccumulatorOutput> {
-        if UtxoAccumulatorOutputs::<T>::contains_key(output) {
-            return Some(Wrap(output));
+        if Pallet::<T>::is_known_root(output) {
+            return Some(Wrap::new(output));
         }
         None
     }
@@ -925,7 +4303,7 @@ where
         if UtxoSet::<T>::contains_key(&utxo) {
             None
         } else {
-            Some(Wrap(utxo))
+            Some(Wrap::new(utxo))
         }
     }
 
@@ -935,21 +4313,18 @@ where
         I: IntoIterator<Item = (Self::ValidUtxo, config::EncryptedNote)>,
     {
         let _ = super_key;
-        let parameters = config::UtxoAccumulatorModel::decode(
-            manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get()
-                .expect("Checksum did not match."),
-        )
-        .expect("Unable to decode the Merkle Tree Parameters.");
         let mut shard_indices = iter
             .into_iter()
             .map(move |(utxo, note)| {
                 (
-                    config::MerkleTreeConfiguration::tree_index(&utxo.0),
+                    Pallet::<T>::utxo_shard(&utxo.0),
                     utxo.0,
-                    note,
+                    EncryptedNote::from(note),
                 )
             })
             .collect::<Vec<_>>();
+        // NOTE: The sort is stable so receivers in the same shard are inserted in post order,
+        //       which keeps the resulting shard roots independent of anything but the post.
         shard_indices.sort_by_key(|(s, _, _)| *s);
         let mut shard_insertions = Vec::<(_, Vec<_>)>::new();
         for (shard_index, utxo, note) in shard_indices {
@@ -958,33 +4333,162 @@ where
                 _ => shard_insertions.push((shard_index, vec![(utxo, note)])),
             }
         }
+        self.touched_shards += shard_insertions.len() as u32;
         for (shard_index, insertions) in shard_insertions {
-            let mut tree = ShardTrees::<T>::get(shard_index);
-            let mut next_root = Option::<config::UtxoAccumulatorOutput>::None;
-            let mut current_path = core::mem::take(&mut tree.current_path).into();
-            for (utxo, note) in insertions {
-                next_root = Some(
-                    merkle_tree::single_path::raw::insert(
-                        &parameters,
-                        &mut tree.leaf_digest,
-                        &mut current_path,
-                        utxo,
-                    )
-                    .expect("If this errors, then we have run out of Merkle Tree capacity."),
-                );
-                let next_index = current_path.leaf_index().0 as u64;
-                UtxoSet::<T>::insert(utxo, ());
-                Shards::<T>::insert(shard_index, next_index, (utxo, EncryptedNote::from(note)));
+            if let Err(context) = self.insert_into_shard(shard_index, insertions) {
+                self.report_inconsistency(context);
             }
-            tree.current_path = current_path.into();
-            if let Some(next_root) = next_root {
-                ShardTrees::<T>::insert(shard_index, tree);
+        }
+    }
+}
+
+impl<T> Ledger<T>
+where
+    T: Config,
+{
+    /// Records that posting found the ledger in an inconsistent state described by `context`,
+    /// keeping only the first inconsistency.
+    #[inline]
+    fn report_inconsistency(&mut self, context: &'static str) {
+        log::error!(target: "manta-pay", "Ledger inconsistency while posting: {}", context);
+        self.inconsistency.get_or_insert(context);
+    }
+
+    /// Returns [`Error::LedgerInconsistent`] if an inconsistency was reported while posting.
+    #[inline]
+    fn check_consistency(&self) -> Result<(), Error<T>> {
+        match self.inconsistency {
+            Some(_) => Err(Error::<T>::LedgerInconsistent),
+            _ => Ok(()),
+        }
+    }
+
+    /// Decodes the verifying context for the shape of `post` unless it is already decoded,
+    /// returning [`Error::ParametersUnavailable`] if it is missing or corrupted. Posts without a
+    /// shape need no verifying context since they fail validation anyway.
+    #[inline]
+    fn load_verifying_context_for(&mut self, post: &TransferPost) -> Result<(), Error<T>> {
+        let shape = match post.shape() {
+            Some(shape) => PostShape::from(shape),
+            _ => return Ok(()),
+        };
+        if !self.verifying_contexts.contains_key(&shape) {
+            let context = Pallet::<T>::decode_verifying_context(
+                &Pallet::<T>::verifying_context_bytes(shape)
+                    .ok_or(Error::<T>::ParametersUnavailable)?,
+            )?;
+            self.verifying_contexts.insert(shape, context);
+        }
+        Ok(())
+    }
+
+    /// Validates and posts `post` to `self`, withdrawing from the `sources` and depositing to the
+    /// `sinks`, and returns the event to deposit for it.
+    ///
+    /// # Note
+    ///
+    /// Every call posting a transfer should go through this function so that validation errors
+    /// are mapped and inconsistencies are reported in the same way.
+    #[inline]
+    fn apply_post(
+        &mut self,
+        sources: Vec<T::AccountId>,
+        sinks: Vec<T::AccountId>,
+        post: TransferPost,
+    ) -> Result<PreprocessedEvent<T>, Error<T>> {
+        self.inconsistency = None;
+        self.load_verifying_context_for(&post)?;
+        let event = config::TransferPost::from(post)
+            .post(sources, sinks, &(), self)
+            .map_err(Error::<T>::from)?;
+        self.check_consistency()?;
+        Ok(event)
+    }
+
+    /// Inserts `insertions` into the shard with the given `shard_index` in iteration order,
+    /// registering each UTXO and storing the new root of the shard.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shard runs out of capacity, in which case the remaining
+    /// insertions are skipped.
+    #[inline]
+    fn insert_into_shard<I>(&self, shard_index: u8, insertions: I) -> Result<(), &'static str>
+    where
+        I: IntoIterator<Item = (config::Utxo, EncryptedNote)>,
+    {
+        let mut tree = ShardTrees::<T>::get(shard_index);
+        let mut next_root = Option::<config::UtxoAccumulatorOutput>::None;
+        let mut current_path = core::mem::take(&mut tree.current_path).into();
+        let mut result = Ok(());
+        let mut inserted = 0;
+        for (utxo, note) in insertions {
+            match merkle_tree::single_path::raw::insert(
+                &self.utxo_accumulator_model,
+                &mut tree.leaf_digest,
+                &mut current_path,
+                utxo,
+            ) {
+                Some(root) => next_root = Some(root),
+                _ => {
+                    result = Err("Merkle tree shard has run out of capacity.");
+                    break;
+                }
+            }
+            let next_index = current_path.leaf_index().0 as u64;
+            UtxoSet::<T>::insert(utxo, ());
+            Shards::<T>::insert(shard_index, next_index, (utxo, note));
+            inserted += 1;
+        }
+        TotalUtxoCount::<T>::mutate(|count| *count = count.saturating_add(inserted));
+        tree.current_path = current_path.into();
+        if let Some(next_root) = next_root {
+            ShardTrees::<T>::insert(shard_index, tree);
+            if !UtxoAccumulatorOutputs::<T>::contains_key(next_root) {
                 UtxoAccumulatorOutputs::<T>::insert(next_root, ());
+                RootCount::<T>::mutate(|count| *count = count.saturating_add(1));
+            }
+            if let Some(previous_root) = LatestRoot::<T>::get(shard_index) {
+                LatestRootSet::<T>::remove(previous_root);
             }
+            LatestRoot::<T>::insert(shard_index, next_root);
+            LatestRootSet::<T>::insert(next_root, ());
         }
+        result
     }
 }
 
+/// Verifies `proof` for the public `input` against the `verifying_context`.
+#[cfg(not(all(test, feature = "skip-verification")))]
+#[inline]
+fn verify_proof(
+    verifying_context: &config::VerifyingContext,
+    input: &ProofInput<config::Config>,
+    proof: &Proof<config::Config>,
+) -> bool {
+    matches!(
+        config::ProofSystem::verify(verifying_context, input, proof),
+        Ok(true)
+    )
+}
+
+/// Accepts every proof without verifying it, so that tests of the pallet plumbing do not pay for
+/// verification.
+///
+/// # Note
+///
+/// This is only compiled into the tests of this crate with the `skip-verification` feature, so
+/// it can never reach a runtime. Every other check of a post still runs.
+#[cfg(all(test, feature = "skip-verification"))]
+#[inline]
+fn verify_proof(
+    _: &config::VerifyingContext,
+    _: &ProofInput<config::Config>,
+    _: &Proof<config::Config>,
+) -> bool {
+    true
+}
+
 impl<T> TransferLedger<config::Config> for Ledger<T>
 where
     T: Config,
@@ -1008,11 +4512,11 @@ where
         // NOTE: Existence of accounts is type-checked so we only need check account balances.
         sources
             .map(move |(account_id, withdraw)| {
-                match Balances::<T>::try_get(&account_id, asset_id.0) {
+                match Balances::<T>::try_get(&account_id, T::AssetId::from(asset_id.0)) {
                     Ok(balance) => {
                         // FIXME: Check if balance would withdraw more than existential deposit.
                         if balance >= withdraw.0 {
-                            Ok(WrapPair(account_id, withdraw))
+                            Ok(WrapPair::new(account_id, withdraw))
                         } else {
                             Err(InvalidSourceAccount {
                                 account_id,
@@ -1042,7 +4546,7 @@ where
         // NOTE: Existence of accounts is type-checked so we don't need to do anything here, just
         //		 pass the data forward.
         Ok(sinks
-            .map(move |(account_id, deposit)| WrapPair(account_id, deposit))
+            .map(move |(account_id, deposit)| WrapPair::new(account_id, deposit))
             .collect())
     }
 
@@ -1056,44 +4560,41 @@ where
         sinks: &[SinkPostingKey<config::Config, Self>],
         proof: Proof<config::Config>,
     ) -> Option<(Self::ValidProof, Self::Event)> {
-        let (mut verifying_context, event) = match TransferShape::select(
+        // NOTE: The shape guarantees the asset id, source, and sink used for the event, but they
+        //       are still looked up fallibly so that an inconsistent shape fails verification
+        //       instead of panicking.
+        let shape = TransferShape::select(
             asset_id.is_some(),
             sources.len(),
             senders.len(),
             receivers.len(),
             sinks.len(),
-        )? {
-            TransferShape::Mint => (
-                manta_sdk::pay::testnet::verifying::Mint::get().expect("Checksum did not match."),
+        )?;
+        let event = match shape {
+            TransferShape::Mint => {
+                let source = sources.first()?;
                 PreprocessedEvent::<T>::Mint {
-                    asset: Asset::new(asset_id.unwrap().0, (sources[0].1).0),
-                    source: sources[0].0.clone(),
-                },
-            ),
-            TransferShape::PrivateTransfer => (
-                manta_sdk::pay::testnet::verifying::PrivateTransfer::get()
-                    .expect("Checksum did not match."),
-                PreprocessedEvent::<T>::PrivateTransfer,
-            ),
-            TransferShape::Reclaim => (
-                manta_sdk::pay::testnet::verifying::Reclaim::get()
-                    .expect("Checksum did not match."),
+                    asset: Asset::new(asset_id?.0, (source.1).0),
+                    source: source.0.clone(),
+                }
+            }
+            TransferShape::PrivateTransfer => PreprocessedEvent::<T>::PrivateTransfer,
+            TransferShape::Reclaim => {
+                let sink = sinks.first()?;
                 PreprocessedEvent::<T>::Reclaim {
-                    asset: Asset::new(asset_id.unwrap().0, (sinks[0].1).0),
-                    sink: sinks[0].0.clone(),
-                },
-            ),
+                    asset: Asset::new(asset_id?.0, (sink.1).0),
+                    sink: sink.0.clone(),
+                }
+            }
         };
-        config::ProofSystem::verify(
-            &config::VerifyingContext::decode(&mut verifying_context)
-                .expect("Unable to decode the verifying context."),
-            &manta_accounting::transfer::TransferPostingKey::generate_proof_input(
-                asset_id, sources, senders, receivers, sinks,
-            ),
+        // NOTE: The context is decoded before posting, so a missing one fails verification.
+        let verifying_context = self.verifying_contexts.get(&PostShape::from(shape))?;
+        verify_proof(
+            verifying_context,
+            &T::ProofInput::generate_proof_input(asset_id, sources, senders, receivers, sinks),
             &proof,
         )
-        .ok()?
-        .then(move || (Wrap(()), event))
+        .then(move || (Wrap::new(()), event))
     }
 
     #[inline]
@@ -1106,11 +4607,28 @@ where
         super_key: &TransferLedgerSuperPostingKey<config::Config, Self>,
     ) {
         let _ = (proof, super_key);
-        for WrapPair(account_id, withdraw) in sources {
-            Balances::<T>::mutate(&account_id, asset_id.0, |balance| *balance -= withdraw.0);
+        // NOTE: Balances are only changed through `adjust_balance`, which removes zeroed entries
+        //       and skips zero deposits so that `Balances` never holds dead entries and the
+        //       holder counts stay in step. `ValueQuery` still reads removed entries as zero.
+        let asset_id = T::AssetId::from(asset_id.0);
+        for (account_id, withdraw) in sources.into_iter().map(WrapPair::into_inner) {
+            if Pallet::<T>::adjust_balance(&account_id, asset_id, BalanceDelta::Debit(withdraw.0))
+                .is_none()
+            {
+                self.report_inconsistency("Source account balance is lower than its withdrawal.");
+            }
+            TotalMinted::<T>::mutate(asset_id, |total| *total = total.saturating_add(withdraw.0));
+            MintValueRange::<T>::mutate(asset_id, |range| {
+                *range = Some(match *range {
+                    Some((min, max)) => (min.min(withdraw.0), max.max(withdraw.0)),
+                    _ => (withdraw.0, withdraw.0),
+                })
+            });
+            UtxoCountPerAsset::<T>::mutate(asset_id, |count| *count = count.saturating_add(1));
         }
-        for WrapPair(account_id, deposit) in sinks {
-            Balances::<T>::mutate(&account_id, asset_id.0, |balance| *balance += deposit.0);
+        for (account_id, deposit) in sinks.into_iter().map(WrapPair::into_inner) {
+            Pallet::<T>::adjust_balance(&account_id, asset_id, BalanceDelta::Credit(deposit.0));
+            TotalReclaimed::<T>::mutate(asset_id, |total| *total = total.saturating_add(deposit.0));
         }
     }
 }
@@ -0,0 +1,70 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage Migrations
+
+use crate::{
+    AccountAssetCount, Balances, Config, HoldersCount, Pallet, RootCount, TotalUtxoCount,
+    UtxoAccumulatorOutputs, UtxoSet,
+};
+use core::marker::PhantomData;
+use frame_support::{
+    traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+    weights::Weight,
+};
+
+/// Populate Counters Migration
+///
+/// Computes [`AccountAssetCount`], [`HoldersCount`], [`RootCount`], and [`TotalUtxoCount`] from
+/// the storage they count, for chains which were running before these counters were added.
+///
+/// # Note
+///
+/// The migration runs once, while the on-chain storage version of the pallet is below `1`, and
+/// then bumps it. Its weight is linear in the number of public balances, UTXOs, and roots.
+pub struct PopulateCounters<T>(PhantomData<T>);
+
+impl<T> OnRuntimeUpgrade for PopulateCounters<T>
+where
+    T: Config,
+{
+    #[inline]
+    fn on_runtime_upgrade() -> Weight {
+        if Pallet::<T>::on_chain_storage_version() >= 1 {
+            return T::DbWeight::get().reads(1);
+        }
+        let _ = AccountAssetCount::<T>::remove_all(None);
+        let _ = HoldersCount::<T>::remove_all(None);
+        let mut reads = 1;
+        let mut writes = 1;
+        for (account, id, value) in Balances::<T>::iter() {
+            reads += 1;
+            if value != 0 {
+                AccountAssetCount::<T>::mutate(account, |count| *count = count.saturating_add(1));
+                HoldersCount::<T>::mutate(id, |count| *count = count.saturating_add(1));
+                writes += 2;
+            }
+        }
+        let utxo_count = UtxoSet::<T>::iter_keys().count() as u64;
+        TotalUtxoCount::<T>::put(utxo_count);
+        let root_count = UtxoAccumulatorOutputs::<T>::iter_keys().count() as u64;
+        RootCount::<T>::put(root_count);
+        reads += utxo_count + root_count;
+        writes += 2;
+        StorageVersion::new(1).put::<Pallet<T>>();
+        T::DbWeight::get().reads_writes(reads as Weight, writes as Weight)
+    }
+}
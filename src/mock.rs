@@ -16,8 +16,18 @@
 
 use frame_support::{
     parameter_types,
-    traits::{ConstU32, Everything},
+    traits::{ConstU32, Everything, GenesisBuild},
+    weights::RuntimeDbWeight,
+    PalletId,
 };
+use manta_accounting::{
+    asset,
+    transfer::{
+        ProofInput, ReceiverPostingKey, SenderPostingKey, SinkPostingKey, SourcePostingKey,
+        TransferLedger,
+    },
+};
+use manta_pay::config;
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
@@ -43,13 +53,14 @@ type BlockNumber = u64;
 parameter_types! {
     pub const BlockHashCount: BlockNumber = 250;
     pub const SS58Prefix: u8 = 42;
+    pub const MantaPayPalletId: PalletId = PalletId(*b"mantapay");
 }
 
 impl frame_system::Config for Test {
     type BaseCallFilter = Everything;
     type BlockWeights = ();
     type BlockLength = ();
-    type DbWeight = ();
+    type DbWeight = DbWeight;
     type Origin = Origin;
     type Call = Call;
     type Index = u64;
@@ -72,14 +83,166 @@ impl frame_system::Config for Test {
     type MaxConsumers = ConstU32<16>;
 }
 
+parameter_types! {
+    pub static MaxPendingPosts: u32 = 0;
+    pub static MaxUtxoCount: u64 = u64::MAX;
+    pub static PublicTransfersEnabled: bool = true;
+    pub static RequireAssetInitForMint: bool = true;
+    pub static StrictRootMode: bool = false;
+    pub static EmitDetailedEvents: bool = true;
+    pub static MaxAssetsPerAccount: Option<u32> = None;
+    pub static ShieldedFee: Option<u128> = None;
+    pub static MinReclaimValue: u128 = 0;
+    pub static ReclaimCooldown: u64 = 0;
+    pub static MinAnonymitySet: u64 = 0;
+    pub static PoolLowWatermark: Option<u128> = None;
+    pub static FeeAssetId: u32 = 0;
+    pub static FeeDestination: u64 = 0;
+    pub static PrependProofInput: bool = false;
+    pub static MaxNoteSize: u32 = 1024;
+    pub static MaxProofSize: u32 = 1024;
+    pub static MaxProcessedBatches: u32 = 4;
+    pub static DbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 0, write: 0 };
+}
+
+/// Proof Input Generator which prepends a constant to the SDK public input when
+/// [`PrependProofInput`] is set.
+pub struct MockProofInput;
+
+impl crate::GenerateProofInput for MockProofInput {
+    #[inline]
+    fn is_sdk_layout() -> bool {
+        !PrependProofInput::get()
+    }
+
+    #[inline]
+    fn generate_proof_input<L>(
+        asset_id: Option<asset::AssetId>,
+        sources: &[SourcePostingKey<config::Config, L>],
+        senders: &[SenderPostingKey<config::Config, L>],
+        receivers: &[ReceiverPostingKey<config::Config, L>],
+        sinks: &[SinkPostingKey<config::Config, L>],
+    ) -> ProofInput<config::Config>
+    where
+        L: TransferLedger<config::Config>,
+    {
+        let mut input = <() as crate::GenerateProofInput>::generate_proof_input(
+            asset_id, sources, senders, receivers, sinks,
+        );
+        if PrependProofInput::get() {
+            input.insert(0, Default::default());
+        }
+        input
+    }
+}
+
+/// Pool Watermark which applies [`PoolLowWatermark`] to every asset.
+pub struct MockPoolWatermark;
+
+impl crate::PoolWatermark<u32> for MockPoolWatermark {
+    #[inline]
+    fn low_watermark(_: u32) -> Option<u128> {
+        PoolLowWatermark::get()
+    }
+}
+
 impl crate::Config for Test {
     type Event = Event;
     type WeightInfo = crate::weights::WeightInfo<Self>;
+    type AssetId = u32;
+    type MaxPendingPosts = MaxPendingPosts;
+    type MaxPrivateBatch = ConstU32<4>;
+    type MaxPublicBatch = ConstU32<8>;
+    type MaxProcessedBatches = MaxProcessedBatches;
+    type PublicTransfersEnabled = PublicTransfersEnabled;
+    type RequireAssetInitForMint = RequireAssetInitForMint;
+    type StrictRootMode = StrictRootMode;
+    type MaxVoidNumberQuery = ConstU32<4>;
+    type MaxRootQuery = ConstU32<4>;
+    type MaxVoidNumberExport = ConstU32<8>;
+    type MaxNotePage = ConstU32<16>;
+    type MaxNoteSize = MaxNoteSize;
+    type MaxProofSize = MaxProofSize;
+    type MaxUtxoCount = MaxUtxoCount;
+    type EmitDetailedEvents = EmitDetailedEvents;
+    type MaxAssetsPerAccount = MaxAssetsPerAccount;
+    type ShieldedFee = ShieldedFee;
+    type MinReclaimValue = MinReclaimValue;
+    type ReclaimCooldown = ReclaimCooldown;
+    type MinAnonymitySet = MinAnonymitySet;
+    type PoolLowWatermark = MockPoolWatermark;
+    type FeeAssetId = FeeAssetId;
+    type FeeDestination = FeeDestination;
+    type PalletId = MantaPayPalletId;
+    type ProofInput = MockProofInput;
 }
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    frame_system::GenesisConfig::default()
-        .build_storage::<Test>()
-        .unwrap()
-        .into()
+    ExtBuilder::default().build()
+}
+
+/// Test Externalities Builder
+///
+/// Seeds the genesis state of the pallet so that tests can declare the assets they start with.
+pub struct ExtBuilder {
+    /// Owner of the Genesis Assets
+    owner: u64,
+
+    /// Genesis Assets
+    assets: Vec<(u32, u128)>,
+
+    /// Genesis Parameters
+    parameters: crate::types::GenesisParameters,
+}
+
+impl Default for ExtBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            owner: 1,
+            assets: Vec::new(),
+            parameters: Default::default(),
+        }
+    }
+}
+
+impl ExtBuilder {
+    /// Sets the account which owns the full supply of every genesis asset.
+    #[inline]
+    pub fn with_owner(mut self, owner: u64) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    /// Adds `assets` as `(id, total)` pairs to the genesis assets.
+    #[inline]
+    pub fn with_assets(mut self, assets: Vec<(u32, u128)>) -> Self {
+        self.assets.extend(assets);
+        self
+    }
+
+    /// Sets the sources of the parameters stored at genesis.
+    #[inline]
+    pub fn with_parameters(mut self, parameters: crate::types::GenesisParameters) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    /// Builds the test externalities from the genesis configuration.
+    #[inline]
+    pub fn build(self) -> sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Test>()
+            .unwrap();
+        GenesisBuild::<Test>::assimilate_storage(
+            &crate::GenesisConfig::<Test> {
+                owner: self.owner,
+                assets: self.assets.into_iter().collect(),
+                parameters: self.parameters,
+            },
+            &mut storage,
+        )
+        .unwrap();
+        storage.into()
+    }
 }
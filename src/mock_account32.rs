@@ -0,0 +1,142 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Mock Runtime with 32-byte Account Ids
+//!
+//! Mirrors [`crate::mock`] with the [`AccountId32`] and [`AccountIdLookup`] types used by real
+//! runtimes, so that tests can catch bugs hidden by plain integer account ids. Asset ids are
+//! `u64`, wider than the protocol asset ids, so that tests can cross the asset id boundary.
+
+use crate::{
+    mock::{
+        EmitDetailedEvents, FeeAssetId, MantaPayPalletId, MaxAssetsPerAccount, MaxNoteSize,
+        MaxPendingPosts, MaxProcessedBatches, MaxProofSize, MaxUtxoCount, MinAnonymitySet,
+        MinReclaimValue, PublicTransfersEnabled, ReclaimCooldown, RequireAssetInitForMint,
+        ShieldedFee, StrictRootMode,
+    },
+    PalletAccount,
+};
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, Everything, Get},
+};
+use sp_core::{crypto::AccountId32, H256};
+use sp_runtime::{
+    testing::Header,
+    traits::{AccountIdLookup, BlakeTwo256},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test32>;
+type Block = frame_system::mocking::MockBlock<Test32>;
+
+frame_support::construct_runtime!(
+    pub enum Test32 where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        MantaPayPallet: crate::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+type BlockNumber = u64;
+
+parameter_types! {
+    pub const BlockHashCount: BlockNumber = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test32 {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = BlockNumber;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId32;
+    type Lookup = AccountIdLookup<AccountId32, ()>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+/// Fee Asset Id which widens [`FeeAssetId`] into the runtime asset id type.
+pub struct WideFeeAssetId;
+
+impl Get<u64> for WideFeeAssetId {
+    #[inline]
+    fn get() -> u64 {
+        FeeAssetId::get().into()
+    }
+}
+
+impl crate::Config for Test32 {
+    type Event = Event;
+    type WeightInfo = crate::weights::WeightInfo<Self>;
+    type AssetId = u64;
+    type MaxPendingPosts = MaxPendingPosts;
+    type MaxPrivateBatch = ConstU32<4>;
+    type MaxPublicBatch = ConstU32<8>;
+    type MaxProcessedBatches = MaxProcessedBatches;
+    type PublicTransfersEnabled = PublicTransfersEnabled;
+    type RequireAssetInitForMint = RequireAssetInitForMint;
+    type StrictRootMode = StrictRootMode;
+    type MaxVoidNumberQuery = ConstU32<4>;
+    type MaxRootQuery = ConstU32<4>;
+    type MaxVoidNumberExport = ConstU32<8>;
+    type MaxNotePage = ConstU32<16>;
+    type MaxNoteSize = MaxNoteSize;
+    type MaxProofSize = MaxProofSize;
+    type MaxUtxoCount = MaxUtxoCount;
+    type EmitDetailedEvents = EmitDetailedEvents;
+    type MaxAssetsPerAccount = MaxAssetsPerAccount;
+    type ShieldedFee = ShieldedFee;
+    type MinReclaimValue = MinReclaimValue;
+    type ReclaimCooldown = ReclaimCooldown;
+    type MinAnonymitySet = MinAnonymitySet;
+    type PoolLowWatermark = ();
+    type FeeAssetId = WideFeeAssetId;
+    type FeeDestination = PalletAccount<Self>;
+    type PalletId = MantaPayPalletId;
+    type ProofInput = ();
+}
+
+/// Returns the account id derived from `seed`.
+#[inline]
+pub fn account(seed: u8) -> AccountId32 {
+    AccountId32::new([seed; 32])
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    frame_system::GenesisConfig::default()
+        .build_storage::<Test32>()
+        .unwrap()
+        .into()
+}
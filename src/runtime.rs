@@ -0,0 +1,141 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! MantaPay Runtime API
+//!
+//! Runtimes implement [`MantaPayApi`] by forwarding each method to the function of the same name
+//! on [`Pallet`](crate::Pallet).
+
+use crate::types::{
+    AssetValue, CurrentPath, EncryptedNote, LedgerSnapshot, NoteCursor, NotePage, PalletLimits,
+    PostDescription, PostShape, QueryError, ShapeDims, ShapeError, StorageStats, TransferPost,
+};
+use alloc::vec::Vec;
+use manta_pay::config;
+use scale_codec::Codec;
+
+sp_api::decl_runtime_apis! {
+    /// MantaPay Runtime API
+    pub trait MantaPayApi<AccountId, AssetId>
+    where
+        AccountId: Codec,
+        AssetId: Codec,
+    {
+        /// Pulls at most `limit`-many notes from the shards in the inclusive `shard_range`,
+        /// starting at `cursor`.
+        fn pull_notes(shard_range: (u8, u8), cursor: NoteCursor, limit: u32) -> NotePage;
+
+        /// Returns the number of void numbers that have been spent.
+        fn void_number_count() -> u64;
+
+        /// Returns the sizes of the shielded ledger storage items for monitoring state growth.
+        fn storage_stats() -> StorageStats;
+
+        /// Returns `true` if the UTXO of the mint `post` commits to its declared asset id and
+        /// value.
+        fn is_consistent_mint(post: TransferPost) -> bool;
+
+        /// Returns whether each of the `void_numbers` has been spent, rejecting queries of more
+        /// than `MaxVoidNumberQuery`-many of them.
+        fn are_spent(void_numbers: Vec<config::VoidNumber>) -> Result<Vec<bool>, QueryError>;
+
+        /// Returns a fingerprint of the protocol parameters used for verification.
+        fn parameters_fingerprint() -> [u8; 32];
+
+        /// Exports the shielded ledger for snapshotting.
+        fn export_ledger() -> LedgerSnapshot;
+
+        /// Exports at most `limit`-many spent void numbers in insertion order, starting at the
+        /// insertion index `cursor`, together with the cursor of the next page.
+        fn export_void_numbers(cursor: u64, limit: u32) -> (Vec<config::VoidNumber>, Option<u64>);
+
+        /// Returns the index of the shard which `utxo` is inserted into, so that clients can
+        /// target the right pull and path queries.
+        fn utxo_shard(utxo: config::Utxo) -> u8;
+
+        /// Returns the UTXO and encrypted note most recently inserted into the shard with the
+        /// given `shard_index`, together with its leaf index.
+        fn latest_utxo(shard_index: u8) -> Option<(config::Utxo, EncryptedNote, u64)>;
+
+        /// Returns `true` if `root` is known and `path` proves that `utxo` is a leaf of the shard
+        /// tree with the given `root`, so that wallets can check the paths they cached.
+        fn verify_membership(
+            utxo: config::Utxo,
+            path: CurrentPath,
+            root: config::UtxoAccumulatorOutput,
+        ) -> bool;
+
+        /// Returns `true` if `root` is a UTXO accumulator output that senders may build proofs
+        /// against.
+        fn is_known_root(root: config::UtxoAccumulatorOutput) -> bool;
+
+        /// Returns whether each of the `roots` is known, rejecting queries of more than
+        /// `MaxRootQuery`-many of them.
+        fn known_roots(roots: Vec<config::UtxoAccumulatorOutput>) -> Result<Vec<bool>, QueryError>;
+
+        /// Returns `true` if `post` would be accepted when submitted by `origin` and at least one
+        /// of its sender posts was built against `root`.
+        fn dry_run_against_root(
+            origin: AccountId,
+            post: TransferPost,
+            root: config::UtxoAccumulatorOutput,
+        ) -> bool;
+
+        /// Returns the index and UTXO of the first receiver post of `post` whose UTXO is already
+        /// registered.
+        fn find_registered_receiver(post: TransferPost) -> Option<(u32, config::Utxo)>;
+
+        /// Returns the total value of the asset with the given `id` ever minted into the shielded
+        /// pool.
+        fn total_minted(id: AssetId) -> AssetValue;
+
+        /// Returns the total value of the asset with the given `id` ever reclaimed from the
+        /// shielded pool.
+        fn total_reclaimed(id: AssetId) -> AssetValue;
+
+        /// Returns the smallest and largest value of the asset with the given `id` minted by a
+        /// single source.
+        fn mint_value_range(id: AssetId) -> Option<(AssetValue, AssetValue)>;
+
+        /// Describes the shape and public parts of `post` for block explorers.
+        fn describe_post(post: TransferPost) -> PostDescription;
+
+        /// Returns the reason why `post` matches none of the transfer shapes, so that clients can
+        /// tell which count was wrong when it is rejected with `InvalidShape`.
+        fn shape_error(post: TransferPost) -> Option<ShapeError>;
+
+        /// Returns the asset id presence and participant counts expected of every transfer
+        /// shape, so that clients can assemble posts without hard-coding them.
+        fn shape_requirements() -> Vec<(PostShape, ShapeDims)>;
+
+        /// Returns the configured limits of the pallet.
+        fn limits() -> PalletLimits;
+
+        /// Splits `total` into power-of-two denominations which wallets can mint as a batch.
+        fn suggest_mint_denominations(total: AssetValue) -> Vec<AssetValue>;
+
+        /// Returns the account which owned the asset with the given `id` when it was initialized.
+        fn asset_owner(id: AssetId) -> Option<AccountId>;
+
+        /// Returns the number of accounts with a nonzero public balance of the asset with the
+        /// given `id`.
+        fn total_accounts_holding(id: AssetId) -> u32;
+
+        /// Returns the storage key of the public balance of `account` for the asset with the
+        /// given `id`, for requesting a read proof of that balance.
+        fn balance_storage_key(account: AccountId, id: AssetId) -> Vec<u8>;
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! SDK Parameters
+//!
+//! Loads the protocol parameters and the proving and verifying contexts shipped with the SDK, so
+//! that the tests, the demo, and the coin precomputation build proofs from the same parameters.
+
+use core::fmt::Debug;
+use manta_pay::config::{
+    KeyAgreementScheme, MultiProvingContext, MultiVerifyingContext, Parameters, ProvingContext,
+    UtxoAccumulatorModel, UtxoCommitmentScheme, VerifyingContext, VoidNumberHashFunction,
+};
+use manta_util::codec::{Decode, IoReader};
+use std::{fs::File, path::Path, thread, time::Duration};
+
+/// Number of Attempts at Downloading each Proving Context
+const DOWNLOAD_ATTEMPTS: u32 = 4;
+
+/// Delay before the First Download Retry, Doubled after every Failed Attempt
+const DOWNLOAD_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Runs `download` until it succeeds, at most [`DOWNLOAD_ATTEMPTS`] times with exponential
+/// backoff, returning `false` after logging a warning for every failed attempt.
+#[inline]
+fn download_with_retry<E, F>(name: &str, mut download: F) -> bool
+where
+    E: Debug,
+    F: FnMut() -> Result<(), E>,
+{
+    let mut backoff = DOWNLOAD_BACKOFF;
+    for attempt in 1..=DOWNLOAD_ATTEMPTS {
+        match download() {
+            Ok(()) => return true,
+            Err(err) => eprintln!(
+                "warning: attempt {}/{} at downloading the {} proving context failed: {:?}",
+                attempt, DOWNLOAD_ATTEMPTS, name, err
+            ),
+        }
+        if attempt < DOWNLOAD_ATTEMPTS {
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+    false
+}
+
+/// Downloads the [`MultiProvingContext`] from the SDK into `directory`, returning `None` if any
+/// proving context could not be downloaded.
+#[inline]
+pub fn download_proving_context(directory: &Path) -> Option<MultiProvingContext> {
+    let mint_path = directory.join("mint.dat");
+    let private_transfer_path = directory.join("private-transfer.dat");
+    let reclaim_path = directory.join("reclaim.dat");
+    let downloaded = download_with_retry("MINT", || {
+        manta_sdk::pay::testnet::proving::Mint::download(&mint_path)
+    }) && download_with_retry("PRIVATE_TRANSFER", || {
+        manta_sdk::pay::testnet::proving::PrivateTransfer::download(&private_transfer_path)
+    }) && download_with_retry("RECLAIM", || {
+        manta_sdk::pay::testnet::proving::Reclaim::download(&reclaim_path)
+    });
+    if !downloaded {
+        return None;
+    }
+    Some(MultiProvingContext {
+        mint: ProvingContext::decode(IoReader(
+            File::open(mint_path).expect("Unable to open MINT proving context file."),
+        ))
+        .expect("Unable to decode MINT proving context."),
+        private_transfer: ProvingContext::decode(IoReader(
+            File::open(private_transfer_path)
+                .expect("Unable to open PRIVATE_TRANSFER proving context file."),
+        ))
+        .expect("Unable to decode PRIVATE_TRANSFER proving context."),
+        reclaim: ProvingContext::decode(IoReader(
+            File::open(reclaim_path).expect("Unable to open RECLAIM proving context file."),
+        ))
+        .expect("Unable to decode RECLAIM proving context."),
+    })
+}
+
+/// Loads the [`MultiVerifyingContext`] from the SDK.
+#[inline]
+pub fn load_verifying_context() -> MultiVerifyingContext {
+    MultiVerifyingContext {
+        mint: VerifyingContext::decode(
+            manta_sdk::pay::testnet::verifying::Mint::get().expect("Checksum did not match."),
+        )
+        .expect("Unable to decode MINT verifying context."),
+        private_transfer: VerifyingContext::decode(
+            manta_sdk::pay::testnet::verifying::PrivateTransfer::get()
+                .expect("Checksum did not match."),
+        )
+        .expect("Unable to decode PRIVATE_TRANSFER verifying context."),
+        reclaim: VerifyingContext::decode(
+            manta_sdk::pay::testnet::verifying::Reclaim::get().expect("Checksum did not match."),
+        )
+        .expect("Unable to decode RECLAIM verifying context."),
+    }
+}
+
+/// Loads the [`Parameters`] from the SDK.
+#[inline]
+pub fn load_parameters() -> Parameters {
+    Parameters {
+        key_agreement: KeyAgreementScheme::decode(
+            manta_sdk::pay::testnet::parameters::KeyAgreement::get()
+                .expect("Checksum did not match."),
+        )
+        .expect("Unable to decode KEY_AGREEMENT parameters."),
+        utxo_commitment: UtxoCommitmentScheme::decode(
+            manta_sdk::pay::testnet::parameters::UtxoCommitmentScheme::get()
+                .expect("Checksum did not match."),
+        )
+        .expect("Unable to decode UTXO_COMMITMENT_SCHEME parameters."),
+        void_number_hash: VoidNumberHashFunction::decode(
+            manta_sdk::pay::testnet::parameters::VoidNumberHashFunction::get()
+                .expect("Checksum did not match."),
+        )
+        .expect("Unable to decode VOID_NUMBER_HASH_FUNCTION parameters."),
+    }
+}
+
+/// Loads the [`UtxoAccumulatorModel`] from the SDK.
+#[inline]
+pub fn load_utxo_accumulator_model() -> UtxoAccumulatorModel {
+    UtxoAccumulatorModel::decode(
+        manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get()
+            .expect("Checksum did not match."),
+    )
+    .expect("Unable to decode UTXO_ACCUMULATOR_MODEL.")
+}
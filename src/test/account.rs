@@ -0,0 +1,193 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::frame::sample_mint;
+use crate::{
+    mock::{self, FeeAssetId, MantaPayPalletId, ShieldedFee, Test},
+    mock_account32::{self, account, Test32},
+    Asset, Config, Error, Pallet,
+};
+use frame_support::{assert_noop, assert_ok, traits::Get};
+use frame_system::RawOrigin;
+use manta_accounting::asset::AssetId;
+use manta_crypto::rand::Rand;
+use rand::thread_rng;
+use sp_runtime::traits::{AccountIdConversion, StaticLookup};
+
+/// Transfers public assets from `source` to `target` on the runtime `T`.
+#[inline]
+fn public_transfer_should_work_for<T>(source: T::AccountId, target: T::AccountId)
+where
+    T: Config,
+{
+    Pallet::<T>::init_asset(&source, T::AssetId::from(7), 100);
+    assert_ok!(Pallet::<T>::transfer(
+        RawOrigin::Signed(source.clone()).into(),
+        T::Lookup::unlookup(target.clone()),
+        Asset::new(7, 40).convert_id()
+    ));
+    assert_eq!(Pallet::<T>::balance(source, T::AssetId::from(7)), 60);
+    assert_eq!(Pallet::<T>::balance(target, T::AssetId::from(7)), 40);
+}
+
+/// Mints a sampled coin from the public balance of `owner` on the runtime `T`.
+#[inline]
+fn mint_should_work_for<T>(owner: T::AccountId)
+where
+    T: Config,
+{
+    let mut rng = thread_rng();
+    let asset_id: AssetId = rng.gen();
+    let id = T::AssetId::from(asset_id.0);
+    Pallet::<T>::init_asset(&owner, id, 1_000_000);
+    assert_ok!(Pallet::<T>::mint(
+        RawOrigin::Signed(owner.clone()).into(),
+        sample_mint(asset_id.value(10), &mut rng).into()
+    ));
+    assert_eq!(Pallet::<T>::balance(owner, id), 1_000_000 - 10);
+}
+
+/// Tests public transfers with `u64` account ids.
+#[test]
+fn public_transfer_should_work() {
+    mock::new_test_ext().execute_with(|| public_transfer_should_work_for::<Test>(1, 2));
+}
+
+/// Tests public transfers with `AccountId32` account ids.
+#[test]
+fn public_transfer_should_work_with_account_id32() {
+    mock_account32::new_test_ext()
+        .execute_with(|| public_transfer_should_work_for::<Test32>(account(1), account(2)));
+}
+
+/// Tests mints with `u64` account ids.
+#[test]
+fn mint_should_work() {
+    require_proving_context!();
+    mock::new_test_ext().execute_with(|| mint_should_work_for::<Test>(1));
+}
+
+/// Tests mints with `AccountId32` account ids.
+#[test]
+fn mint_should_work_with_account_id32() {
+    require_proving_context!();
+    mock_account32::new_test_ext().execute_with(|| mint_should_work_for::<Test32>(account(1)));
+}
+
+/// Tests that the pallet account is derived from the pallet id and receives the shielded fees of
+/// runtimes which route fees to it, with the fee paid in a different asset than the minted one.
+#[test]
+fn shielded_fee_should_route_to_pallet_account() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let asset_id: AssetId = rng.gen();
+    mock_account32::new_test_ext().execute_with(|| {
+        let pallet_account = Pallet::<Test32>::account_id();
+        assert_eq!(
+            pallet_account,
+            AccountIdConversion::<<Test32 as frame_system::Config>::AccountId>::into_account(
+                &MantaPayPalletId::get()
+            )
+        );
+        assert_eq!(<Test32 as Config>::FeeDestination::get(), pallet_account);
+        assert_ne!(pallet_account, account(1));
+        Pallet::<Test32>::init_asset(&account(1), asset_id.0.into(), 100);
+        ShieldedFee::set(&Some(3));
+        FeeAssetId::set(&asset_id.0);
+        mint_should_work_for::<Test32>(account(1));
+        assert_eq!(Pallet::<Test32>::balance(account(1), asset_id.0.into()), 97);
+        assert_eq!(
+            Pallet::<Test32>::balance(pallet_account, asset_id.0.into()),
+            3
+        );
+    });
+}
+
+/// Tests that runtime asset ids are checked at both sides of the boundary with the narrower
+/// protocol asset ids.
+#[test]
+fn asset_id_out_of_range_should_not_work() {
+    mock_account32::new_test_ext().execute_with(|| {
+        let max = u64::from(crate::AssetId::MAX);
+        assert_eq!(
+            Pallet::<Test32>::protocol_asset_id(max),
+            Some(crate::AssetId::MAX)
+        );
+        assert_eq!(Pallet::<Test32>::protocol_asset_id(max + 1), None);
+        assert!(matches!(
+            Pallet::<Test32>::runtime_asset_id(crate::AssetId::MAX),
+            Ok(id) if id == max
+        ));
+        assert_noop!(
+            Pallet::<Test32>::initialize_asset(
+                RawOrigin::Root.into(),
+                <Test32 as frame_system::Config>::Lookup::unlookup(account(1)),
+                max + 1,
+                100
+            ),
+            Error::<Test32>::AssetIdOutOfRange
+        );
+        assert_ok!(Pallet::<Test32>::initialize_asset(
+            RawOrigin::Root.into(),
+            <Test32 as frame_system::Config>::Lookup::unlookup(account(1)),
+            max,
+            100
+        ));
+        assert_ok!(Pallet::<Test32>::transfer(
+            RawOrigin::Signed(account(1)).into(),
+            <Test32 as frame_system::Config>::Lookup::unlookup(account(2)),
+            Asset { id: max, value: 40 }
+        ));
+        assert_eq!(Pallet::<Test32>::balance(account(2), max), 40);
+        assert_noop!(
+            Pallet::<Test32>::transfer(
+                RawOrigin::Signed(account(1)).into(),
+                <Test32 as frame_system::Config>::Lookup::unlookup(account(2)),
+                Asset {
+                    id: max + 1,
+                    value: 10
+                }
+            ),
+            Error::<Test32>::UninitializedSupply
+        );
+    });
+}
+
+/// Tests that public transfers take and report the runtime asset ids of runtimes whose asset ids
+/// are wider than the protocol asset ids.
+#[test]
+fn public_transfer_should_use_runtime_asset_ids() {
+    mock_account32::new_test_ext().execute_with(|| {
+        frame_system::Pallet::<Test32>::set_block_number(1);
+        let asset = Asset {
+            id: u64::from(crate::AssetId::MAX),
+            value: 40,
+        };
+        Pallet::<Test32>::init_asset(&account(1), asset.id, 100);
+        assert_ok!(Pallet::<Test32>::transfer(
+            RawOrigin::Signed(account(1)).into(),
+            <Test32 as frame_system::Config>::Lookup::unlookup(account(2)),
+            asset
+        ));
+        frame_system::Pallet::<Test32>::assert_last_event(mock_account32::Event::MantaPayPallet(
+            crate::Event::Transfer {
+                asset,
+                source: account(1),
+                sink: account(2),
+            },
+        ));
+    });
+}
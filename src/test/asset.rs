@@ -0,0 +1,731 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    migrations::PopulateCounters,
+    mock::{
+        new_test_ext, Event, ExtBuilder, MantaPayPallet, MaxAssetsPerAccount, MaxProcessedBatches,
+        Origin, PublicTransfersEnabled, System, Test,
+    },
+    AccountAssetCount, BalanceDelta, Balances, BatchMode, Error, HoldersCount, TotalSupply,
+};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+use sp_runtime::DispatchError;
+
+/// Tests that initializing an asset through the extrinsic emits an event.
+#[test]
+fn initialize_asset_should_emit_event() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(MantaPayPallet::initialize_asset(Origin::root(), 2, 7, 100));
+        System::assert_last_event(Event::MantaPayPallet(crate::Event::AssetInitialized {
+            id: 7,
+            owner: 2,
+            total: 100,
+        }));
+        assert_eq!(MantaPayPallet::balance(2, 7), 100);
+        assert_eq!(MantaPayPallet::total_supply(7), 100);
+    });
+}
+
+/// Tests that an asset cannot be initialized twice or by a signed origin.
+#[test]
+fn initialize_asset_twice_should_not_work() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MantaPayPallet::initialize_asset(Origin::signed(1), 1, 7, 100),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(MantaPayPallet::initialize_asset(Origin::root(), 1, 7, 100));
+        assert_noop!(
+            MantaPayPallet::initialize_asset(Origin::root(), 2, 7, 100),
+            Error::<Test>::AssetInitialized
+        );
+    });
+}
+
+/// Tests that draining a balance removes its entry from storage.
+#[test]
+fn full_drain_transfer_should_remove_balance_entry() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 40)
+            ));
+            assert!(Balances::<Test>::contains_key(1, 7));
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 60)
+            ));
+            assert!(!Balances::<Test>::contains_key(1, 7));
+            assert_eq!(MantaPayPallet::balance(1, 7), 0);
+            assert_eq!(MantaPayPallet::balance(2, 7), 100);
+        });
+}
+
+/// Tests that `u32` runtime asset ids map onto the protocol asset ids used by private transfers.
+#[test]
+fn runtime_asset_id_should_map_into_protocol() {
+    new_test_ext().execute_with(|| {
+        let protocol_id: crate::AssetId = 7;
+        let runtime_id = <Test as crate::Config>::AssetId::from(protocol_id);
+        assert_eq!(runtime_id, 7u32);
+        assert_eq!(
+            MantaPayPallet::protocol_asset_id(runtime_id),
+            Some(protocol_id)
+        );
+        assert_eq!(
+            MantaPayPallet::protocol_asset_id(u32::MAX),
+            crate::AssetId::try_from(u32::MAX).ok()
+        );
+        MantaPayPallet::init_asset(&1, runtime_id, 100);
+        assert_ok!(MantaPayPallet::transfer(
+            Origin::signed(1),
+            2,
+            crate::Asset::new(protocol_id, 40)
+        ));
+        assert_eq!(MantaPayPallet::balance(2, runtime_id), 40);
+    });
+}
+
+/// Tests that a read proof of a public balance verifies against the state root, including a proof
+/// of a zero balance, and fails against any other root.
+#[cfg(feature = "client")]
+#[test]
+fn balance_proof_should_verify_against_state_root() {
+    use crate::client::verify_balance_proof;
+    use sp_runtime::traits::BlakeTwo256;
+    let mut ext = new_test_ext();
+    let (key, empty_key) = ext.execute_with(|| {
+        MantaPayPallet::init_asset(&1, 7, 100);
+        (
+            MantaPayPallet::balance_storage_key(1, 7),
+            MantaPayPallet::balance_storage_key(2, 7),
+        )
+    });
+    let backend = ext.as_backend();
+    let root = *backend.root();
+    let proof = sp_state_machine::prove_read(backend, [&key, &empty_key])
+        .expect("Proving existing storage should work.");
+    assert_eq!(
+        verify_balance_proof::<BlakeTwo256>(root, proof.clone(), &key).ok(),
+        Some(100)
+    );
+    assert_eq!(
+        verify_balance_proof::<BlakeTwo256>(root, proof.clone(), &empty_key).ok(),
+        Some(0)
+    );
+    assert!(verify_balance_proof::<BlakeTwo256>(Default::default(), proof, &key).is_err());
+}
+
+/// Tests that a batch of public transfers is applied in full or not at all.
+#[test]
+fn batch_transfer_should_be_atomic() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            assert_ok!(MantaPayPallet::batch_transfer(
+                Origin::signed(1),
+                vec![(2, crate::Asset::new(7, 30)), (3, crate::Asset::new(7, 20))],
+                BatchMode::Atomic,
+                None
+            ));
+            assert_eq!(MantaPayPallet::balance(1, 7), 50);
+            assert_eq!(MantaPayPallet::balance(2, 7), 30);
+            assert_eq!(MantaPayPallet::balance(3, 7), 20);
+            assert_noop!(
+                MantaPayPallet::batch_transfer(
+                    Origin::signed(1),
+                    vec![(2, crate::Asset::new(7, 30)), (3, crate::Asset::new(7, 30))],
+                    BatchMode::Atomic,
+                    None
+                ),
+                Error::<Test>::BalanceLow
+            );
+        });
+}
+
+/// Tests that a failing middle leg reverts the whole batch in atomic mode and only itself in
+/// best-effort mode, where the outcome of every leg is reported.
+#[test]
+fn batch_mode_should_decide_failed_leg_handling() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            let transfers = vec![
+                (2, crate::Asset::new(7, 10)),
+                (3, crate::Asset::new(8, 10)),
+                (4, crate::Asset::new(7, 10)),
+            ];
+            assert_noop!(
+                MantaPayPallet::batch_transfer(
+                    Origin::signed(1),
+                    transfers.clone(),
+                    BatchMode::Atomic,
+                    None
+                ),
+                Error::<Test>::UninitializedSupply
+            );
+            assert_ok!(MantaPayPallet::batch_transfer(
+                Origin::signed(1),
+                transfers,
+                BatchMode::BestEffort,
+                None
+            ));
+            assert_eq!(MantaPayPallet::balance(1, 7), 80);
+            assert_eq!(MantaPayPallet::balance(2, 7), 10);
+            assert_eq!(MantaPayPallet::balance(3, 8), 0);
+            assert_eq!(MantaPayPallet::balance(4, 7), 10);
+            let outcomes = System::events()
+                .into_iter()
+                .filter_map(|record| match record.event {
+                    Event::MantaPayPallet(crate::Event::BatchLegOutcome { index, result }) => {
+                        Some((index, result))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                outcomes,
+                vec![
+                    (0, Ok(())),
+                    (1, Err(Error::<Test>::UninitializedSupply.into())),
+                    (2, Ok(())),
+                ]
+            );
+        });
+}
+
+/// Tests that a resubmitted batch with the same idempotency key is rejected for the same account
+/// only.
+#[test]
+fn resubmitted_batch_transfer_should_not_work() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 50)
+            ));
+            let transfers = vec![(3, crate::Asset::new(7, 10))];
+            assert_ok!(MantaPayPallet::batch_transfer(
+                Origin::signed(1),
+                transfers.clone(),
+                BatchMode::Atomic,
+                Some([1; 32])
+            ));
+            assert_noop!(
+                MantaPayPallet::batch_transfer(
+                    Origin::signed(1),
+                    transfers.clone(),
+                    BatchMode::Atomic,
+                    Some([1; 32])
+                ),
+                Error::<Test>::BatchAlreadyProcessed
+            );
+            assert_ok!(MantaPayPallet::batch_transfer(
+                Origin::signed(2),
+                transfers.clone(),
+                BatchMode::Atomic,
+                Some([1; 32])
+            ));
+            assert_ok!(MantaPayPallet::batch_transfer(
+                Origin::signed(1),
+                transfers.clone(),
+                BatchMode::Atomic,
+                None
+            ));
+            assert_ok!(MantaPayPallet::batch_transfer(
+                Origin::signed(1),
+                transfers,
+                BatchMode::Atomic,
+                None
+            ));
+            assert_eq!(MantaPayPallet::balance(3, 7), 40);
+        });
+}
+
+/// Tests that the oldest idempotency key of an account is forgotten once its keys in
+/// [`crate::ProcessedBatches`] are full, without forgetting the keys of other accounts.
+#[test]
+fn oldest_idempotency_key_should_be_forgotten() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 10)
+            ));
+            let other_transfers = vec![(3, crate::Asset::new(7, 1))];
+            assert_ok!(MantaPayPallet::batch_transfer(
+                Origin::signed(2),
+                other_transfers.clone(),
+                BatchMode::Atomic,
+                Some([0; 32])
+            ));
+            let transfers = vec![(2, crate::Asset::new(7, 1))];
+            for key in 0..=MaxProcessedBatches::get() as u8 {
+                assert_ok!(MantaPayPallet::batch_transfer(
+                    Origin::signed(1),
+                    transfers.clone(),
+                    BatchMode::Atomic,
+                    Some([key; 32])
+                ));
+            }
+            assert_eq!(
+                crate::ProcessedBatches::<Test>::get(1).len(),
+                MaxProcessedBatches::get() as usize
+            );
+            assert_noop!(
+                MantaPayPallet::batch_transfer(
+                    Origin::signed(2),
+                    other_transfers,
+                    BatchMode::Atomic,
+                    Some([0; 32])
+                ),
+                Error::<Test>::BatchAlreadyProcessed
+            );
+            assert_ok!(MantaPayPallet::batch_transfer(
+                Origin::signed(1),
+                transfers.clone(),
+                BatchMode::Atomic,
+                Some([0; 32])
+            ));
+            assert_noop!(
+                MantaPayPallet::batch_transfer(
+                    Origin::signed(1),
+                    transfers,
+                    BatchMode::Atomic,
+                    Some([0; 32])
+                ),
+                Error::<Test>::BatchAlreadyProcessed
+            );
+        });
+}
+
+/// Tests that an account cannot be credited with more distinct assets than the configured cap,
+/// while assets it already holds can still be credited.
+#[test]
+fn crediting_beyond_asset_cap_should_not_work() {
+    MaxAssetsPerAccount::set(&Some(2));
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100), (8, 100), (9, 100)])
+        .build()
+        .execute_with(|| {
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 10)
+            ));
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(8, 10)
+            ));
+            assert_eq!(AccountAssetCount::<Test>::get(2), 2);
+            assert_noop!(
+                MantaPayPallet::transfer(Origin::signed(1), 2, crate::Asset::new(9, 10)),
+                Error::<Test>::TooManyAssets
+            );
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 10)
+            ));
+            assert_eq!(MantaPayPallet::balance(2, 7), 20);
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(2),
+                3,
+                crate::Asset::new(8, 10)
+            ));
+            assert_eq!(AccountAssetCount::<Test>::get(2), 1);
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(9, 10)
+            ));
+        });
+}
+
+/// Tests that suggested mint denominations are distinct powers of two in descending order which
+/// add up to the total.
+#[test]
+fn suggested_mint_denominations_should_sum_to_total() {
+    assert!(MantaPayPallet::suggest_mint_denominations(0).is_empty());
+    assert_eq!(
+        MantaPayPallet::suggest_mint_denominations(13),
+        vec![8, 4, 1]
+    );
+    for total in [1, 1_000, 12_345_678, u128::MAX] {
+        let denominations = MantaPayPallet::suggest_mint_denominations(total);
+        assert_eq!(denominations.iter().sum::<u128>(), total);
+        assert!(denominations.iter().all(|value| value.is_power_of_two()));
+        assert!(denominations.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+    assert_eq!(
+        MantaPayPallet::suggest_mint_denominations(u128::MAX).len(),
+        128
+    );
+}
+
+/// Tests that public transfers are rejected once they are disabled.
+#[test]
+fn disabled_public_transfer_should_not_work() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 10)
+            ));
+            PublicTransfersEnabled::set(&false);
+            assert_noop!(
+                MantaPayPallet::transfer(Origin::signed(1), 2, crate::Asset::new(7, 10)),
+                Error::<Test>::PublicTransfersDisabled
+            );
+            assert_noop!(
+                MantaPayPallet::batch_transfer(
+                    Origin::signed(1),
+                    vec![(2, crate::Asset::new(7, 10))],
+                    BatchMode::Atomic,
+                    None
+                ),
+                Error::<Test>::PublicTransfersDisabled
+            );
+            assert_eq!(MantaPayPallet::balance(2, 7), 10);
+        });
+}
+
+/// Tests that destroying an asset retires its id until reuse is explicitly allowed.
+#[test]
+fn reusing_destroyed_asset_id_should_not_work() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 10)
+            ));
+            assert_noop!(
+                MantaPayPallet::destroy_asset(Origin::root(), 1, 7),
+                Error::<Test>::SupplyOutstanding
+            );
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(2),
+                1,
+                crate::Asset::new(7, 10)
+            ));
+            assert_ok!(MantaPayPallet::destroy_asset(Origin::root(), 1, 7));
+            System::assert_last_event(Event::MantaPayPallet(crate::Event::AssetDestroyed {
+                id: 7,
+                owner: 1,
+            }));
+            assert_eq!(MantaPayPallet::balance(1, 7), 0);
+            assert_eq!(MantaPayPallet::total_supply(7), 0);
+            assert_noop!(
+                MantaPayPallet::initialize_asset(Origin::root(), 1, 7, 50),
+                Error::<Test>::AssetIdRetired
+            );
+            assert_ok!(MantaPayPallet::allow_asset_id_reuse(Origin::root(), 7));
+            System::assert_last_event(Event::MantaPayPallet(crate::Event::AssetIdReuseAllowed {
+                id: 7,
+            }));
+            assert_ok!(MantaPayPallet::initialize_asset(Origin::root(), 1, 7, 50));
+            assert_eq!(MantaPayPallet::balance(1, 7), 50);
+        });
+}
+
+/// Tests that genesis assets report the configured owner.
+#[test]
+fn genesis_asset_should_report_configured_owner() {
+    ExtBuilder::default()
+        .with_owner(3)
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            assert_eq!(MantaPayPallet::asset_owner(7), Some(3));
+            assert_eq!(MantaPayPallet::asset_owner(8), None);
+            assert_ok!(MantaPayPallet::initialize_asset(Origin::root(), 4, 8, 50));
+            assert_eq!(MantaPayPallet::asset_owner(8), Some(4));
+        });
+}
+
+/// Tests that forcing a balance adjusts the total supply by the difference without wrapping.
+#[test]
+fn force_set_balance_should_adjust_supply() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            assert_noop!(
+                MantaPayPallet::force_set_balance(Origin::signed(1), 1, 7, 0),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                MantaPayPallet::force_set_balance(Origin::root(), 1, 8, 10),
+                Error::<Test>::UninitializedSupply
+            );
+            assert_ok!(MantaPayPallet::force_set_balance(Origin::root(), 2, 7, 50));
+            System::assert_last_event(Event::MantaPayPallet(crate::Event::BalanceSet {
+                id: 7,
+                who: 2,
+                value: 50,
+            }));
+            assert_eq!(MantaPayPallet::total_supply(7), 150);
+            assert_ok!(MantaPayPallet::force_set_balance(Origin::root(), 1, 7, 0));
+            assert_eq!(MantaPayPallet::balance(1, 7), 0);
+            assert_eq!(MantaPayPallet::total_supply(7), 50);
+            assert_ok!(MantaPayPallet::force_set_balance(
+                Origin::root(),
+                1,
+                7,
+                u128::MAX - 50
+            ));
+            assert_eq!(MantaPayPallet::total_supply(7), u128::MAX);
+            assert_noop!(
+                MantaPayPallet::force_set_balance(Origin::root(), 2, 7, 51),
+                Error::<Test>::SupplyAdjustmentOverflow
+            );
+            TotalSupply::<Test>::insert(7, 10);
+            assert_noop!(
+                MantaPayPallet::force_set_balance(Origin::root(), 2, 7, 0),
+                Error::<Test>::SupplyAdjustmentUnderflow
+            );
+        });
+}
+
+/// Tests that the number of holders of an asset only changes when a balance goes from zero to
+/// nonzero or back.
+#[test]
+fn holders_count_should_track_nonzero_balances() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100)])
+        .build()
+        .execute_with(|| {
+            assert_eq!(MantaPayPallet::total_accounts_holding(7), 1);
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 40)
+            ));
+            assert_eq!(MantaPayPallet::total_accounts_holding(7), 2);
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(2),
+                1,
+                crate::Asset::new(7, 10)
+            ));
+            assert_eq!(MantaPayPallet::total_accounts_holding(7), 2);
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(2),
+                1,
+                crate::Asset::new(7, 30)
+            ));
+            assert_eq!(MantaPayPallet::total_accounts_holding(7), 1);
+            assert_ok!(MantaPayPallet::destroy_asset(Origin::root(), 1, 7));
+            assert_eq!(MantaPayPallet::total_accounts_holding(7), 0);
+        });
+}
+
+/// Asserts that the balance entry of `account` for the asset `id` holds `balance`, that it only
+/// exists when `balance` is nonzero, and that the asset has `holders` holders.
+#[inline]
+fn assert_balance_entry(account: u64, id: u32, balance: u128, holders: u32) {
+    assert_eq!(Balances::<Test>::get(account, id), balance);
+    assert_eq!(Balances::<Test>::contains_key(account, id), balance != 0);
+    assert_eq!(HoldersCount::<Test>::get(id), holders);
+}
+
+/// Tests that crediting an account without a balance creates its entry and counts it once.
+#[test]
+fn credit_from_zero_should_count_holder_once() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Credit(0)),
+            Some(())
+        );
+        assert_balance_entry(2, 7, 0, 0);
+        assert_eq!(
+            MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Credit(10)),
+            Some(())
+        );
+        assert_balance_entry(2, 7, 10, 1);
+        assert_eq!(
+            MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Credit(5)),
+            Some(())
+        );
+        assert_balance_entry(2, 7, 15, 1);
+        assert_eq!(AccountAssetCount::<Test>::get(2), 1);
+    });
+}
+
+/// Tests that a partial debit keeps the entry and its holder, and that an overdraft changes
+/// nothing.
+#[test]
+fn partial_debit_should_keep_holder() {
+    new_test_ext().execute_with(|| {
+        MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Credit(10));
+        assert_eq!(
+            MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Debit(4)),
+            Some(())
+        );
+        assert_balance_entry(2, 7, 6, 1);
+        assert_eq!(
+            MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Debit(7)),
+            None
+        );
+        assert_balance_entry(2, 7, 6, 1);
+        assert_eq!(AccountAssetCount::<Test>::get(2), 1);
+    });
+}
+
+/// Tests that draining a balance removes its entry and uncounts its holder exactly once, and
+/// that crediting it again counts the holder again.
+#[test]
+fn full_drain_should_uncount_holder_once() {
+    new_test_ext().execute_with(|| {
+        MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Credit(10));
+        MantaPayPallet::adjust_balance(&3, 7, BalanceDelta::Credit(10));
+        assert_balance_entry(2, 7, 10, 2);
+        assert_eq!(
+            MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Debit(10)),
+            Some(())
+        );
+        assert_balance_entry(2, 7, 0, 1);
+        assert!(!AccountAssetCount::<Test>::contains_key(2));
+        assert_eq!(
+            MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Debit(0)),
+            Some(())
+        );
+        assert_balance_entry(2, 7, 0, 1);
+        MantaPayPallet::adjust_balance(&2, 7, BalanceDelta::Credit(1));
+        assert_balance_entry(2, 7, 1, 2);
+        assert_eq!(AccountAssetCount::<Test>::get(2), 1);
+    });
+}
+
+/// Tests that reconciling the total supply recomputes it from the public balances and the
+/// shielded pool, within the declared number of balances.
+#[test]
+fn reconcile_total_supply_should_restore_desynced_supply() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100), (8, 50)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 40)
+            ));
+            TotalSupply::<Test>::insert(7, 500);
+            crate::TotalMinted::<Test>::insert(7, 30);
+            crate::TotalReclaimed::<Test>::insert(7, 10);
+            assert_noop!(
+                MantaPayPallet::reconcile_total_supply(Origin::signed(1), 7, 3),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                MantaPayPallet::reconcile_total_supply(Origin::root(), 9, 3),
+                Error::<Test>::UninitializedSupply
+            );
+            assert_noop!(
+                MantaPayPallet::reconcile_total_supply(Origin::root(), 7, 2),
+                Error::<Test>::BalanceWitnessTooLow
+            );
+            assert_ok!(MantaPayPallet::reconcile_total_supply(Origin::root(), 7, 3));
+            assert_eq!(MantaPayPallet::total_supply(7), 120);
+            assert_eq!(MantaPayPallet::total_supply(8), 50);
+            System::assert_last_event(Event::MantaPayPallet(crate::Event::SupplyReconciled {
+                id: 7,
+                previous: 500,
+                total: 120,
+            }));
+        });
+}
+
+/// Tests that the counters migration recomputes the counters of chains which ran before they
+/// were added, and only runs once.
+#[test]
+fn counters_migration_should_populate_counters() {
+    ExtBuilder::default()
+        .with_assets(vec![(7, 100), (8, 50)])
+        .build()
+        .execute_with(|| {
+            assert_ok!(MantaPayPallet::transfer(
+                Origin::signed(1),
+                2,
+                crate::Asset::new(7, 40)
+            ));
+            crate::UtxoSet::<Test>::insert(manta_pay::config::Utxo::default(), ());
+            crate::UtxoAccumulatorOutputs::<Test>::insert(
+                manta_pay::config::UtxoAccumulatorOutput::default(),
+                (),
+            );
+            let _ = AccountAssetCount::<Test>::remove_all(None);
+            let _ = HoldersCount::<Test>::remove_all(None);
+            StorageVersion::new(0).put::<MantaPayPallet>();
+            PopulateCounters::<Test>::on_runtime_upgrade();
+            assert_eq!(AccountAssetCount::<Test>::get(1), 2);
+            assert_eq!(AccountAssetCount::<Test>::get(2), 1);
+            assert_eq!(HoldersCount::<Test>::get(7), 2);
+            assert_eq!(HoldersCount::<Test>::get(8), 1);
+            assert_eq!(crate::TotalUtxoCount::<Test>::get(), 1);
+            assert_eq!(crate::RootCount::<Test>::get(), 1);
+            assert_eq!(MantaPayPallet::on_chain_storage_version(), 1);
+            HoldersCount::<Test>::insert(8, 5);
+            PopulateCounters::<Test>::on_runtime_upgrade();
+            assert_eq!(HoldersCount::<Test>::get(8), 5);
+        });
+}
+
+/// Tests that crediting at the boundary of the asset value type saturates instead of wrapping.
+#[test]
+fn credit_at_value_boundary_should_not_wrap() {
+    new_test_ext().execute_with(|| {
+        Balances::<Test>::insert(1, 7, u128::MAX - 1);
+        assert_eq!(
+            MantaPayPallet::adjust_balance(&1, 7, BalanceDelta::Credit(1)),
+            Some(())
+        );
+        assert_eq!(Balances::<Test>::get(1, 7), u128::MAX);
+        assert_eq!(
+            MantaPayPallet::adjust_balance(&1, 7, BalanceDelta::Credit(1)),
+            Some(())
+        );
+        assert_eq!(Balances::<Test>::get(1, 7), u128::MAX);
+    });
+}
@@ -0,0 +1,150 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    compat::{
+        convert_mint, convert_private_transfer, convert_reclaim, describe_mint,
+        describe_private_transfer, describe_reclaim, MintData, PrivateTransferData, ReceiverData,
+        ReclaimData, SenderData, Unconvertible,
+    },
+    types::{AssetId, PostShape},
+};
+
+/// Builds a legacy sender payload filled with `byte`.
+#[inline]
+fn sender(byte: u8) -> SenderData {
+    SenderData {
+        k: [byte; 32],
+        void_number: [byte; 32],
+        root: [byte; 32],
+    }
+}
+
+/// Builds a legacy receiver payload filled with `byte`.
+#[inline]
+fn receiver(byte: u8) -> ReceiverData {
+    ReceiverData {
+        k: [byte; 32],
+        cm: [byte; 32],
+        cipher: [byte; 16],
+        sender_pk: [byte; 32],
+    }
+}
+
+/// Builds a legacy mint payload of `amount` for the asset `asset_id`.
+#[inline]
+fn mint(asset_id: u64, amount: u64) -> MintData {
+    MintData {
+        asset_id,
+        amount,
+        cm: [1; 32],
+        k: [2; 32],
+        s: [3; 32],
+    }
+}
+
+/// Builds a legacy reclaim payload of `reclaim_amount` for the asset `asset_id`.
+#[inline]
+fn reclaim(asset_id: u64, reclaim_amount: u64) -> ReclaimData {
+    ReclaimData {
+        asset_id,
+        reclaim_amount,
+        sender_1: sender(1),
+        sender_2: sender(2),
+        receiver: receiver(3),
+        proof: [4; 192],
+    }
+}
+
+/// Tests that the public parts of legacy payloads map onto the matching post shapes.
+#[test]
+fn legacy_payloads_should_describe_matching_shapes() {
+    let description = describe_mint(&mint(7, 100)).expect("The asset id is in range.");
+    assert_eq!(description.shape, Some(PostShape::Mint));
+    assert_eq!(description.asset_id, Some(7));
+    assert_eq!(description.sources, vec![100]);
+    assert!(description.sinks.is_empty());
+    assert_eq!(
+        (description.sender_count, description.receiver_count),
+        (0, 1)
+    );
+
+    let description = describe_private_transfer(&PrivateTransferData {
+        sender_1: sender(1),
+        sender_2: sender(2),
+        receiver_1: receiver(3),
+        receiver_2: receiver(4),
+        proof: [5; 192],
+    });
+    assert_eq!(description.shape, Some(PostShape::PrivateTransfer));
+    assert_eq!(description.asset_id, None);
+    assert!(description.sources.is_empty() && description.sinks.is_empty());
+    assert_eq!(
+        (description.sender_count, description.receiver_count),
+        (2, 2)
+    );
+
+    let description = describe_reclaim(&reclaim(7, 50)).expect("The asset id is in range.");
+    assert_eq!(description.shape, Some(PostShape::Reclaim));
+    assert_eq!(description.asset_id, Some(7));
+    assert!(description.sources.is_empty());
+    assert_eq!(description.sinks, vec![50]);
+    assert_eq!(
+        (description.sender_count, description.receiver_count),
+        (2, 1)
+    );
+}
+
+/// Tests that legacy payloads whose asset id does not fit into an [`AssetId`] are unconvertible.
+#[test]
+fn legacy_asset_id_out_of_range_should_not_convert() {
+    let id = AssetId::MAX as u64 + 1;
+    assert_eq!(
+        describe_mint(&mint(id, 100)),
+        Err(Unconvertible::AssetIdOutOfRange)
+    );
+    assert_eq!(
+        convert_mint(&mint(id, 100)),
+        Err(Unconvertible::AssetIdOutOfRange)
+    );
+    assert_eq!(
+        convert_reclaim(&reclaim(id, 50)),
+        Err(Unconvertible::AssetIdOutOfRange)
+    );
+}
+
+/// Tests that structurally valid legacy payloads are rejected for their incompatible proofs.
+#[test]
+fn legacy_proofs_should_not_convert() {
+    assert_eq!(
+        convert_mint(&mint(7, 100)),
+        Err(Unconvertible::IncompatibleProof)
+    );
+    assert_eq!(
+        convert_private_transfer(&PrivateTransferData {
+            sender_1: sender(1),
+            sender_2: sender(2),
+            receiver_1: receiver(3),
+            receiver_2: receiver(4),
+            proof: [5; 192],
+        }),
+        Err(Unconvertible::IncompatibleProof)
+    );
+    assert_eq!(
+        convert_reclaim(&reclaim(7, 50)),
+        Err(Unconvertible::IncompatibleProof)
+    );
+}
@@ -15,116 +15,226 @@
 // along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-    mock::{new_test_ext, MantaPayPallet, Origin, Test},
-    Error,
+    mock::{
+        new_test_ext, DbWeight, EmitDetailedEvents, Event, ExtBuilder, FeeAssetId, FeeDestination,
+        MantaPayPallet, MaxAssetsPerAccount, MaxNoteSize, MaxPendingPosts, MaxProofSize,
+        MaxUtxoCount, MinAnonymitySet, MinReclaimValue, Origin, PoolLowWatermark,
+        PrependProofInput, ReclaimCooldown, RequireAssetInitForMint, ShieldedFee, StrictRootMode,
+        System, Test,
+    },
+    sdk,
+    types::{
+        GenesisParameters, LedgerSnapshot, PalletLimits, ParametersSource, PostDescription,
+        PostShape, QueryError, StorageStats,
+    },
+    BatchMode, Error, PendingPosts, WeightInfo as _,
+};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::Hooks,
+    weights::{RuntimeDbWeight, Weight},
 };
-use frame_support::{assert_noop, assert_ok};
 use manta_accounting::{
     asset::{Asset, AssetId, AssetValue},
-    transfer::{self, test::value_distribution, SpendingKey},
+    transfer::{self, test::value_distribution, ReceiverLedger, Sender, SpendingKey},
 };
 use manta_crypto::{
     accumulator::Accumulator,
-    merkle_tree::{forest::TreeArrayMerkleForest, full::Full},
+    merkle_tree::{
+        forest::{Configuration as _, TreeArrayMerkleForest},
+        full::Full,
+    },
     rand::{CryptoRng, Rand, RngCore, Sample},
 };
 use manta_pay::config::{
-    FullParameters, KeyAgreementScheme, MerkleTreeConfiguration, Mint, MultiProvingContext,
-    Parameters, PrivateTransfer, ProvingContext, Reclaim, TransferPost, UtxoAccumulatorModel,
-    UtxoCommitmentScheme, VoidNumberHashFunction,
+    Config, FullParameters, MerkleTreeConfiguration, Mint, MultiProvingContext, Parameters,
+    PrivateTransfer, Reclaim, TransferPost, UtxoAccumulatorModel, VerifyingContext,
 };
-use manta_util::codec::{Decode, IoReader};
+use manta_util::codec::{Decode, Encode as _};
 use rand::thread_rng;
-use std::fs::File;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use scale_codec::{DecodeAll, Encode};
 
 /// UTXO Accumulator for Building Circuits
 type UtxoAccumulator =
     TreeArrayMerkleForest<MerkleTreeConfiguration, Full<MerkleTreeConfiguration>, 256>;
 
 lazy_static::lazy_static! {
-    static ref PROVING_CONTEXT: MultiProvingContext = load_proving_context();
-    static ref PARAMETERS: Parameters = load_parameters();
-    static ref UTXO_ACCUMULATOR_MODEL: UtxoAccumulatorModel = load_utxo_accumulator_model();
+    static ref PROVING_CONTEXT: Option<MultiProvingContext> = load_proving_context();
+    static ref PARAMETERS: Parameters = sdk::load_parameters();
+    static ref UTXO_ACCUMULATOR_MODEL: UtxoAccumulatorModel = sdk::load_utxo_accumulator_model();
 }
 
-/// Loads the [`MultiProvingContext`] from the SDK.
+/// Loads the [`MultiProvingContext`] from the SDK, returning `None` if any proving context could
+/// not be downloaded.
+///
+/// # Note
+///
+/// Tests which build proofs call `require_proving_context!` first, so that they are skipped with
+/// a warning instead of failing when the SDK cannot be reached.
 #[inline]
-fn load_proving_context() -> MultiProvingContext {
+fn load_proving_context() -> Option<MultiProvingContext> {
     let directory = tempfile::tempdir().expect("Unable to create temporary directory.");
-    let path = directory.path();
-    let mint_path = path.join("mint.dat");
-    manta_sdk::pay::testnet::proving::Mint::download(&mint_path)
-        .expect("Unable to download MINT proving context.");
-    let private_transfer_path = path.join("private-transfer.dat");
-    manta_sdk::pay::testnet::proving::PrivateTransfer::download(&private_transfer_path)
-        .expect("Unable to download PRIVATE_TRANSFER proving context.");
-    let reclaim_path = path.join("reclaim.dat");
-    manta_sdk::pay::testnet::proving::Reclaim::download(&reclaim_path)
-        .expect("Unable to download RECLAIM proving context.");
-    MultiProvingContext {
-        mint: ProvingContext::decode(IoReader(
-            File::open(mint_path).expect("Unable to open MINT proving context file."),
-        ))
-        .expect("Unable to decode MINT proving context."),
-        private_transfer: ProvingContext::decode(IoReader(
-            File::open(private_transfer_path)
-                .expect("Unable to open PRIVATE_TRANSFER proving context file."),
-        ))
-        .expect("Unable to decode PRIVATE_TRANSFER proving context."),
-        reclaim: ProvingContext::decode(IoReader(
-            File::open(reclaim_path).expect("Unable to open RECLAIM proving context file."),
-        ))
-        .expect("Unable to decode RECLAIM proving context."),
+    let proving_context = sdk::download_proving_context(directory.path());
+    if proving_context.is_none() {
+        eprintln!("warning: tests which build proofs will be skipped.");
     }
+    proving_context
 }
 
-/// Loads the [`Parameters`] from the SDK.
+/// Returns `true` if the proving contexts were downloaded, loading them on first use.
 #[inline]
-fn load_parameters() -> Parameters {
-    Parameters {
-        key_agreement: KeyAgreementScheme::decode(
-            manta_sdk::pay::testnet::parameters::KeyAgreement::get()
-                .expect("Checksum did not match."),
-        )
-        .expect("Unable to decode KEY_AGREEMENT parameters."),
-        utxo_commitment: UtxoCommitmentScheme::decode(
-            manta_sdk::pay::testnet::parameters::UtxoCommitmentScheme::get()
-                .expect("Checksum did not match."),
-        )
-        .expect("Unable to decode UTXO_COMMITMENT_SCHEME parameters."),
-        void_number_hash: VoidNumberHashFunction::decode(
-            manta_sdk::pay::testnet::parameters::VoidNumberHashFunction::get()
-                .expect("Checksum did not match."),
-        )
-        .expect("Unable to decode VOID_NUMBER_HASH_FUNCTION parameters."),
-    }
+pub(super) fn proving_context_available() -> bool {
+    PROVING_CONTEXT.is_some()
 }
 
-/// Loads the [`UtxoAccumulatorModel`] from the SDK.
+/// Returns the downloaded proving contexts.
 #[inline]
-fn load_utxo_accumulator_model() -> UtxoAccumulatorModel {
-    UtxoAccumulatorModel::decode(
-        manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get()
-            .expect("Checksum did not match."),
-    )
-    .expect("Unable to decode UTXO_ACCUMULATOR_MODEL.")
+fn proving_context() -> &'static MultiProvingContext {
+    PROVING_CONTEXT
+        .as_ref()
+        .expect("Tests which build proofs must call `require_proving_context!` first.")
 }
 
 /// Samples a [`Mint`] transaction of `asset` with a random secret.
 #[inline]
-fn sample_mint<R>(asset: Asset, rng: &mut R) -> TransferPost
+pub(super) fn sample_mint<R>(asset: Asset, rng: &mut R) -> TransferPost
 where
     R: CryptoRng + RngCore + ?Sized,
 {
     Mint::from_spending_key(&PARAMETERS, &rng.gen(), asset, rng)
         .into_post(
             FullParameters::new(&PARAMETERS, &UTXO_ACCUMULATOR_MODEL),
-            &PROVING_CONTEXT.mint,
+            &proving_context().mint,
             rng,
         )
         .expect("Unable to build MINT proof.")
 }
 
+/// Samples a [`Mint`] transaction of `asset` into `utxo_accumulator` under `spending_key`,
+/// returning the post and the sender that can spend it.
+#[inline]
+fn sample_mint_sender<R>(
+    utxo_accumulator: &mut UtxoAccumulator,
+    spending_key: &SpendingKey<Config>,
+    asset: Asset,
+    rng: &mut R,
+) -> (TransferPost, Sender<Config>)
+where
+    R: CryptoRng + RngCore + ?Sized,
+{
+    let (mint, pre_sender) = transfer::test::sample_mint(
+        &proving_context().mint,
+        FullParameters::new(&PARAMETERS, utxo_accumulator.model()),
+        spending_key,
+        asset,
+        rng,
+    )
+    .expect("Unable to build MINT proof.");
+    let sender = pre_sender
+        .insert_and_upgrade(utxo_accumulator)
+        .expect("Just inserted so this should not fail.");
+    (mint, sender)
+}
+
+/// Samples a [`PrivateTransfer`] transaction under two [`Mint`]s of `asset_0` and `asset_1`,
+/// swapping the assets between the two spending keys.
+///
+/// # Note
+///
+/// The [`Mint`]s must be posted before the [`PrivateTransfer`] to a ledger which has no other
+/// UTXOs registered, since the proof is built against a fresh UTXO accumulator.
+#[inline]
+fn sample_private_transfer<R>(
+    asset_0: Asset,
+    asset_1: Asset,
+    rng: &mut R,
+) -> ([TransferPost; 2], TransferPost)
+where
+    R: CryptoRng + RngCore + ?Sized,
+{
+    sample_private_transfer_with(
+        &mut UtxoAccumulator::new(UTXO_ACCUMULATOR_MODEL.clone()),
+        asset_0,
+        asset_1,
+        rng,
+    )
+}
+
+/// Samples a [`PrivateTransfer`] transaction like [`sample_private_transfer`] but inserting the
+/// [`Mint`]s into an existing `utxo_accumulator`.
+///
+/// # Note
+///
+/// The [`Mint`]s of every transfer sampled against `utxo_accumulator` must be posted in sampling
+/// order before any of the transfers.
+#[inline]
+fn sample_private_transfer_with<R>(
+    utxo_accumulator: &mut UtxoAccumulator,
+    asset_0: Asset,
+    asset_1: Asset,
+    rng: &mut R,
+) -> ([TransferPost; 2], TransferPost)
+where
+    R: CryptoRng + RngCore + ?Sized,
+{
+    let spending_key_0 = SpendingKey::gen(rng);
+    let (mint_0, sender_0) = sample_mint_sender(utxo_accumulator, &spending_key_0, asset_0, rng);
+    let spending_key_1 = SpendingKey::gen(rng);
+    let (mint_1, sender_1) = sample_mint_sender(utxo_accumulator, &spending_key_1, asset_1, rng);
+    let private_transfer = PrivateTransfer::build(
+        [sender_0, sender_1],
+        [
+            spending_key_0.receiver(&PARAMETERS, rng.gen(), asset_1),
+            spending_key_1.receiver(&PARAMETERS, rng.gen(), asset_0),
+        ],
+    )
+    .into_post(
+        FullParameters::new(&PARAMETERS, utxo_accumulator.model()),
+        &proving_context().private_transfer,
+        rng,
+    )
+    .expect("Unable to build PRIVATE_TRANSFER proof.");
+    ([mint_0, mint_1], private_transfer)
+}
+
+/// Samples a [`Reclaim`] transaction under two [`Mint`]s of `asset_0` and `asset_1`, reclaiming
+/// `asset_0` and sending `asset_1` back to the first spending key.
+///
+/// # Note
+///
+/// The [`Mint`]s must be posted before the [`Reclaim`] to a ledger which has no other UTXOs
+/// registered, since the proof is built against a fresh UTXO accumulator.
+#[inline]
+fn sample_reclaim<R>(
+    asset_0: Asset,
+    asset_1: Asset,
+    rng: &mut R,
+) -> ([TransferPost; 2], TransferPost)
+where
+    R: CryptoRng + RngCore + ?Sized,
+{
+    let mut utxo_accumulator = UtxoAccumulator::new(UTXO_ACCUMULATOR_MODEL.clone());
+    let spending_key_0 = SpendingKey::gen(rng);
+    let (mint_0, sender_0) =
+        sample_mint_sender(&mut utxo_accumulator, &spending_key_0, asset_0, rng);
+    let spending_key_1 = SpendingKey::gen(rng);
+    let (mint_1, sender_1) =
+        sample_mint_sender(&mut utxo_accumulator, &spending_key_1, asset_1, rng);
+    let reclaim = Reclaim::build(
+        [sender_0, sender_1],
+        [spending_key_0.receiver(&PARAMETERS, rng.gen(), asset_1)],
+        asset_0,
+    )
+    .into_post(
+        FullParameters::new(&PARAMETERS, utxo_accumulator.model()),
+        &proving_context().reclaim,
+        rng,
+    )
+    .expect("Unable to build RECLAIM proof.");
+    ([mint_0, mint_1], reclaim)
+}
+
 /// Mints many assets with the given `id` and `value`.
 #[inline]
 fn mint_tokens<R>(id: AssetId, values: &[AssetValue], rng: &mut R)
@@ -146,15 +256,17 @@ where
     R: CryptoRng + RngCore + ?Sized,
 {
     let asset_id = rng.gen();
-    let total_balance = rng.gen();
+    let total_balance = AssetValue::gen(rng)
+        .checked_sub(AssetValue(count as u128))
+        .unwrap_or_default();
     let balances = value_distribution(count, total_balance, rng);
-    initialize_test(asset_id, total_balance);
+    initialize_test(asset_id, total_balance + count as u128);
     let mut utxo_accumulator = UtxoAccumulator::new(UTXO_ACCUMULATOR_MODEL.clone());
     let mut posts = Vec::new();
     for balance in balances {
         let spending_key = SpendingKey::gen(rng);
         let (mint_0, pre_sender_0) = transfer::test::sample_mint(
-            &PROVING_CONTEXT.mint,
+            &proving_context().mint,
             FullParameters::new(&PARAMETERS, utxo_accumulator.model()),
             &spending_key,
             asset_id.with(balance),
@@ -166,10 +278,10 @@ where
             .insert_and_upgrade(&mut utxo_accumulator)
             .expect("Just inserted so this should not fail.");
         let (mint_1, pre_sender_1) = transfer::test::sample_mint(
-            &PROVING_CONTEXT.mint,
+            &proving_context().mint,
             FullParameters::new(&PARAMETERS, utxo_accumulator.model()),
             &spending_key,
-            asset_id.value(0),
+            asset_id.value(1),
             rng,
         )
         .unwrap();
@@ -178,14 +290,14 @@ where
             .insert_and_upgrade(&mut utxo_accumulator)
             .expect("Just inserted so this should not fail.");
         let (receiver_0, pre_sender_0) =
-            spending_key.internal_pair(&PARAMETERS, rng.gen(), asset_id.value(0));
+            spending_key.internal_pair(&PARAMETERS, rng.gen(), asset_id.value(1));
         let (receiver_1, pre_sender_1) =
             spending_key.internal_pair(&PARAMETERS, rng.gen(), asset_id.with(balance));
         let private_transfer =
             PrivateTransfer::build([sender_0, sender_1], [receiver_0, receiver_1])
                 .into_post(
                     FullParameters::new(&PARAMETERS, utxo_accumulator.model()),
-                    &PROVING_CONTEXT.private_transfer,
+                    &proving_context().private_transfer,
                     rng,
                 )
                 .unwrap();
@@ -207,15 +319,17 @@ where
     R: CryptoRng + RngCore + ?Sized,
 {
     let asset_id = rng.gen();
-    let total_balance = rng.gen();
+    let total_balance = AssetValue::gen(rng)
+        .checked_sub(AssetValue(count as u128))
+        .unwrap_or_default();
     let balances = value_distribution(count, total_balance, rng);
-    initialize_test(asset_id, total_balance);
+    initialize_test(asset_id, total_balance + count as u128);
     let mut utxo_accumulator = UtxoAccumulator::new(UTXO_ACCUMULATOR_MODEL.clone());
     let mut posts = Vec::new();
     for balance in balances {
         let spending_key = SpendingKey::gen(rng);
         let (mint_0, pre_sender_0) = transfer::test::sample_mint(
-            &PROVING_CONTEXT.mint,
+            &proving_context().mint,
             FullParameters::new(&PARAMETERS, utxo_accumulator.model()),
             &spending_key,
             asset_id.with(balance),
@@ -227,10 +341,10 @@ where
             .insert_and_upgrade(&mut utxo_accumulator)
             .expect("Just inserted so this should not fail.");
         let (mint_1, pre_sender_1) = transfer::test::sample_mint(
-            &PROVING_CONTEXT.mint,
+            &proving_context().mint,
             FullParameters::new(&PARAMETERS, utxo_accumulator.model()),
             &spending_key,
-            asset_id.value(0),
+            asset_id.value(1),
             rng,
         )
         .unwrap();
@@ -239,11 +353,11 @@ where
             .insert_and_upgrade(&mut utxo_accumulator)
             .expect("Just inserted so this should not fail.");
         let (receiver, pre_sender) =
-            spending_key.internal_pair(&PARAMETERS, rng.gen(), asset_id.value(0));
+            spending_key.internal_pair(&PARAMETERS, rng.gen(), asset_id.value(1));
         let reclaim = Reclaim::build([sender_0, sender_1], [receiver], asset_id.with(balance))
             .into_post(
                 FullParameters::new(&PARAMETERS, utxo_accumulator.model()),
-                &PROVING_CONTEXT.reclaim,
+                &proving_context().reclaim,
                 rng,
             )
             .unwrap();
@@ -257,6 +371,25 @@ where
     posts
 }
 
+/// Environment Variable Overriding the Seed of [`seeded_rng`]
+const SEED_VARIABLE: &str = "MANTA_PAY_TEST_SEED";
+
+/// Returns a seeded RNG for sampling posts, printing its seed so that failures can be reproduced.
+///
+/// # Note
+///
+/// The seed is read from [`SEED_VARIABLE`] if it is set and drawn at random otherwise. The test
+/// harness only shows the output of failing tests, so the seed is printed with every failure.
+#[inline]
+fn seeded_rng() -> ChaCha20Rng {
+    let seed = std::env::var(SEED_VARIABLE)
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(|| thread_rng().next_u64());
+    println!("Reproduce with {}={}", SEED_VARIABLE, seed);
+    ChaCha20Rng::seed_from_u64(seed)
+}
+
 /// Initializes a test by allocating `value`-many assets of the given `id` to the default account.
 #[inline]
 fn initialize_test(id: AssetId, value: AssetValue) {
@@ -267,7 +400,8 @@ fn initialize_test(id: AssetId, value: AssetValue) {
 /// Tests multiple mints from some total supply.
 #[test]
 fn mint_should_work() {
-    let mut rng = thread_rng();
+    require_proving_context!();
+    let mut rng = seeded_rng();
     new_test_ext().execute_with(|| {
         let asset_id = rng.gen();
         let total_supply = rng.gen();
@@ -280,10 +414,41 @@ fn mint_should_work() {
     });
 }
 
+/// Tests that the range of minted values tracks the smallest and largest mint of each asset.
+#[test]
+fn mint_value_range_should_track_extremes() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        assert_eq!(MantaPayPallet::mint_value_range(asset_id.0), None);
+        mint_tokens(asset_id, &[AssetValue(500)], &mut rng);
+        assert_eq!(
+            MantaPayPallet::mint_value_range(asset_id.0),
+            Some((500, 500))
+        );
+        mint_tokens(
+            asset_id,
+            &[AssetValue(20), AssetValue(7_000), AssetValue(300)],
+            &mut rng,
+        );
+        assert_eq!(
+            MantaPayPallet::mint_value_range(asset_id.0),
+            Some((20, 7_000))
+        );
+        assert_eq!(
+            MantaPayPallet::mint_value_range(asset_id.0.wrapping_add(1)),
+            None
+        );
+    });
+}
+
 /// Tests a mint that would overdraw the total supply.
 #[test]
 fn overdrawn_mint_should_not_work() {
-    let mut rng = thread_rng();
+    require_proving_context!();
+    let mut rng = seeded_rng();
     new_test_ext().execute_with(|| {
         let asset_id = rng.gen();
         let total_supply = AssetValue::gen(&mut rng)
@@ -300,10 +465,26 @@ fn overdrawn_mint_should_not_work() {
     });
 }
 
-/// Tests a mint that would overdraw from a non-existent supply.
+/// Tests that a mint of an uninitialized asset is rejected for the missing asset.
 #[test]
 fn mint_without_init_should_not_work() {
-    let mut rng = thread_rng();
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MantaPayPallet::mint(Origin::signed(1), sample_mint(rng.gen(), &mut rng).into()),
+            Error::<Test>::UninitializedSupply,
+        );
+    });
+}
+
+/// Tests a mint that would overdraw from a non-existent supply when mints do not require the
+/// asset to be initialized.
+#[test]
+fn mint_without_init_should_overdraw_when_not_required() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    RequireAssetInitForMint::set(&false);
     new_test_ext().execute_with(|| {
         assert_noop!(
             MantaPayPallet::mint(Origin::signed(1), sample_mint(rng.gen(), &mut rng).into()),
@@ -312,71 +493,2091 @@ fn mint_without_init_should_not_work() {
     });
 }
 
-/// Tests that a double-spent [`Mint`] will fail.
+/// Tests that a [`Mint`] of zero value will fail before verification.
 #[test]
-fn mint_existing_coin_should_not_work() {
-    let mut rng = thread_rng();
+fn zero_mint_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
     new_test_ext().execute_with(|| {
         let asset_id = rng.gen();
         initialize_test(asset_id, AssetValue(32579));
-        let mint_post = sample_mint(asset_id.value(100), &mut rng);
+        assert_noop!(
+            MantaPayPallet::mint(
+                Origin::signed(1),
+                sample_mint(asset_id.value(0), &mut rng).into()
+            ),
+            Error::<Test>::ZeroMint
+        );
+    });
+}
+
+/// Tests that mints without exactly one receiver post are rejected before verification.
+#[test]
+fn mint_with_wrong_receiver_count_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let mint = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+        let mut empty = mint.clone();
+        empty.receiver_posts.clear();
+        assert_noop!(
+            MantaPayPallet::mint(Origin::signed(1), empty),
+            Error::<Test>::InvalidShape
+        );
+        let mut doubled = mint.clone();
+        doubled.receiver_posts.push(mint.receiver_posts[0].clone());
+        assert_noop!(
+            MantaPayPallet::mint(Origin::signed(1), doubled),
+            Error::<Test>::InvalidShape
+        );
+    });
+}
+
+/// Tests that proofs are verified against the public input of the configured generator, so that
+/// SDK proofs are rejected once the generator changes the layout.
+#[cfg(not(feature = "skip-verification"))]
+#[test]
+fn custom_proof_input_should_be_used_for_verification() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
         assert_ok!(MantaPayPallet::mint(
             Origin::signed(1),
-            mint_post.clone().into()
+            sample_mint(asset_id.value(10), &mut rng).into()
         ));
+        PrependProofInput::set(&true);
         assert_noop!(
-            MantaPayPallet::mint(Origin::signed(1), mint_post.into()),
-            Error::<Test>::AssetRegistered
+            MantaPayPallet::mint(
+                Origin::signed(1),
+                sample_mint(asset_id.value(10), &mut rng).into()
+            ),
+            Error::<Test>::InvalidProof
         );
     });
 }
 
-/// Tests a [`PrivateTransfer`] transaction.
+/// Returns the encoded verifying context matching `encoded` for the public input layout of
+/// [`MockProofInput`](crate::mock::MockProofInput) with [`PrependProofInput`] set.
+///
+/// # Note
+///
+/// The prepended input is zero, so the circuit which allocates it without constraining it has the
+/// same proving key as the SDK circuit and the identity as the verifying key point of the new
+/// input. Proofs built with the SDK proving contexts verify against the returned context.
+#[inline]
+fn prepended_verifying_context(encoded: &[u8]) -> Vec<u8> {
+    let mut context =
+        VerifyingContext::decode(encoded).expect("Unable to decode the verifying context.");
+    context.0.vk.gamma_abc_g1.insert(1, Default::default());
+    context.to_vec()
+}
+
+/// Tests that a custom proof input is verified end-to-end against verifying contexts generated
+/// for its layout, and that the SDK layout is rejected by them.
+#[cfg(not(feature = "skip-verification"))]
 #[test]
-fn private_transfer_should_work() {
-    new_test_ext().execute_with(|| private_transfer_test(1, &mut thread_rng()));
+fn custom_proof_input_with_matching_contexts_should_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    PrependProofInput::set(&true);
+    ExtBuilder::default()
+        .with_parameters(GenesisParameters {
+            mint: ParametersSource::Embedded(prepended_verifying_context(
+                manta_sdk::pay::testnet::verifying::Mint::get().expect("Checksum did not match."),
+            )),
+            private_transfer: ParametersSource::Embedded(prepended_verifying_context(
+                manta_sdk::pay::testnet::verifying::PrivateTransfer::get()
+                    .expect("Checksum did not match."),
+            )),
+            reclaim: ParametersSource::Embedded(prepended_verifying_context(
+                manta_sdk::pay::testnet::verifying::Reclaim::get()
+                    .expect("Checksum did not match."),
+            )),
+            ..Default::default()
+        })
+        .build()
+        .execute_with(|| {
+            let asset_id = rng.gen();
+            initialize_test(asset_id, AssetValue(1_000_000));
+            assert_ok!(MantaPayPallet::mint(
+                Origin::signed(1),
+                sample_mint(asset_id.value(10), &mut rng).into()
+            ));
+            PrependProofInput::set(&false);
+            assert_noop!(
+                MantaPayPallet::mint(
+                    Origin::signed(1),
+                    sample_mint(asset_id.value(10), &mut rng).into()
+                ),
+                Error::<Test>::InvalidProof
+            );
+        });
 }
 
-/// Tests multiple [`PrivateTransfer`] transactions.
+/// Tests that the integrity test and the parameter check of the genesis build pass for a custom
+/// proof input, which the SDK samples were not proven for.
 #[test]
-fn private_transfer_10_times_should_work() {
-    new_test_ext().execute_with(|| private_transfer_test(10, &mut thread_rng()));
+fn custom_proof_input_should_pass_integrity_test() {
+    PrependProofInput::set(&true);
+    MantaPayPallet::integrity_test();
+    new_test_ext().execute_with(|| assert_ok!(MantaPayPallet::check_parameters()));
 }
 
-/// Tests that a double-spent [`PrivateTransfer`] will fail.
+/// Tests that a mint carrying a note with a corrupted ephemeral key is rejected before its UTXO
+/// is registered.
 #[test]
-fn double_spend_in_private_transfer_should_not_work() {
+fn mint_with_malformed_note_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
     new_test_ext().execute_with(|| {
-        for private_transfer in private_transfer_test(1, &mut thread_rng()) {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let mut mint = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+        mint.receiver_posts[0].note.ephemeral_public_key = Default::default();
+        let utxo = mint.receiver_posts[0].utxo;
+        assert_noop!(
+            MantaPayPallet::mint(Origin::signed(1), mint),
+            Error::<Test>::MalformedNote
+        );
+        assert!(!crate::UtxoSet::<Test>::contains_key(utxo));
+    });
+}
+
+/// Tests that the integrity test rejects a [`MaxNoteSize`] which does not cover the encoded size
+/// of an encrypted note.
+#[test]
+#[should_panic(expected = "`MaxNoteSize` should cover the encoded size of an encrypted note.")]
+fn max_note_size_below_note_size_should_fail_integrity_test() {
+    MaxNoteSize::set(&1);
+    MantaPayPallet::integrity_test();
+}
+
+/// Tests that the integrity test rejects a [`MaxProofSize`] which does not cover the encoded size
+/// of a validity proof.
+#[test]
+#[should_panic(expected = "`MaxProofSize` should cover the encoded size of a validity proof.")]
+fn max_proof_size_below_proof_size_should_fail_integrity_test() {
+    MaxProofSize::set(&1);
+    MantaPayPallet::integrity_test();
+}
+
+/// Tests that a mint is consistent with its declared asset only until its public parts are
+/// tampered with.
+#[cfg(not(feature = "skip-verification"))]
+#[test]
+fn tampered_mint_should_be_inconsistent() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id: AssetId = rng.gen();
+        let mint = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+        assert!(MantaPayPallet::is_consistent_mint(mint.clone()));
+        assert_eq!(MantaPayPallet::balance(0, asset_id.0), 0);
+        let mut tampered = mint.clone();
+        tampered.sources[0] = 11;
+        assert!(!MantaPayPallet::is_consistent_mint(tampered));
+        let mut tampered = mint.clone();
+        tampered.asset_id = Some(asset_id.0.wrapping_add(1));
+        assert!(!MantaPayPallet::is_consistent_mint(tampered));
+        let mut tampered = mint;
+        tampered.receiver_posts[0].utxo = Default::default();
+        assert!(!MantaPayPallet::is_consistent_mint(tampered));
+    });
+}
+
+/// Tests that mints without exactly one source fail gracefully instead of panicking on a missing
+/// source.
+#[test]
+fn mint_with_wrong_source_count_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let mint = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+        let mut sourceless = mint.clone();
+        sourceless.sources.clear();
+        assert_eq!(sourceless.shape(), None);
+        assert!(!MantaPayPallet::is_consistent_mint(sourceless.clone()));
+        assert_noop!(
+            MantaPayPallet::mint(Origin::signed(1), sourceless),
+            Error::<Test>::InvalidShape
+        );
+        let mut doubled = mint;
+        doubled.sources.push(10);
+        assert_noop!(
+            MantaPayPallet::mint(Origin::signed(1), doubled),
+            Error::<Test>::InvalidShape
+        );
+    });
+}
+
+/// Tests that mints without a public asset id are rejected before verification.
+#[test]
+fn mint_without_asset_id_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let mut mint = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+        mint.asset_id = None;
+        assert_noop!(
+            MantaPayPallet::mint(Origin::signed(1), mint),
+            Error::<Test>::InvalidShape
+        );
+    });
+}
+
+/// Tests that private transfers with a public asset id are rejected, alone and in a batch.
+#[test]
+fn private_transfer_with_asset_id_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id: AssetId = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, private_transfer) =
+            sample_private_transfer(asset_id.value(10), asset_id.value(20), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        let mut private_transfer = crate::TransferPost::from(private_transfer);
+        private_transfer.asset_id = Some(asset_id.0);
+        assert_noop!(
+            MantaPayPallet::private_transfer(Origin::signed(1), private_transfer.clone()),
+            Error::<Test>::InvalidShape
+        );
+        assert_noop!(
+            MantaPayPallet::batch_private_transfer(
+                Origin::signed(1),
+                vec![private_transfer],
+                BatchMode::Atomic,
+                None
+            ),
+            Error::<Test>::InvalidShape
+        );
+    });
+}
+
+/// Tests that reclaims without a public asset id are rejected before verification.
+#[test]
+fn reclaim_without_asset_id_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id: AssetId = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, reclaim) = sample_reclaim(asset_id.value(10), asset_id.value(20), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        let mut reclaim = crate::TransferPost::from(reclaim);
+        reclaim.asset_id = None;
+        assert_noop!(
+            MantaPayPallet::reclaim(Origin::signed(1), reclaim),
+            Error::<Test>::InvalidShape
+        );
+    });
+}
+
+/// Tests that a mint with an all-zero proof is rejected before verification, while the same
+/// mint with its valid proof is accepted.
+///
+/// # Note
+///
+/// An all-zero proof may not even decode as a proof, in which case it is rejected by the decoder
+/// before the call is dispatched, so the degeneracy check is asserted on the encoded proof
+/// regardless.
+#[test]
+fn mint_with_zero_proof_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id: AssetId = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let mint = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+        let proof_size = scale_codec::Encode::encoded_size(&mint.validity_proof);
+        let mut encoded = scale_codec::Encode::encode(&mint);
+        let proof_start = encoded.len() - proof_size;
+        assert!(!crate::types::is_degenerate_proof(&encoded[proof_start..]));
+        encoded[proof_start..].fill(0);
+        assert!(crate::types::is_degenerate_proof(&encoded[proof_start..]));
+        if let Ok(zeroed) =
+            <crate::TransferPost as scale_codec::Decode>::decode(&mut encoded.as_slice())
+        {
+            assert!(zeroed.has_degenerate_proof());
             assert_noop!(
-                MantaPayPallet::private_transfer(Origin::signed(1), private_transfer.into()),
-                Error::<Test>::AssetSpent,
+                MantaPayPallet::mint(Origin::signed(1), zeroed),
+                Error::<Test>::MalformedProof
             );
         }
+        assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint));
     });
 }
 
-/// Tests a [`Reclaim`] transaction.
+/// Tests that a mint pays the shielded fee from the public balance of the signer.
 #[test]
-fn reclaim_should_work() {
-    new_test_ext().execute_with(|| reclaim_test(1, &mut thread_rng()));
+fn mint_should_pay_shielded_fee() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id: AssetId = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000));
+        ShieldedFee::set(&Some(5));
+        FeeAssetId::set(&asset_id.0);
+        FeeDestination::set(&2);
+        assert_ok!(MantaPayPallet::mint(
+            Origin::signed(1),
+            sample_mint(asset_id.value(10), &mut rng).into()
+        ));
+        assert_eq!(MantaPayPallet::balance(1, asset_id.0), 985);
+        assert_eq!(MantaPayPallet::balance(2, asset_id.0), 5);
+    });
 }
 
-/// Tests multiple [`Reclaim`] transactions.
+/// Tests that a mint whose signer cannot pay the shielded fee is rejected.
 #[test]
-fn reclaim_10_times_should_work() {
-    new_test_ext().execute_with(|| reclaim_test(10, &mut thread_rng()));
+fn mint_without_shielded_fee_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id: AssetId = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000));
+        ShieldedFee::set(&Some(5));
+        FeeAssetId::set(&asset_id.0.wrapping_add(1));
+        assert_noop!(
+            MantaPayPallet::mint(
+                Origin::signed(1),
+                sample_mint(asset_id.value(10), &mut rng).into()
+            ),
+            Error::<Test>::CannotPayFee
+        );
+    });
 }
 
-/// Tests that a double-spent [`Reclaim`] will fail.
+/// Tests that a reclaim of zero value fails before its proof is checked.
 #[test]
-fn double_spend_in_reclaim_should_not_work() {
+fn zero_reclaim_should_not_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
     new_test_ext().execute_with(|| {
-        for reclaim in reclaim_test(1, &mut thread_rng()) {
+        let asset_id: AssetId = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (_, reclaim) = sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        let mut reclaim = crate::TransferPost::from(reclaim);
+        reclaim.sinks[0] = 0;
+        assert_noop!(
+            MantaPayPallet::reclaim(Origin::signed(1), reclaim),
+            Error::<Test>::ZeroReclaim
+        );
+    });
+}
+
+/// Tests that a reclaim below the minimum reclaim value fails before its proof is checked.
+#[test]
+fn reclaim_below_minimum_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    MinReclaimValue::set(&10_001);
+    new_test_ext().execute_with(|| {
+        let asset_id: AssetId = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (_, reclaim) = sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        assert_noop!(
+            MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()),
+            Error::<Test>::ReclaimTooSmall
+        );
+    });
+}
+
+/// Tests that a reclaim of exactly the minimum reclaim value is accepted.
+#[test]
+fn reclaim_at_minimum_should_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    MinReclaimValue::set(&10_000);
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let (mints, reclaim) =
+                sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            assert_ok!(MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()));
+            assert_eq!(MantaPayPallet::balance(1, asset_id.0), 980_000);
+        });
+}
+
+/// Tests that a reclaim fails before its proof is checked while fewer than the minimum anonymity
+/// set of UTXOs of its asset have been minted.
+#[test]
+fn reclaim_below_anonymity_set_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    MinAnonymitySet::set(&3);
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let (mints, reclaim) =
+                sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            assert_eq!(crate::UtxoCountPerAsset::<Test>::get(asset_id.0), 2);
             assert_noop!(
                 MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()),
-                Error::<Test>::AssetSpent,
+                Error::<Test>::AnonymitySetTooSmall
             );
-        }
+        });
+}
+
+/// Tests that a reclaim is accepted once exactly the minimum anonymity set of UTXOs of its asset
+/// have been minted.
+#[test]
+fn reclaim_at_anonymity_set_should_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    MinAnonymitySet::set(&2);
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let (mints, reclaim) =
+                sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            assert_ok!(MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()));
+            assert_eq!(MantaPayPallet::balance(1, asset_id.0), 980_000);
+        });
+}
+
+/// Counts the [`PoolBalanceLow`](crate::Event::PoolBalanceLow) events deposited so far.
+#[inline]
+fn pool_balance_low_events() -> usize {
+    System::events()
+        .into_iter()
+        .filter(|record| {
+            matches!(
+                record.event,
+                Event::MantaPayPallet(crate::Event::PoolBalanceLow { .. })
+            )
+        })
+        .count()
+}
+
+/// Tests that a reclaim taking the shielded pool below its watermark emits a single warning.
+#[test]
+fn reclaim_crossing_pool_watermark_should_warn_once() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    PoolLowWatermark::set(&Some(25_000));
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            let (mints, reclaim) =
+                sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            assert_eq!(MantaPayPallet::pool_balance(asset_id.0), 30_000);
+            assert_ok!(MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()));
+            assert_eq!(MantaPayPallet::pool_balance(asset_id.0), 20_000);
+            System::assert_last_event(Event::MantaPayPallet(crate::Event::PoolBalanceLow {
+                id: asset_id.0,
+                balance: 20_000,
+            }));
+            assert_eq!(pool_balance_low_events(), 1);
+        });
+}
+
+/// Tests that a reclaim from a shielded pool which is already below its watermark does not warn
+/// again.
+#[test]
+fn reclaim_below_pool_watermark_should_not_warn_again() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    PoolLowWatermark::set(&Some(40_000));
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            let (mints, reclaim) =
+                sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            assert_ok!(MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()));
+            assert_eq!(MantaPayPallet::pool_balance(asset_id.0), 20_000);
+            assert_eq!(pool_balance_low_events(), 0);
+        });
+}
+
+/// Tests that a reclaim within the cooldown of the previous reclaim of the same account fails
+/// before its proof is checked.
+#[test]
+fn reclaim_within_cooldown_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    ReclaimCooldown::set(&5);
+    new_test_ext().execute_with(|| {
+        let asset_id: AssetId = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        System::set_block_number(3);
+        crate::LastReclaimBlock::<Test>::insert(1, 1);
+        let (_, reclaim) = sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        assert_noop!(
+            MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()),
+            Error::<Test>::ReclaimCooldownActive
+        );
+    });
+}
+
+/// Tests that a reclaim is accepted once the cooldown of the previous reclaim of the same
+/// account has passed, restarting the cooldown.
+#[test]
+fn reclaim_after_cooldown_should_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    ReclaimCooldown::set(&5);
+    new_test_ext().execute_with(|| {
+        System::set_block_number(6);
+        crate::LastReclaimBlock::<Test>::insert(1, 1);
+        reclaim_test(1, &mut rng);
+        assert_eq!(crate::LastReclaimBlock::<Test>::get(1), Some(6));
+    });
+}
+
+/// Tests that a reclaim missing its sink is rejected gracefully instead of panicking.
+#[test]
+fn reclaim_without_sink_should_not_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let (mints, reclaim) =
+                sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            let mut reclaim = crate::TransferPost::from(reclaim);
+            reclaim.sinks.clear();
+            assert_noop!(
+                MantaPayPallet::reclaim(Origin::signed(1), reclaim),
+                Error::<Test>::InvalidShape
+            );
+        });
+}
+
+/// Tests that equal posts have equal hashes and that a modified post hashes differently.
+#[test]
+fn post_hash_should_be_canonical() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let post = crate::TransferPost::from(sample_mint(rng.gen(), &mut rng));
+    assert_eq!(post.hash(), post.clone().hash());
+    let mut flipped = post.clone();
+    flipped.sources[0] ^= 1;
+    assert_ne!(post.hash(), flipped.hash());
+}
+
+/// Tests that a double-spent [`Mint`] will fail.
+#[test]
+fn mint_existing_coin_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(32579));
+        let mint_post = sample_mint(asset_id.value(100), &mut rng);
+        assert_ok!(MantaPayPallet::mint(
+            Origin::signed(1),
+            mint_post.clone().into()
+        ));
+        assert_noop!(
+            MantaPayPallet::mint(Origin::signed(1), mint_post.into()),
+            Error::<Test>::AssetRegistered
+        );
+    });
+}
+
+/// Tests a [`PrivateTransfer`] transaction.
+#[test]
+fn private_transfer_should_work() {
+    require_proving_context!();
+    new_test_ext().execute_with(|| private_transfer_test(1, &mut thread_rng()));
+}
+
+/// Tests multiple [`PrivateTransfer`] transactions.
+#[test]
+fn private_transfer_10_times_should_work() {
+    require_proving_context!();
+    new_test_ext().execute_with(|| private_transfer_test(10, &mut thread_rng()));
+}
+
+/// Tests that a double-spent [`PrivateTransfer`] will fail.
+#[test]
+fn double_spend_in_private_transfer_should_not_work() {
+    require_proving_context!();
+    new_test_ext().execute_with(|| {
+        for private_transfer in private_transfer_test(1, &mut thread_rng()) {
+            assert_noop!(
+                MantaPayPallet::private_transfer(Origin::signed(1), private_transfer.into()),
+                Error::<Test>::AssetSpent,
+            );
+        }
+    });
+}
+
+/// Tests a [`Reclaim`] transaction.
+#[test]
+fn reclaim_should_work() {
+    require_proving_context!();
+    new_test_ext().execute_with(|| reclaim_test(1, &mut thread_rng()));
+}
+
+/// Tests multiple [`Reclaim`] transactions.
+#[test]
+fn reclaim_10_times_should_work() {
+    require_proving_context!();
+    new_test_ext().execute_with(|| reclaim_test(10, &mut thread_rng()));
+}
+
+/// Tests that mints and reclaims are counted towards the shielded pool of their asset, keeping
+/// the supply split between public and shielded balances.
+#[test]
+fn shielded_pool_counters_should_track_mints_and_reclaims() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let (mints, reclaim) =
+                sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            assert_eq!(MantaPayPallet::total_minted(asset_id.0), 30_000);
+            assert_eq!(MantaPayPallet::total_reclaimed(asset_id.0), 0);
+            assert_ok!(MantaPayPallet::do_try_state());
+            assert_ok!(MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()));
+            assert_eq!(MantaPayPallet::total_minted(asset_id.0), 30_000);
+            assert_eq!(MantaPayPallet::total_reclaimed(asset_id.0), 10_000);
+            assert_eq!(MantaPayPallet::balance(1, asset_id.0), 980_000);
+            assert_ok!(MantaPayPallet::do_try_state());
+        });
+}
+
+/// Tests that the quick invariant check catches counters which contradict each other.
+#[test]
+fn quick_invariant_check_should_catch_corrupted_counters() {
+    new_test_ext().execute_with(|| {
+        let id = 0;
+        MantaPayPallet::init_asset(&1, id, 1_000);
+        assert_ok!(MantaPayPallet::quick_invariant_check(id));
+        crate::VoidNumberSetSize::<Test>::put(3);
+        assert!(MantaPayPallet::quick_invariant_check(id).is_err());
+        crate::VoidNumberSetSize::<Test>::kill();
+        assert_ok!(MantaPayPallet::quick_invariant_check(id));
+        crate::TotalReclaimed::<Test>::insert(id, 10);
+        assert!(MantaPayPallet::quick_invariant_check(id).is_err());
+        crate::TotalMinted::<Test>::insert(id, 2_000);
+        assert!(MantaPayPallet::quick_invariant_check(id).is_err());
+    });
+}
+
+/// Tests that reclaims are described with their public sink amount and private transfers without
+/// any public amount.
+#[test]
+fn posts_should_be_described_with_public_amounts() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let asset_id: AssetId = rng.gen();
+    let (_, reclaim) = sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+    assert_eq!(
+        MantaPayPallet::describe_post(reclaim.into()),
+        PostDescription {
+            shape: Some(PostShape::Reclaim),
+            asset_id: Some(asset_id.0),
+            sources: vec![],
+            sinks: vec![10_000],
+            sender_count: 2,
+            receiver_count: 1,
+        }
+    );
+    let (_, private_transfer) =
+        sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+    assert_eq!(
+        MantaPayPallet::describe_post(private_transfer.into()),
+        PostDescription {
+            shape: Some(PostShape::PrivateTransfer),
+            asset_id: None,
+            sources: vec![],
+            sinks: vec![],
+            sender_count: 2,
+            receiver_count: 2,
+        }
+    );
+}
+
+/// Tests a [`PrivateTransfer`] built by [`sample_private_transfer`].
+#[test]
+fn sampled_private_transfer_should_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let (mints, private_transfer) =
+                sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            assert_ok!(MantaPayPallet::private_transfer(
+                Origin::signed(1),
+                private_transfer.into()
+            ));
+        });
+}
+
+/// Tests a [`Reclaim`] built by [`sample_reclaim`].
+#[test]
+fn sampled_reclaim_should_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, reclaim) =
+            sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        assert_ok!(MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()));
+        assert_eq!(MantaPayPallet::balance(1, asset_id.0), 1_000_000 - 20_000);
+    });
+}
+
+/// Tests that a [`Reclaim`] which cannot credit its sink is rejected before verification, even
+/// though its input coins were never minted.
+#[test]
+fn reclaim_to_account_that_cannot_receive_should_not_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let asset_id = AssetId(7);
+    let (_, reclaim) = sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MantaPayPallet::reclaim(Origin::signed(2), reclaim.clone().into()),
+            Error::<Test>::UninitializedSupply
+        );
+        initialize_test(asset_id, AssetValue(1_000_000));
+        MantaPayPallet::init_asset(&2, 8, 100);
+        MaxAssetsPerAccount::set(&Some(1));
+        assert_noop!(
+            MantaPayPallet::reclaim(Origin::signed(2), reclaim.clone().into()),
+            Error::<Test>::TooManyAssets
+        );
+        MaxAssetsPerAccount::set(&None);
+        assert_noop!(
+            MantaPayPallet::reclaim(Origin::signed(2), reclaim.into()),
+            Error::<Test>::InvalidUtxoAccumulatorOutput
+        );
+    });
+}
+
+/// Tests that a double-spent [`Reclaim`] will fail.
+#[test]
+fn double_spend_in_reclaim_should_not_work() {
+    require_proving_context!();
+    new_test_ext().execute_with(|| {
+        for reclaim in reclaim_test(1, &mut thread_rng()) {
+            assert_noop!(
+                MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()),
+                Error::<Test>::AssetSpent,
+            );
+        }
+    });
+}
+
+/// Tests that corrupted UTXO accumulator parameters are reported as an error instead of a panic.
+#[test]
+fn corrupted_parameters_should_not_work() {
+    let bytes = manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get()
+        .expect("Checksum did not match.");
+    assert!(matches!(
+        MantaPayPallet::decode_utxo_accumulator_model(&bytes[..bytes.len() / 2]),
+        Err(Error::<Test>::ParametersUnavailable)
+    ));
+    assert!(matches!(
+        MantaPayPallet::decode_utxo_accumulator_model(&[]),
+        Err(Error::<Test>::ParametersUnavailable)
+    ));
+    assert!(MantaPayPallet::decode_utxo_accumulator_model(bytes).is_ok());
+}
+
+/// Tests that a corrupted verifying context is reported as an error instead of a panic, both when
+/// posting and when validating without posting.
+#[test]
+fn corrupted_verifying_context_should_not_work() {
+    new_test_ext().execute_with(|| {
+        let mint = <crate::TransferPost as scale_codec::Decode>::decode(
+            &mut &*crate::precomputed_coins::MINT,
+        )
+        .expect("Unable to decode the precomputed mint.");
+        let id = mint.asset_id.expect("Mints have an asset id.");
+        MantaPayPallet::init_asset(&1, id, mint.sources[0]);
+        let context =
+            manta_sdk::pay::testnet::verifying::Mint::get().expect("Checksum did not match.");
+        crate::VerifyingContextOverrides::<Test>::insert(
+            PostShape::Mint,
+            context[..context.len() / 2].to_vec(),
+        );
+        assert!(!MantaPayPallet::is_consistent_mint(mint.clone()));
+        assert_noop!(
+            MantaPayPallet::mint(Origin::signed(1), mint),
+            Error::<Test>::ParametersUnavailable
+        );
+    });
+}
+
+/// Tests that the parameters fingerprint is stable and changes when the UTXO accumulator model
+/// is replaced.
+#[test]
+fn parameters_fingerprint_should_track_updates() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let fingerprint = MantaPayPallet::parameters_fingerprint();
+        assert_eq!(MantaPayPallet::parameters_fingerprint(), fingerprint);
+        let mut model = manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get()
+            .expect("Checksum did not match.")
+            .to_vec();
+        assert_noop!(
+            MantaPayPallet::set_utxo_accumulator_model(
+                Origin::root(),
+                model[..model.len() / 2].to_vec()
+            ),
+            Error::<Test>::ParametersUnavailable
+        );
+        model.push(0);
+        assert_ok!(MantaPayPallet::set_utxo_accumulator_model(
+            Origin::root(),
+            model
+        ));
+        let updated = MantaPayPallet::parameters_fingerprint();
+        assert_ne!(updated, fingerprint);
+        System::assert_last_event(Event::MantaPayPallet(crate::Event::ParametersUpdated {
+            fingerprint: updated,
+        }));
+    });
+}
+
+/// Tests that genesis loads the UTXO accumulator model and the verifying contexts from local
+/// files.
+#[test]
+fn genesis_parameters_from_file_should_work() {
+    let model = manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get()
+        .expect("Checksum did not match.");
+    let contexts = [
+        (
+            PostShape::Mint,
+            manta_sdk::pay::testnet::verifying::Mint::get().expect("Checksum did not match."),
+        ),
+        (
+            PostShape::PrivateTransfer,
+            manta_sdk::pay::testnet::verifying::PrivateTransfer::get()
+                .expect("Checksum did not match."),
+        ),
+        (
+            PostShape::Reclaim,
+            manta_sdk::pay::testnet::verifying::Reclaim::get().expect("Checksum did not match."),
+        ),
+    ];
+    let directory = tempfile::tempdir().expect("Unable to create temporary directory.");
+    let source = |name: &str, bytes: &[u8]| {
+        let path = directory.path().join(name);
+        std::fs::write(&path, bytes).expect("Unable to write the parameters file.");
+        ParametersSource::File(path.to_str().expect("Path should be valid UTF-8.").into())
+    };
+    let parameters = GenesisParameters {
+        utxo_accumulator_model: source("utxo-accumulator-model.dat", model),
+        mint: source("mint.dat", contexts[0].1),
+        private_transfer: source("private-transfer.dat", contexts[1].1),
+        reclaim: source("reclaim.dat", contexts[2].1),
+    };
+    ExtBuilder::default()
+        .with_parameters(parameters)
+        .build()
+        .execute_with(|| {
+            assert_eq!(
+                crate::UtxoAccumulatorModelOverride::<Test>::get().as_deref(),
+                Some(model)
+            );
+            for (shape, context) in contexts {
+                assert_eq!(
+                    crate::VerifyingContextOverrides::<Test>::get(shape).as_deref(),
+                    Some(context)
+                );
+            }
+            assert_ok!(MantaPayPallet::check_parameters());
+        });
+    new_test_ext().execute_with(|| {
+        assert_eq!(crate::UtxoAccumulatorModelOverride::<Test>::get(), None);
+        assert_eq!(crate::VerifyingContextOverrides::<Test>::iter().count(), 0);
+    });
+}
+
+/// Returns a UTXO accumulator model which decodes but differs from the one the verifying contexts
+/// were generated with, by flipping a single bit of the SDK model.
+#[inline]
+fn corrupted_utxo_accumulator_model() -> Vec<u8> {
+    let model = manta_sdk::pay::testnet::parameters::UtxoAccumulatorModel::get()
+        .expect("Checksum did not match.");
+    new_test_ext().execute_with(|| {
+        (0..model.len())
+            .find_map(|i| {
+                let mut corrupted = model.to_vec();
+                corrupted[i] ^= 1;
+                MantaPayPallet::set_utxo_accumulator_model(Origin::root(), corrupted.clone())
+                    .ok()
+                    .map(|_| corrupted)
+            })
+            .expect("Some single bit flip of the model should still decode.")
+    })
+}
+
+/// Tests that the parameter check rejects a UTXO accumulator model which differs from the one
+/// the verifying contexts were generated with.
+#[test]
+fn mismatched_parameters_should_fail_check() {
+    let corrupted = corrupted_utxo_accumulator_model();
+    new_test_ext().execute_with(|| {
+        assert_ok!(MantaPayPallet::check_parameters());
+        assert_ok!(MantaPayPallet::set_utxo_accumulator_model(
+            Origin::root(),
+            corrupted
+        ));
+        assert!(matches!(
+            MantaPayPallet::check_parameters(),
+            Err(Error::<Test>::InconsistentParameters)
+        ));
+    });
+}
+
+/// Tests that the genesis build rejects a stored UTXO accumulator model which differs from the
+/// one the verifying contexts were generated with.
+#[test]
+#[should_panic(
+    expected = "The genesis parameters should be consistent with the verifying contexts."
+)]
+fn mismatched_genesis_parameters_should_not_build() {
+    ExtBuilder::default()
+        .with_parameters(GenesisParameters {
+            utxo_accumulator_model: ParametersSource::Embedded(corrupted_utxo_accumulator_model()),
+            ..Default::default()
+        })
+        .build();
+}
+
+/// Tests that a queued [`PrivateTransfer`] is charged the full verification weight and is
+/// verified and posted in a later block.
+#[test]
+fn queued_private_transfer_should_be_finalized() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        MaxPendingPosts::set(&4);
+        System::set_block_number(1);
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, private_transfer) =
+            sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        let post_info =
+            MantaPayPallet::private_transfer(Origin::signed(1), private_transfer.into())
+                .expect("The sampled private transfer should be queued.");
+        assert_eq!(post_info.actual_weight, None);
+        System::assert_last_event(Event::MantaPayPallet(crate::Event::PostQueued {
+            origin: 1,
+        }));
+        assert_eq!(PendingPosts::<Test>::get().len(), 1);
+        System::set_block_number(2);
+        MantaPayPallet::on_idle(2, Weight::MAX);
+        System::assert_last_event(Event::MantaPayPallet(crate::Event::PrivateTransfer {
+            origin: 1,
+        }));
+        assert!(PendingPosts::<Test>::get().is_empty());
+    });
+}
+
+/// Tests that a queued post registering a UTXO already registered by a post verified earlier in
+/// the same block is dropped by the [`UtxoSet`](crate::UtxoSet) check before its proof is
+/// verified, while a post with an invalid proof cannot block a valid post registering the same
+/// UTXOs.
+#[cfg(not(feature = "skip-verification"))]
+#[test]
+fn duplicate_registration_in_block_should_not_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        MaxPendingPosts::set(&4);
+        System::set_block_number(1);
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, private_transfer) =
+            sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        let private_transfer = crate::TransferPost::from(private_transfer);
+        let mut forged = private_transfer.clone();
+        forged.sender_posts[0].void_number = Default::default();
+        assert_ok!(MantaPayPallet::private_transfer(Origin::signed(2), forged));
+        assert_ok!(MantaPayPallet::private_transfer(
+            Origin::signed(1),
+            private_transfer.clone()
+        ));
+        assert_ok!(MantaPayPallet::private_transfer(
+            Origin::signed(3),
+            private_transfer.clone()
+        ));
+        MantaPayPallet::on_idle(1, Weight::MAX);
+        System::assert_has_event(Event::MantaPayPallet(crate::Event::PendingPostDropped {
+            origin: 2,
+            error: Error::<Test>::InvalidProof.into(),
+        }));
+        System::assert_has_event(Event::MantaPayPallet(crate::Event::PrivateTransfer {
+            origin: 1,
+        }));
+        System::assert_last_event(Event::MantaPayPallet(crate::Event::PendingPostDropped {
+            origin: 3,
+            error: Error::<Test>::AssetRegistered.into(),
+        }));
+        assert!(PendingPosts::<Test>::get().is_empty());
+    });
+}
+
+/// Tests that a queued [`PrivateTransfer`] which fails to post is dropped with an event.
+#[test]
+fn invalid_queued_private_transfer_should_be_dropped() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        MaxPendingPosts::set(&4);
+        System::set_block_number(1);
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, private_transfer) =
+            sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        assert_ok!(MantaPayPallet::private_transfer(
+            Origin::signed(1),
+            private_transfer.clone().into()
+        ));
+        System::set_block_number(2);
+        assert_ok!(MantaPayPallet::private_transfer(
+            Origin::signed(2),
+            private_transfer.into()
+        ));
+        MantaPayPallet::on_idle(2, Weight::MAX);
+        System::assert_has_event(Event::MantaPayPallet(crate::Event::PrivateTransfer {
+            origin: 1,
+        }));
+        System::assert_last_event(Event::MantaPayPallet(crate::Event::PendingPostDropped {
+            origin: 2,
+            error: Error::<Test>::AssetSpent.into(),
+        }));
+        assert!(PendingPosts::<Test>::get().is_empty());
+    });
+}
+
+/// Tests that a queued [`PrivateTransfer`] whose receivers no longer fit into their shard is
+/// dropped whole, without spending its void numbers.
+#[test]
+fn queued_private_transfer_into_full_shard_should_be_dropped() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        MaxPendingPosts::set(&4);
+        System::set_block_number(1);
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, private_transfer) =
+            sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        let private_transfer = crate::TransferPost::from(private_transfer);
+        assert_ok!(MantaPayPallet::private_transfer(
+            Origin::signed(1),
+            private_transfer.clone()
+        ));
+        leave_shard_slots(
+            MantaPayPallet::utxo_shard(&private_transfer.receiver_posts[0].utxo),
+            0,
+        );
+        MantaPayPallet::on_idle(1, Weight::MAX);
+        System::assert_last_event(Event::MantaPayPallet(crate::Event::PendingPostDropped {
+            origin: 1,
+            error: Error::<Test>::ShardFull.into(),
+        }));
+        assert!(PendingPosts::<Test>::get().is_empty());
+        for sender_post in &private_transfer.sender_posts {
+            assert!(!crate::VoidNumberSet::<Test>::contains_key(
+                sender_post.void_number
+            ));
+        }
+    });
+}
+
+/// Tests that a batch of independent [`PrivateTransfer`]s is posted.
+#[test]
+fn independent_private_transfer_batch_should_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let mut utxo_accumulator = UtxoAccumulator::new(UTXO_ACCUMULATOR_MODEL.clone());
+        let mut mints = Vec::new();
+        let mut private_transfers = Vec::new();
+        for _ in 0..2 {
+            let (mint, private_transfer) = sample_private_transfer_with(
+                &mut utxo_accumulator,
+                asset_id.value(10_000),
+                asset_id.value(20_000),
+                &mut rng,
+            );
+            mints.extend(mint);
+            private_transfers.push(private_transfer.into());
+        }
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        assert_ok!(MantaPayPallet::batch_private_transfer(
+            Origin::signed(1),
+            private_transfers,
+            BatchMode::Atomic,
+            None
+        ));
+        assert_eq!(crate::VoidNumberSetSize::<Test>::get(), 4);
+    });
+}
+
+/// Tests that a batch of dependent [`PrivateTransfer`]s is reverted entirely.
+#[test]
+fn dependent_private_transfer_batch_should_not_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, private_transfer) =
+            sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        let private_transfer = crate::TransferPost::from(private_transfer);
+        assert_noop!(
+            MantaPayPallet::batch_private_transfer(
+                Origin::signed(1),
+                vec![private_transfer.clone(), private_transfer],
+                BatchMode::Atomic,
+                None
+            ),
+            Error::<Test>::AssetSpent
+        );
+        assert_eq!(crate::VoidNumberSetSize::<Test>::get(), 0);
+    });
+}
+
+/// Tests that a batch larger than the configured maximum is rejected.
+#[test]
+fn oversized_private_transfer_batch_should_not_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        let post = crate::TransferPost::from(sample_mint(rng.gen(), &mut rng));
+        assert_noop!(
+            MantaPayPallet::batch_private_transfer(
+                Origin::signed(1),
+                vec![post; 5],
+                BatchMode::Atomic,
+                None
+            ),
+            Error::<Test>::BatchTooLarge
+        );
+    });
+}
+
+/// Tests that the void number count matches the number of spent coins.
+#[test]
+fn void_number_count_should_match_spent_coins() {
+    require_proving_context!();
+    new_test_ext().execute_with(|| {
+        assert_eq!(MantaPayPallet::void_number_count(), 0);
+        let posts = private_transfer_test(2, &mut thread_rng());
+        assert_eq!(
+            MantaPayPallet::void_number_count(),
+            posts
+                .iter()
+                .map(|post| post.sender_posts.len() as u64)
+                .sum::<u64>()
+        );
+    });
+}
+
+/// Tests that the storage statistics follow mints and spends.
+#[test]
+fn storage_stats_should_follow_ledger_operations() {
+    require_proving_context!();
+    new_test_ext().execute_with(|| {
+        assert_eq!(MantaPayPallet::storage_stats(), StorageStats::default());
+        let posts = private_transfer_test(2, &mut thread_rng());
+        let stats = MantaPayPallet::storage_stats();
+        assert_eq!(
+            stats.utxo_count,
+            crate::UtxoSet::<Test>::iter_keys().count() as u64
+        );
+        assert_eq!(
+            stats.void_number_count,
+            posts
+                .iter()
+                .map(|post| post.sender_posts.len() as u64)
+                .sum::<u64>()
+        );
+        assert_eq!(
+            stats.root_count,
+            crate::UtxoAccumulatorOutputs::<Test>::iter_keys().count() as u64
+        );
+        assert_eq!(
+            stats.shard_sizes.iter().map(|(_, size)| size).sum::<u64>(),
+            stats.utxo_count
+        );
+        for (shard_index, size) in stats.shard_sizes {
+            assert_eq!(
+                crate::Shards::<Test>::iter_prefix(shard_index).count() as u64,
+                size
+            );
+        }
+    });
+}
+
+/// Tests that batched root queries report each root in order and reject queries of more than
+/// `MaxRootQuery`-many roots.
+#[test]
+fn known_roots_should_report_each_root() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, private_transfer) =
+            sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        let first = private_transfer.sender_posts[0].utxo_accumulator_output;
+        let second = private_transfer.sender_posts[1].utxo_accumulator_output;
+        let unknown = Default::default();
+        assert_eq!(
+            MantaPayPallet::known_roots(vec![first, unknown, second]),
+            Ok(vec![true, false, true])
+        );
+        assert_eq!(
+            MantaPayPallet::known_roots(vec![unknown, first, unknown, second]),
+            Ok(vec![false, true, false, true])
+        );
+        assert_eq!(
+            MantaPayPallet::known_roots(vec![unknown, first, unknown, second, first]),
+            Err(QueryError::TooManyItems { max: 4 })
+        );
+    });
+}
+
+/// Tests that strict root mode rejects senders whose root is no longer the latest root of its
+/// shard, while the default mode accepts any historical root.
+#[test]
+fn strict_root_mode_should_reject_old_roots() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        // NOTE: Both senders are built against the latest roots only if their mints land in
+        //       different shards, since the first sender is built before the second mint.
+        let (mints, private_transfer) = loop {
+            let (mints, private_transfer) =
+                sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            if MerkleTreeConfiguration::tree_index(&mints[0].receiver_posts[0].utxo)
+                != MerkleTreeConfiguration::tree_index(&mints[1].receiver_posts[0].utxo)
+            {
+                break (mints, private_transfer);
+            }
+        };
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        let private_transfer = crate::TransferPost::from(private_transfer);
+        StrictRootMode::set(&true);
+        assert!(MantaPayPallet::dry_run_against_root(
+            1,
+            private_transfer.clone(),
+            private_transfer.sender_posts[0].utxo_accumulator_output
+        ));
+        let root = private_transfer.sender_posts[0].utxo_accumulator_output;
+        let shard_index = crate::LatestRoot::<Test>::iter()
+            .find(|(_, latest_root)| *latest_root == root)
+            .map(|(shard_index, _)| shard_index)
+            .expect("The sender root should be the latest root of its shard.");
+        advance_shard(shard_index);
+        assert!(!MantaPayPallet::is_known_root(root));
+        assert_noop!(
+            MantaPayPallet::private_transfer(Origin::signed(1), private_transfer.clone()),
+            Error::<Test>::InvalidUtxoAccumulatorOutput
+        );
+        StrictRootMode::set(&false);
+        assert_ok!(MantaPayPallet::private_transfer(
+            Origin::signed(1),
+            private_transfer
+        ));
+    });
+}
+
+/// Tests that a [`PrivateTransfer`] built against an old root still passes a dry run after the
+/// accumulator has advanced.
+#[test]
+fn dry_run_against_old_root_should_work() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    new_test_ext().execute_with(|| {
+        let asset_id = rng.gen();
+        initialize_test(asset_id, AssetValue(1_000_000));
+        let (mints, private_transfer) =
+            sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+        for mint in mints {
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+        }
+        let private_transfer = crate::TransferPost::from(private_transfer);
+        let root = private_transfer.sender_posts[0].utxo_accumulator_output;
+        mint_tokens(asset_id, &[AssetValue(100), AssetValue(200)], &mut rng);
+        assert!(MantaPayPallet::is_known_root(root));
+        assert!(MantaPayPallet::dry_run_against_root(
+            1,
+            private_transfer.clone(),
+            root
+        ));
+        assert!(!MantaPayPallet::dry_run_against_root(
+            1,
+            private_transfer.clone(),
+            Default::default()
+        ));
+        assert_ok!(MantaPayPallet::private_transfer(
+            Origin::signed(1),
+            private_transfer.clone()
+        ));
+        assert!(!MantaPayPallet::dry_run_against_root(
+            1,
+            private_transfer,
+            root
+        ));
+    });
+}
+
+/// Tests that each kind of post updates balances, counters, and events as expected when posted
+/// one after the other.
+#[test]
+fn posts_of_each_shape_should_update_ledger() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            let (mints, private_transfer) =
+                sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            System::assert_last_event(Event::MantaPayPallet(crate::Event::Mint {
+                asset: crate::Asset::new(asset_id.0, 20_000),
+                source: 1,
+            }));
+            assert_ok!(MantaPayPallet::private_transfer(
+                Origin::signed(2),
+                private_transfer.into()
+            ));
+            System::assert_last_event(Event::MantaPayPallet(crate::Event::PrivateTransfer {
+                origin: 2,
+            }));
+            assert_eq!(MantaPayPallet::void_number_count(), 2);
+            let (mints, reclaim) =
+                sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            assert_ok!(MantaPayPallet::reclaim(Origin::signed(3), reclaim.into()));
+            System::assert_last_event(Event::MantaPayPallet(crate::Event::Reclaim {
+                asset: crate::Asset::new(asset_id.0, 10_000),
+                sink: 3,
+            }));
+            assert_eq!(MantaPayPallet::void_number_count(), 4);
+            assert_eq!(MantaPayPallet::balance(1, asset_id.0), 1_000_000 - 60_000);
+            assert_eq!(MantaPayPallet::balance(3, asset_id.0), 10_000);
+            assert_eq!(MantaPayPallet::total_minted(asset_id.0), 60_000);
+            assert_eq!(MantaPayPallet::total_reclaimed(asset_id.0), 10_000);
+        });
+}
+
+/// Tests that [`Mint`] and [`Reclaim`] events report the runtime asset id of the mock, which is a
+/// `u32`.
+#[test]
+fn events_should_report_runtime_asset_id() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            let (mints, reclaim) =
+                sample_reclaim(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            let minted: crate::Asset<u32> = crate::Asset {
+                id: asset_id.0,
+                value: 20_000,
+            };
+            System::assert_last_event(Event::MantaPayPallet(crate::Event::Mint {
+                asset: minted,
+                source: 1,
+            }));
+            assert_ok!(MantaPayPallet::reclaim(Origin::signed(1), reclaim.into()));
+            match System::events().last().map(|record| record.event.clone()) {
+                Some(Event::MantaPayPallet(crate::Event::Reclaim { asset, sink })) => {
+                    let id: u32 = asset.id;
+                    assert_eq!(id, asset_id.0);
+                    assert_eq!(asset.value, 10_000);
+                    assert_eq!(sink, 1);
+                }
+                event => panic!("Expected a reclaim event, found {:?}.", event),
+            }
+        });
+}
+
+/// Tests that mints up to [`MaxUtxoCount`] succeed and that the next one is rejected without
+/// touching the ledger.
+#[test]
+fn mint_beyond_utxo_capacity_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    MaxUtxoCount::set(&2);
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            mint_tokens(asset_id, &[AssetValue(10), AssetValue(20)], &mut rng);
+            assert_eq!(crate::TotalUtxoCount::<Test>::get(), 2);
+            assert_noop!(
+                MantaPayPallet::mint(
+                    Origin::signed(1),
+                    sample_mint(asset_id.value(30), &mut rng).into()
+                ),
+                Error::<Test>::LedgerFull
+            );
+        });
+}
+
+/// Tests that a mint emits the detailed [`Mint`] event by default and only the [`Posted`] marker
+/// when detailed events are disabled.
+///
+/// [`Posted`]: crate::Event::Posted
+#[test]
+fn quiet_mint_should_emit_posted_marker() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    for detailed in [true, false] {
+        EmitDetailedEvents::set(&detailed);
+        let asset_id: AssetId = rng.gen();
+        ExtBuilder::default()
+            .with_assets(vec![(asset_id.0, 1_000_000)])
+            .build()
+            .execute_with(|| {
+                System::set_block_number(1);
+                assert_ok!(MantaPayPallet::mint(
+                    Origin::signed(1),
+                    sample_mint(asset_id.value(10), &mut rng).into()
+                ));
+                let expected = if detailed {
+                    crate::Event::Mint {
+                        asset: crate::Asset::new(asset_id.0, 10),
+                        source: 1,
+                    }
+                } else {
+                    crate::Event::Posted
+                };
+                System::assert_last_event(Event::MantaPayPallet(expected));
+                assert_eq!(System::events().len(), 1);
+            });
+    }
+}
+
+/// Tests that a freshly minted coin can be read back from its shard without knowing its index.
+#[test]
+fn latest_utxo_should_return_minted_coin() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let mint = sample_mint(asset_id.value(10), &mut rng);
+            let utxo = mint.receiver_posts[0].utxo;
+            let shard_index = MerkleTreeConfiguration::tree_index(&utxo);
+            assert!(MantaPayPallet::shard_is_empty(shard_index));
+            assert_eq!(MantaPayPallet::latest_utxo(shard_index), None);
+            let mint = crate::TransferPost::from(mint);
+            let note = mint.receiver_posts[0].note.clone();
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint));
+            assert!(!MantaPayPallet::shard_is_empty(shard_index));
+            assert_eq!(
+                MantaPayPallet::latest_utxo(shard_index),
+                Some((utxo, note, 0))
+            );
+        });
+}
+
+/// Registers a placeholder UTXO in the shard with the given `shard_index`, which moves the latest
+/// root of the shard.
+#[inline]
+fn advance_shard(shard_index: u8) {
+    let utxo = (0..=u16::MAX)
+        .map(super::ledger::utxo)
+        .find(|utxo| MantaPayPallet::utxo_shard(utxo) == shard_index)
+        .expect("The candidates should span every shard.");
+    let mut ledger = MantaPayPallet::ledger().expect("Parameters should be available.");
+    ledger.register_all(
+        [(
+            crate::Wrap::new(utxo),
+            crate::types::EncryptedNote::default().into(),
+        )],
+        &(crate::Wrap::new(()), ()),
+    );
+}
+
+/// Tests that the current path of a freshly minted coin verifies against the latest root of its
+/// shard, that a tampered path does not, and that no path verifies against a root which strict
+/// root mode no longer accepts.
+#[test]
+fn verify_membership_should_check_stored_path() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let mint = sample_mint(asset_id.value(10), &mut rng);
+            let utxo = mint.receiver_posts[0].utxo;
+            let shard_index = MerkleTreeConfiguration::tree_index(&utxo);
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            let mut path = crate::ShardTrees::<Test>::get(shard_index).current_path;
+            let root = crate::LatestRoot::<Test>::get(shard_index)
+                .expect("The shard of the minted coin should have a root.");
+            assert!(MantaPayPallet::verify_membership(utxo, path.clone(), root));
+            advance_shard(shard_index);
+            assert!(MantaPayPallet::verify_membership(utxo, path.clone(), root));
+            StrictRootMode::set(&true);
+            assert!(!MantaPayPallet::verify_membership(utxo, path.clone(), root));
+            StrictRootMode::set(&false);
+            path.leaf_index += 1;
+            assert!(!MantaPayPallet::verify_membership(utxo, path, root));
+        });
+}
+
+/// Tests that the shard reported for a minted UTXO is the shard it was stored in.
+#[test]
+fn utxo_shard_should_match_stored_shard() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let mint = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+            let utxo = mint.receiver_posts[0].utxo;
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint));
+            let shard_index = MantaPayPallet::utxo_shard(&utxo);
+            assert_eq!(crate::Shards::<Test>::get(shard_index, 0).0, utxo);
+            assert!((0..=u8::MAX)
+                .filter(|index| *index != shard_index)
+                .all(MantaPayPallet::shard_is_empty));
+        });
+}
+
+/// Tests that the receiver of a private transfer colliding with a registered UTXO is reported by
+/// its index and UTXO.
+#[test]
+fn registered_receiver_should_be_reported() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let (mints, private_transfer) =
+                sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            let registered_utxo = mints[0].receiver_posts[0].utxo;
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            let mut private_transfer = crate::TransferPost::from(private_transfer);
+            assert_eq!(
+                MantaPayPallet::find_registered_receiver(&private_transfer),
+                None
+            );
+            private_transfer.receiver_posts[1].utxo = registered_utxo;
+            assert_noop!(
+                MantaPayPallet::private_transfer(Origin::signed(1), private_transfer.clone()),
+                Error::<Test>::AssetRegistered
+            );
+            assert_eq!(
+                MantaPayPallet::find_registered_receiver(&private_transfer),
+                Some((1, registered_utxo))
+            );
+        });
+}
+
+/// Tests that two mints of the same value register distinct UTXOs, since value equality does not
+/// imply UTXO equality.
+#[test]
+fn same_value_mints_should_register_distinct_utxos() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let first = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+            let second = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+            assert_eq!(first.sources, second.sources);
+            assert_ne!(first.receiver_posts[0].utxo, second.receiver_posts[0].utxo);
+            let utxos = [first.receiver_posts[0].utxo, second.receiver_posts[0].utxo];
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), first));
+            assert_ok!(MantaPayPallet::mint(Origin::signed(1), second));
+            assert!(utxos
+                .iter()
+                .all(|utxo| crate::UtxoSet::<Test>::contains_key(utxo)));
+            assert_eq!(crate::TotalUtxoCount::<Test>::get(), 2);
+            assert_eq!(MantaPayPallet::balance(1, asset_id.0), 1_000_000 - 20);
+        });
+}
+
+/// Tests that a batch of mints registers every minted coin and debits their total value.
+#[test]
+fn batch_mint_should_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let posts = [10, 20, 30]
+                .into_iter()
+                .map(|value| sample_mint(asset_id.value(value), &mut rng).into())
+                .collect();
+            assert_ok!(MantaPayPallet::batch_mint(
+                Origin::signed(1),
+                posts,
+                BatchMode::Atomic,
+                None
+            ));
+            assert_eq!(MantaPayPallet::balance(1, asset_id.0), 1_000_000 - 60);
+            assert_eq!(crate::TotalUtxoCount::<Test>::get(), 3);
+        });
+}
+
+/// Tests that a best-effort batch of mints skips a duplicate middle post and registers the
+/// others, while an atomic batch with the same posts is reverted.
+#[test]
+fn best_effort_batch_mint_should_skip_failed_post() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            let first = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+            let last = crate::TransferPost::from(sample_mint(asset_id.value(20), &mut rng));
+            let posts = vec![first.clone(), first, last];
+            assert_noop!(
+                MantaPayPallet::batch_mint(
+                    Origin::signed(1),
+                    posts.clone(),
+                    BatchMode::Atomic,
+                    None
+                ),
+                Error::<Test>::AssetRegistered
+            );
+            assert_ok!(MantaPayPallet::batch_mint(
+                Origin::signed(1),
+                posts,
+                BatchMode::BestEffort,
+                None
+            ));
+            assert_eq!(MantaPayPallet::balance(1, asset_id.0), 1_000_000 - 30);
+            assert_eq!(crate::TotalUtxoCount::<Test>::get(), 2);
+            System::assert_has_event(Event::MantaPayPallet(crate::Event::BatchLegOutcome {
+                index: 1,
+                result: Err(Error::<Test>::AssetRegistered.into()),
+            }));
+        });
+}
+
+/// Leaves `free` slots in the shard with the given `shard_index` by marking the rest as taken.
+#[inline]
+fn leave_shard_slots(shard_index: u8, free: u64) {
+    let capacity =
+        1u64 << (<MerkleTreeConfiguration as manta_crypto::merkle_tree::Configuration>::HEIGHT - 1);
+    crate::ShardTrees::<Test>::mutate(shard_index, |tree| {
+        tree.leaf_digest = Some(Default::default());
+        tree.current_path.leaf_index = (capacity - 1 - free) as _;
+    });
+}
+
+/// Tests that a batch of mints whose receivers fill a shard fails the post which overflows it
+/// with [`Error::ShardFull`] instead of reporting an inconsistent ledger, and that the same
+/// check applies to the mint entry point.
+#[test]
+fn batch_mint_into_full_shard_should_not_work() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            let first = crate::TransferPost::from(sample_mint(asset_id.value(10), &mut rng));
+            let shard_index = MantaPayPallet::utxo_shard(&first.receiver_posts[0].utxo);
+            let second = loop {
+                let second = crate::TransferPost::from(sample_mint(asset_id.value(20), &mut rng));
+                if MantaPayPallet::utxo_shard(&second.receiver_posts[0].utxo) == shard_index {
+                    break second;
+                }
+            };
+            leave_shard_slots(shard_index, 1);
+            let posts = vec![first, second.clone()];
+            assert_noop!(
+                MantaPayPallet::batch_mint(
+                    Origin::signed(1),
+                    posts.clone(),
+                    BatchMode::Atomic,
+                    None
+                ),
+                Error::<Test>::ShardFull
+            );
+            assert_ok!(MantaPayPallet::batch_mint(
+                Origin::signed(1),
+                posts,
+                BatchMode::BestEffort,
+                None
+            ));
+            assert_eq!(MantaPayPallet::balance(1, asset_id.0), 1_000_000 - 10);
+            assert_eq!(crate::TotalUtxoCount::<Test>::get(), 1);
+            System::assert_has_event(Event::MantaPayPallet(crate::Event::BatchLegOutcome {
+                index: 1,
+                result: Err(Error::<Test>::ShardFull.into()),
+            }));
+            assert_noop!(
+                MantaPayPallet::mint(Origin::signed(1), second),
+                Error::<Test>::ShardFull
+            );
+        });
+}
+
+/// Tests that a batch of mints emits its mint events in post order and registers the UTXO of
+/// every post, so that indexers can match events to posts by position.
+#[test]
+fn batch_mint_events_should_follow_post_order() {
+    require_proving_context!();
+    let mut rng = seeded_rng();
+    let asset_id: AssetId = rng.gen();
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            System::set_block_number(1);
+            let values = [30, 10, 20];
+            let posts = values
+                .into_iter()
+                .map(|value| {
+                    crate::TransferPost::from(sample_mint(asset_id.value(value), &mut rng))
+                })
+                .collect::<Vec<_>>();
+            let utxos = posts
+                .iter()
+                .map(|post| post.receiver_posts[0].utxo)
+                .collect::<Vec<_>>();
+            assert_ok!(MantaPayPallet::batch_mint(
+                Origin::signed(1),
+                posts,
+                BatchMode::Atomic,
+                None
+            ));
+            let minted = System::events()
+                .into_iter()
+                .filter_map(|record| match record.event {
+                    Event::MantaPayPallet(crate::Event::Mint { asset, source }) => {
+                        assert_eq!(asset.id, asset_id.0);
+                        assert_eq!(source, 1);
+                        Some(asset.value)
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(minted, values);
+            for utxo in utxos {
+                assert!(crate::UtxoSet::<Test>::contains_key(utxo));
+            }
+        });
+}
+
+/// Tests that a snapshot of a populated ledger restores the same ledger into a fresh instance.
+#[test]
+fn ledger_snapshot_should_round_trip() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let asset_id: AssetId = rng.gen();
+    let snapshot = ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let (mints, private_transfer) =
+                sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            assert_ok!(MantaPayPallet::private_transfer(
+                Origin::signed(1),
+                private_transfer.into()
+            ));
+            MantaPayPallet::export_ledger()
+        });
+    assert_eq!(snapshot.utxo_set.len(), 4);
+    assert_eq!(snapshot.void_numbers.len(), 2);
+    assert_eq!(snapshot.latest_roots.len(), snapshot.shard_trees.len());
+    assert_eq!(snapshot.total_minted, vec![(asset_id.0, 30_000)]);
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MantaPayPallet::import_ledger(Origin::signed(1), snapshot.clone(), true),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(MantaPayPallet::import_ledger(
+            Origin::root(),
+            snapshot.clone(),
+            true
+        ));
+        let imported = MantaPayPallet::export_ledger();
+        assert_eq!(imported.shard_trees, snapshot.shard_trees);
+        assert_eq!(
+            imported.utxo_accumulator_outputs,
+            snapshot.utxo_accumulator_outputs
+        );
+        assert_eq!(imported, snapshot);
+        assert_eq!(crate::TotalUtxoCount::<Test>::get(), 4);
+        assert_eq!(MantaPayPallet::pool_balance(asset_id.0), 30_000);
+        assert_noop!(
+            MantaPayPallet::import_ledger(Origin::root(), snapshot, false),
+            Error::<Test>::LedgerNotEmpty
+        );
+    });
+}
+
+/// Tests that snapshots encoded by older known versions are decoded and migrated on import while
+/// unknown versions are rejected.
+#[test]
+fn ledger_snapshot_version_should_be_checked() {
+    new_test_ext().execute_with(|| {
+        let snapshot = MantaPayPallet::export_ledger();
+        assert_eq!(snapshot.version, LedgerSnapshot::VERSION);
+        assert_eq!(
+            LedgerSnapshot::decode_all(&snapshot.encode()).expect("Decoding should succeed."),
+            snapshot
+        );
+        let decode_legacy = |version: &[u8]| {
+            let mut bytes = version.to_vec();
+            (
+                &snapshot.shards,
+                &snapshot.shard_trees,
+                &snapshot.utxo_set,
+                &snapshot.utxo_accumulator_outputs,
+                &snapshot.void_numbers,
+            )
+                .encode_to(&mut bytes);
+            LedgerSnapshot::decode_all(&bytes).expect("Decoding should succeed.")
+        };
+        let v1 = decode_legacy(&[1]);
+        let v2 = decode_legacy(&2u16.to_le_bytes());
+        assert_eq!((v1.version, v2.version), (1, 2));
+        assert_eq!(v2.clone().migrate(), Some(snapshot.clone()));
+        assert_noop!(
+            MantaPayPallet::import_ledger(
+                Origin::root(),
+                LedgerSnapshot {
+                    version: LedgerSnapshot::VERSION + 1,
+                    ..snapshot.clone()
+                },
+                false
+            ),
+            Error::<Test>::UnsupportedSnapshotVersion
+        );
+        assert_ok!(MantaPayPallet::import_ledger(Origin::root(), v1, false));
+        assert_ok!(MantaPayPallet::import_ledger(
+            Origin::root(),
+            snapshot,
+            true
+        ));
+    });
+}
+
+/// Tests that private transfers are refunded the shard tree access they do not use when both of
+/// their receivers land in the same shard.
+#[test]
+fn same_shard_private_transfer_should_be_billed_less() {
+    require_proving_context!();
+    let mut rng = thread_rng();
+    let asset_id: AssetId = rng.gen();
+    DbWeight::set(&RuntimeDbWeight {
+        read: 20_000_000,
+        write: 100_000_000,
+    });
+    ExtBuilder::default()
+        .with_assets(vec![(asset_id.0, 1_000_000)])
+        .build()
+        .execute_with(|| {
+            let full = <Test as crate::Config>::WeightInfo::private_transfer();
+            assert_eq!(MantaPayPallet::private_transfer_weight(1, 2), full);
+            assert_eq!(
+                MantaPayPallet::private_transfer_weight(1, 1),
+                full - 120_000_000
+            );
+            assert_eq!(
+                MantaPayPallet::private_transfer_weight(2, 3),
+                2 * full - 120_000_000
+            );
+            let (mints, private_transfer) =
+                sample_private_transfer(asset_id.value(10_000), asset_id.value(20_000), &mut rng);
+            for mint in mints {
+                assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint.into()));
+            }
+            let mut shards = private_transfer
+                .receiver_posts
+                .iter()
+                .map(|post| MerkleTreeConfiguration::tree_index(&post.utxo))
+                .collect::<Vec<_>>();
+            shards.dedup();
+            let post_info =
+                MantaPayPallet::private_transfer(Origin::signed(1), private_transfer.into())
+                    .expect("The sampled private transfer should be valid.");
+            let refund = if shards.len() == 1 { 120_000_000 } else { 0 };
+            assert_eq!(post_info.actual_weight, Some(full - refund));
+        });
+}
+
+/// Tests that the reported limits match the configured constants of the mock runtime.
+#[test]
+fn limits_should_match_configuration() {
+    MaxAssetsPerAccount::set(&Some(3));
+    MinReclaimValue::set(&5);
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            MantaPayPallet::limits(),
+            PalletLimits {
+                max_private_batch: 4,
+                max_public_batch: 8,
+                max_pending_posts: 0,
+                max_senders: 2,
+                max_receivers: 2,
+                max_note_size: 1024,
+                max_proof_size: 1024,
+                max_void_number_query: 4,
+                max_root_query: 4,
+                max_utxo_count: u64::MAX,
+                max_assets_per_account: Some(3),
+                min_reclaim_value: 5,
+                min_anonymity_set: 0,
+                max_void_number_export: 8,
+                max_note_page: 16,
+            }
+        );
+    });
+}
+
+/// Tests the storage and events of a mint whose public value no longer matches its proof, which
+/// is only accepted because the `skip-verification` feature bypasses proof verification.
+#[cfg(feature = "skip-verification")]
+#[test]
+fn mint_without_verification_should_update_storage() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let mut mint = <crate::TransferPost as scale_codec::Decode>::decode(
+            &mut &*crate::precomputed_coins::MINT,
+        )
+        .expect("Unable to decode the precomputed mint.");
+        let id = mint.asset_id.expect("Mints have an asset id.");
+        mint.sources[0] += 1;
+        let value = mint.sources[0];
+        let utxo = mint.receiver_posts[0].utxo;
+        MantaPayPallet::init_asset(&1, id, value);
+        assert_ok!(MantaPayPallet::mint(Origin::signed(1), mint));
+        System::assert_last_event(Event::MantaPayPallet(crate::Event::Mint {
+            asset: crate::Asset::new(id, value),
+            source: 1,
+        }));
+        assert!(crate::UtxoSet::<Test>::contains_key(utxo));
+        assert_eq!(crate::TotalUtxoCount::<Test>::get(), 1);
+        assert_eq!(MantaPayPallet::balance(1, id), 0);
+        assert_eq!(MantaPayPallet::total_minted(id), value);
     });
 }
@@ -0,0 +1,331 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    mock::{new_test_ext, MantaPayPallet, Origin, Test},
+    types::{CurrentPath, EncryptedNote, QueryError},
+    Error, ShardTrees, Shards, UtxoSet, Wrap, WrapPair,
+};
+use frame_support::{assert_noop, assert_ok};
+use manta_accounting::{
+    asset,
+    transfer::{ReceiverLedger, SenderLedger, TransferLedger},
+};
+use manta_crypto::merkle_tree::{self, forest::Configuration as _};
+use manta_pay::config;
+use scale_codec::{Decode, Encode};
+
+/// Builds a distinct [`VoidNumber`](config::VoidNumber) from `n`.
+#[inline]
+fn void_number(n: u8) -> config::VoidNumber {
+    let mut bytes = [0; 32];
+    bytes[0] = n;
+    config::VoidNumber::decode(&mut bytes.as_slice()).expect("Small field elements are valid.")
+}
+
+/// Builds a distinct [`Utxo`](config::Utxo) from `n`.
+#[inline]
+pub(super) fn utxo(n: u16) -> config::Utxo {
+    let mut bytes = [0; 32];
+    bytes[..2].copy_from_slice(&n.to_le_bytes());
+    config::Utxo::decode(&mut bytes.as_slice()).expect("Small field elements are valid.")
+}
+
+/// Returns the shard index of `utxo`.
+#[inline]
+fn shard_index(utxo: &config::Utxo) -> u8 {
+    config::MerkleTreeConfiguration::tree_index(utxo)
+}
+
+/// Moves the shard with the given `shard_index` to its last leaf so that no further UTXO fits.
+#[inline]
+fn fill_shard(shard_index: u8) {
+    let capacity =
+        1 << (<config::MerkleTreeConfiguration as merkle_tree::Configuration>::HEIGHT - 1);
+    ShardTrees::<Test>::mutate(shard_index, |tree| {
+        tree.leaf_digest = Some(Default::default());
+        tree.current_path = CurrentPath {
+            leaf_index: capacity - 1,
+            inner_path: vec![Default::default(); CurrentPath::INNER_PATH_LENGTH],
+            ..Default::default()
+        };
+    });
+}
+
+/// Registers `utxos` as the receivers of a single post and returns the encoded tree of the shard
+/// with the given `shard_index` together with the first two UTXOs stored in it.
+#[inline]
+fn register_receivers(utxos: &[config::Utxo], shard_index: u8) -> (Vec<u8>, [config::Utxo; 2]) {
+    new_test_ext().execute_with(|| {
+        let mut ledger = MantaPayPallet::ledger().expect("Parameters should be available.");
+        ledger.register_all(
+            utxos
+                .iter()
+                .map(|utxo| (Wrap::new(*utxo), EncryptedNote::default().into())),
+            &(Wrap::new(()), ()),
+        );
+        assert!(ledger.check_consistency().is_ok());
+        (
+            ShardTrees::<Test>::get(shard_index).encode(),
+            [
+                Shards::<Test>::get(shard_index, 0).0,
+                Shards::<Test>::get(shard_index, 1).0,
+            ],
+        )
+    })
+}
+
+/// Tests that every shard of a fresh ledger is empty.
+#[test]
+fn fresh_shards_should_be_empty() {
+    new_test_ext().execute_with(|| {
+        assert!((0..=u8::MAX).all(MantaPayPallet::shard_is_empty));
+        assert!((0..=u8::MAX).all(|shard_index| MantaPayPallet::latest_utxo(shard_index).is_none()));
+    });
+}
+
+/// Tests that void numbers are unspent on a fresh ledger.
+#[test]
+fn fresh_void_number_should_be_unspent() {
+    new_test_ext().execute_with(|| {
+        let ledger = MantaPayPallet::ledger().expect("Parameters should be available.");
+        assert!(ledger.is_unspent(void_number(1)).is_some());
+    });
+}
+
+/// Tests that a batched spent query answers each void number in order, and that queries over
+/// the bound are rejected.
+#[test]
+fn are_spent_should_report_each_void_number() {
+    new_test_ext().execute_with(|| {
+        crate::VoidNumberSet::<Test>::insert(void_number(2), ());
+        crate::VoidNumberSet::<Test>::insert(void_number(3), ());
+        assert_eq!(
+            MantaPayPallet::are_spent(vec![void_number(1), void_number(2), void_number(4)]),
+            Ok(vec![false, true, false])
+        );
+        assert_eq!(
+            MantaPayPallet::are_spent((1..=4).map(void_number).collect()),
+            Ok(vec![false, true, true, false])
+        );
+        assert_eq!(
+            MantaPayPallet::are_spent((1..=5).map(void_number).collect()),
+            Err(QueryError::TooManyItems { max: 4 })
+        );
+    });
+}
+
+/// Tests that paging through the spent void numbers exports each of them exactly once, in
+/// insertion order, and that oversized pages are clamped to `MaxVoidNumberExport`.
+#[test]
+fn void_number_export_should_resume_from_cursor() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(MantaPayPallet::export_void_numbers(0, 4), (vec![], None));
+        for n in 0..10 {
+            crate::VoidNumberSet::<Test>::insert(void_number(n), ());
+            crate::VoidNumberSetInsertionOrder::<Test>::insert(n as u64, void_number(n));
+        }
+        crate::VoidNumberSetSize::<Test>::put(10);
+        let mut exported = Vec::new();
+        let mut cursor = Some(0);
+        let mut pages = 0;
+        while let Some(next) = cursor {
+            let (page, next) = MantaPayPallet::export_void_numbers(next, 4);
+            assert!(page.len() <= 4);
+            exported.extend(page);
+            cursor = next;
+            pages += 1;
+        }
+        assert_eq!(pages, 3);
+        assert_eq!(exported, (0..10).map(void_number).collect::<Vec<_>>());
+        assert_eq!(
+            MantaPayPallet::export_void_numbers(8, 4),
+            (vec![void_number(8), void_number(9)], None)
+        );
+        assert_eq!(MantaPayPallet::export_void_numbers(12, 4), (vec![], None));
+        assert_eq!(
+            MantaPayPallet::export_void_numbers(0, u32::MAX),
+            ((0..8).map(void_number).collect(), Some(8))
+        );
+    });
+}
+
+/// Tests that a force-inserted void number is seen as spent by the sender ledger.
+#[cfg(feature = "test-helpers")]
+#[test]
+fn force_inserted_void_number_should_be_spent() {
+    new_test_ext().execute_with(|| {
+        MantaPayPallet::force_insert_void_number(void_number(1));
+        let ledger = MantaPayPallet::ledger().expect("Parameters should be available.");
+        assert!(ledger.is_unspent(void_number(1)).is_none());
+        assert!(ledger.is_unspent(void_number(2)).is_some());
+        assert_eq!(crate::VoidNumberSetSize::<crate::mock::Test>::get(), 1);
+    });
+}
+
+/// Tests that debiting an account below zero while posting is reported as an inconsistency
+/// instead of panicking.
+#[test]
+fn balance_underflow_should_be_inconsistent() {
+    new_test_ext().execute_with(|| {
+        let mut ledger = MantaPayPallet::ledger().expect("Parameters should be available.");
+        assert!(ledger.check_consistency().is_ok());
+        ledger.update_public_balances(
+            asset::AssetId(7),
+            vec![WrapPair::new(1, asset::AssetValue(10))],
+            vec![],
+            Wrap::new(()),
+            &(),
+        );
+        assert!(matches!(
+            ledger.check_consistency(),
+            Err(Error::<Test>::LedgerInconsistent)
+        ));
+        assert_eq!(MantaPayPallet::balance(1, 7), 0);
+    });
+}
+
+/// Tests that validity witnesses expose the validated value through [`AsRef`].
+#[test]
+fn witnesses_should_expose_validated_value() {
+    let wrap = Wrap::new(3_u8);
+    assert_eq!(wrap.as_ref(), &3);
+    let pair = WrapPair::new(1_u64, asset::AssetValue(10));
+    assert_eq!(pair.as_ref(), &asset::AssetValue(10));
+    assert_eq!(pair.into_inner(), (1, asset::AssetValue(10)));
+}
+
+/// Tests that receivers of one post which share a shard are inserted in post order, regardless
+/// of the receivers in other shards around them.
+#[test]
+fn same_shard_receivers_should_be_inserted_in_post_order() {
+    let candidates = (0..=256).map(utxo).collect::<Vec<_>>();
+    let (first, second) = candidates
+        .iter()
+        .enumerate()
+        .find_map(|(i, a)| {
+            candidates[i + 1..]
+                .iter()
+                .find(|b| shard_index(a) == shard_index(b))
+                .map(|b| (*a, *b))
+        })
+        .expect("More candidates than shards so two of them must share a shard.");
+    let shard = shard_index(&first);
+    let other = *candidates
+        .iter()
+        .find(|utxo| shard_index(utxo) != shard)
+        .expect("The candidates should span more than one shard.");
+    let (root, stored) = register_receivers(&[first, other, second], shard);
+    assert_eq!(stored, [first, second]);
+    assert_eq!(register_receivers(&[other, first, second], shard).0, root);
+    assert_eq!(register_receivers(&[first, second, other], shard).0, root);
+    assert_ne!(register_receivers(&[second, other, first], shard).0, root);
+}
+
+/// Tests that registering a receiver into a full shard reports an inconsistent ledger instead of
+/// skipping the receiver.
+#[test]
+fn registration_into_full_shard_should_be_inconsistent() {
+    let full = utxo(0);
+    new_test_ext().execute_with(|| {
+        fill_shard(shard_index(&full));
+        let mut ledger = MantaPayPallet::ledger().expect("Parameters should be available.");
+        ledger.register_all(
+            [(Wrap::new(full), EncryptedNote::default().into())],
+            &(Wrap::new(()), ()),
+        );
+        assert!(ledger.check_consistency().is_err());
+        assert!(!UtxoSet::<Test>::contains_key(full));
+    });
+}
+
+/// Tests that verified snapshot imports accept exported ledgers and reject snapshots whose shard
+/// trees or UTXO set do not match their shard entries or whose roots are missing.
+#[test]
+fn verified_snapshot_import_should_reject_inconsistent_roots() {
+    let first = utxo(0);
+    let (second, other) = {
+        let mut candidates = (1..=256).map(utxo);
+        (
+            candidates
+                .clone()
+                .find(|utxo| shard_index(utxo) == shard_index(&first))
+                .expect("The candidates should share a shard with the first UTXO."),
+            candidates
+                .find(|utxo| shard_index(utxo) != shard_index(&first))
+                .expect("The candidates should span more than one shard."),
+        )
+    };
+    let snapshot = new_test_ext().execute_with(|| {
+        let mut ledger = MantaPayPallet::ledger().expect("Parameters should be available.");
+        ledger.register_all(
+            [first, second, other]
+                .into_iter()
+                .map(|utxo| (Wrap::new(utxo), EncryptedNote::default().into())),
+            &(Wrap::new(()), ()),
+        );
+        assert!(ledger.check_consistency().is_ok());
+        MantaPayPallet::export_ledger()
+    });
+    new_test_ext().execute_with(|| {
+        assert_ok!(MantaPayPallet::import_ledger(
+            Origin::root(),
+            snapshot.clone(),
+            true
+        ));
+    });
+    let mut reordered = snapshot.clone();
+    let positions = reordered
+        .shards
+        .iter()
+        .enumerate()
+        .filter(|(_, (shard, _, _, _))| *shard == shard_index(&first))
+        .map(|(position, _)| position)
+        .collect::<Vec<_>>();
+    let (utxo_0, utxo_1) = (
+        reordered.shards[positions[0]].2,
+        reordered.shards[positions[1]].2,
+    );
+    reordered.shards[positions[0]].2 = utxo_1;
+    reordered.shards[positions[1]].2 = utxo_0;
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MantaPayPallet::import_ledger(Origin::root(), reordered.clone(), true),
+            Error::<Test>::SnapshotInconsistent
+        );
+        assert_ok!(MantaPayPallet::import_ledger(
+            Origin::root(),
+            reordered,
+            false
+        ));
+    });
+    let mut mismatched = snapshot.clone();
+    mismatched.utxo_set[0] = utxo(u16::MAX);
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MantaPayPallet::import_ledger(Origin::root(), mismatched, true),
+            Error::<Test>::SnapshotInconsistent
+        );
+    });
+    let mut rootless = snapshot;
+    rootless.utxo_accumulator_outputs.clear();
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            MantaPayPallet::import_ledger(Origin::root(), rootless, true),
+            Error::<Test>::SnapshotInconsistent
+        );
+    });
+}
@@ -14,5 +14,32 @@
 // You should have received a copy of the GNU General Public License
 // along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
 
+/// Returns early from the current test with a warning if the proving contexts could not be
+/// downloaded, so that network failures skip the tests which build proofs.
+///
+/// # Note
+///
+/// Setting the `REQUIRE_PROVING_CONTEXT` environment variable to `1` fails the test instead, so
+/// that CI cannot report success without any proof coverage.
+macro_rules! require_proving_context {
+    () => {
+        if !$crate::test::frame::proving_context_available() {
+            assert_ne!(
+                std::env::var("REQUIRE_PROVING_CONTEXT").as_deref(),
+                Ok("1"),
+                "The proving contexts are unavailable but `REQUIRE_PROVING_CONTEXT` is set."
+            );
+            eprintln!("warning: skipping test since the proving contexts are unavailable.");
+            return;
+        }
+    };
+}
+
+mod account;
+mod asset;
+mod compat;
 mod frame;
+mod ledger;
+mod pull;
 mod storage;
+mod types;
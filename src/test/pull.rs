@@ -0,0 +1,136 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    mock::{new_test_ext, MantaPayPallet, Test},
+    types::{EncryptedNote, NoteCursor, NotePage},
+    Shards,
+};
+
+/// Inserts `count`-many notes into `shard_index`, tagging each ciphertext with `tag` and its
+/// leaf index.
+#[inline]
+fn insert_notes(shard_index: u8, count: u64, tag: u8) {
+    for leaf_index in 0..count {
+        let mut note = EncryptedNote::default();
+        note.ciphertext[0] = tag;
+        note.ciphertext[1] = leaf_index as u8;
+        Shards::<Test>::insert(shard_index, leaf_index, (Default::default(), note));
+    }
+}
+
+/// Returns the `(tag, leaf_index)` pairs of the notes in `page`.
+#[inline]
+fn tags(page: &NotePage) -> Vec<(u8, u8)> {
+    page.entries()
+        .map(|(_, (_, note))| (note.ciphertext[0], note.ciphertext[1]))
+        .collect()
+}
+
+/// Tests that the note cursor advances across shards and reports exhaustion.
+#[test]
+fn pull_notes_cursor_should_advance_across_shards() {
+    new_test_ext().execute_with(|| {
+        insert_notes(1, 3, 1);
+        insert_notes(4, 2, 4);
+        insert_notes(9, 1, 9);
+        let page = MantaPayPallet::pull_notes((0, 5), NoteCursor::default(), 2);
+        assert_eq!(tags(&page), vec![(1, 0), (1, 1)]);
+        assert_eq!(page.next, Some(NoteCursor::new(1, 2)));
+        let page = MantaPayPallet::pull_notes((0, 5), page.next.unwrap(), 2);
+        assert_eq!(tags(&page), vec![(1, 2), (4, 0)]);
+        assert_eq!(page.next, Some(NoteCursor::new(4, 1)));
+        let page = MantaPayPallet::pull_notes((0, 5), page.next.unwrap(), 2);
+        assert_eq!(tags(&page), vec![(4, 1)]);
+        assert_eq!(page.next, None);
+    });
+}
+
+/// Tests that pulling from an exhausted or empty range returns an empty final page.
+#[test]
+fn pull_notes_should_report_exhaustion() {
+    new_test_ext().execute_with(|| {
+        insert_notes(2, 2, 2);
+        let page = MantaPayPallet::pull_notes((0, u8::MAX), NoteCursor::new(2, 2), 10);
+        assert!(page.is_empty());
+        assert_eq!(page.next, None);
+        let page = MantaPayPallet::pull_notes((3, 7), NoteCursor::default(), 10);
+        assert!(page.is_empty());
+        assert_eq!(page.next, None);
+        let page = MantaPayPallet::pull_notes((0, u8::MAX), NoteCursor::default(), 10);
+        assert_eq!(tags(&page), vec![(2, 0), (2, 1)]);
+        assert_eq!(page.next, None);
+    });
+}
+
+/// Tests that oversized pages are clamped to `MaxNotePage` and resume at the first note left out.
+#[test]
+fn pull_notes_should_clamp_limit() {
+    new_test_ext().execute_with(|| {
+        insert_notes(0, 20, 0);
+        let page = MantaPayPallet::pull_notes((0, 0), NoteCursor::default(), u32::MAX);
+        assert_eq!(page.entries().count(), 16);
+        assert_eq!(page.next, Some(NoteCursor::new(0, 16)));
+        let page = MantaPayPallet::pull_notes((0, 0), page.next.unwrap(), u32::MAX);
+        assert_eq!(tags(&page), (16..20).map(|i| (0, i)).collect::<Vec<_>>());
+        assert_eq!(page.next, None);
+    });
+}
+
+/// Tests that a multi-chunk page decodes incrementally to the same notes as a batch decode.
+#[cfg(feature = "client")]
+#[test]
+fn pull_response_reader_should_match_batch_decode() {
+    use crate::client::PullResponseReader;
+    use scale_codec::{Decode, Encode};
+    new_test_ext().execute_with(|| {
+        insert_notes(0, 2, 0);
+        insert_notes(3, 3, 3);
+        insert_notes(7, 1, 7);
+        let page = MantaPayPallet::pull_notes((0, 7), NoteCursor::default(), 5);
+        assert_eq!(page.chunks.len(), 2);
+        let encoded = page.encode();
+        let decoded = NotePage::decode(&mut encoded.as_slice()).expect("Page should decode.");
+        let reader = PullResponseReader::new(encoded.as_slice()).expect("Header should decode.");
+        assert_eq!(reader.next_cursor(), decoded.next);
+        assert_eq!(
+            reader
+                .collect::<Result<Vec<_>, _>>()
+                .expect("Notes should decode."),
+            decoded
+                .entries()
+                .map(|(cursor, entry)| (cursor, entry.clone()))
+                .collect::<Vec<_>>()
+        );
+    });
+}
+
+/// Tests that a truncated page yields a decoding error instead of panicking.
+#[cfg(feature = "client")]
+#[test]
+fn pull_response_reader_should_fail_on_truncated_input() {
+    use crate::client::PullResponseReader;
+    use scale_codec::Encode;
+    new_test_ext().execute_with(|| {
+        insert_notes(5, 2, 5);
+        let encoded = MantaPayPallet::pull_notes((0, 7), NoteCursor::default(), 10).encode();
+        let mut reader =
+            PullResponseReader::new(&encoded[..encoded.len() - 1]).expect("Header should decode.");
+        assert!(matches!(reader.next(), Some(Ok(_))));
+        assert!(matches!(reader.next(), Some(Err(_))));
+        assert!(reader.next().is_none());
+    });
+}
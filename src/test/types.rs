@@ -0,0 +1,385 @@
+// Copyright 2019-2022 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    precomputed_coins::{MINT, PRIVATE_TRANSFER, RECLAIM},
+    types::{
+        checked_sum, classify_shape_error, is_degenerate_proof, shape_requirements, Asset, AssetId,
+        AssetValue, CurrentPath, InnerDigest, PostShape, ShapeError, TransferPost,
+    },
+};
+use manta_accounting::{asset, transfer::canonical::TransferShape};
+use manta_crypto::merkle_tree;
+use manta_pay::config;
+use rand::{Rng, RngCore};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use scale_codec::{Decode, Encode};
+
+/// Number of Inputs per Decoder Fuzzing Strategy
+const DECODE_FUZZ_ROUNDS: usize = 4096;
+
+/// Builds a [`CurrentPath`] with `length`-many inner digests.
+#[inline]
+fn path_with_length(length: usize) -> CurrentPath {
+    CurrentPath {
+        inner_path: vec![InnerDigest::default(); length],
+        ..Default::default()
+    }
+}
+
+/// Tests that sums of asset values are computed exactly and that overflowing sums are rejected.
+#[test]
+fn checked_sum_should_reject_overflow() {
+    assert_eq!(checked_sum(&[]), Some(0));
+    assert_eq!(checked_sum(&[1, 2, 3]), Some(6));
+    assert_eq!(
+        checked_sum(&[AssetValue::MAX - 1, 1]),
+        Some(AssetValue::MAX)
+    );
+    assert_eq!(checked_sum(&[AssetValue::MAX, 1]), None);
+    assert_eq!(checked_sum(&[AssetValue::MAX, 0, AssetValue::MAX]), None);
+}
+
+/// Tests that the inner path length matches the SDK path length.
+#[test]
+fn inner_path_length_should_match_configuration() {
+    assert_eq!(
+        CurrentPath::INNER_PATH_LENGTH,
+        merkle_tree::path_length::<config::MerkleTreeConfiguration>()
+    );
+}
+
+/// Tests that only paths with the configured depth have a valid depth.
+#[test]
+fn wrong_depth_path_should_be_invalid() {
+    assert!(path_with_length(CurrentPath::INNER_PATH_LENGTH).has_valid_depth());
+    for length in [
+        0,
+        CurrentPath::INNER_PATH_LENGTH - 1,
+        CurrentPath::INNER_PATH_LENGTH + 1,
+    ] {
+        assert!(!path_with_length(length).has_valid_depth());
+    }
+}
+
+/// Tests that assets round-trip losslessly through the protocol asset type at the boundaries of
+/// the id and value types.
+#[test]
+fn asset_should_round_trip_through_protocol_asset() {
+    for id in [0, 1, AssetId::MAX] {
+        for value in [0, 1, AssetValue::from(u64::MAX) + 1, AssetValue::MAX] {
+            let asset = Asset::new(id, value);
+            let protocol = asset::Asset::from(asset);
+            assert_eq!(protocol.id, asset::AssetId(id));
+            assert_eq!(protocol.value, asset::AssetValue(value));
+            assert_eq!(Asset::from(protocol), asset);
+        }
+    }
+}
+
+/// Rebuilds `post` from SDK components without setting its proof.
+#[cfg(feature = "client")]
+#[inline]
+fn rebuild(post: &crate::types::TransferPost) -> crate::client::TransferPostBuilder {
+    let mut builder = crate::client::TransferPostBuilder::new();
+    if let Some(asset_id) = post.asset_id {
+        builder = builder.asset_id(asset_id);
+    }
+    for source in &post.sources {
+        builder = builder.source(*source);
+    }
+    for sender in &post.sender_posts {
+        builder = builder.add_sender(sender.clone().into());
+    }
+    for receiver in &post.receiver_posts {
+        builder = builder.add_receiver(receiver.clone().into());
+    }
+    for sink in &post.sinks {
+        builder = builder.sink(*sink);
+    }
+    builder
+}
+
+/// Tests that the transfer post builder reproduces posts of every shape.
+#[cfg(feature = "client")]
+#[test]
+fn transfer_post_builder_should_build_each_shape() {
+    use manta_accounting::transfer::TransferShape;
+    for (encoded, shape) in [
+        (MINT, TransferShape::Mint),
+        (PRIVATE_TRANSFER, TransferShape::PrivateTransfer),
+        (RECLAIM, TransferShape::Reclaim),
+    ] {
+        let post = TransferPost::decode(&mut &*encoded).expect("Unable to decode post.");
+        let built = rebuild(&post)
+            .proof(post.validity_proof.clone())
+            .build()
+            .expect("Rebuilding a valid post should succeed.");
+        assert_eq!(built.shape(), Some(shape));
+        assert_eq!(built, post);
+    }
+}
+
+/// Tests that all-zero and all-identical proofs are degenerate while the precomputed proofs are
+/// not.
+#[test]
+fn degenerate_proofs_should_be_detected() {
+    assert!(is_degenerate_proof(&[0; 192]));
+    assert!(is_degenerate_proof(&[0xff; 192]));
+    assert!(!is_degenerate_proof(&[[0; 96], [1; 96]].concat()));
+    for encoded in [MINT, PRIVATE_TRANSFER, RECLAIM] {
+        let post = TransferPost::decode(&mut &*encoded).expect("Unable to decode post.");
+        assert!(!post.has_degenerate_proof());
+    }
+}
+
+/// Tests that every invalid combination of asset id presence and participant counts is
+/// classified with the count that is wrong, and that the valid shapes are not.
+#[test]
+fn shape_errors_should_be_classified() {
+    assert_eq!(classify_shape_error(true, 1, 0, 1, 0), None);
+    assert_eq!(classify_shape_error(false, 0, 2, 2, 0), None);
+    assert_eq!(classify_shape_error(true, 0, 2, 1, 1), None);
+    for (counts, expected) in [
+        ((false, 1, 0, 1, 0), ShapeError::AssetIdPresenceMismatch),
+        ((true, 0, 2, 2, 0), ShapeError::AssetIdPresenceMismatch),
+        ((false, 0, 2, 1, 1), ShapeError::AssetIdPresenceMismatch),
+        ((true, 0, 0, 1, 0), ShapeError::MissingSource),
+        ((true, 2, 0, 1, 0), ShapeError::TooManySources),
+        ((false, 1, 2, 2, 0), ShapeError::UnexpectedSource),
+        ((true, 1, 2, 1, 1), ShapeError::UnexpectedSource),
+        ((false, 0, 1, 2, 0), ShapeError::MissingSender),
+        ((true, 0, 1, 1, 1), ShapeError::MissingSender),
+        ((false, 0, 3, 2, 0), ShapeError::TooManySenders),
+        ((true, 1, 0, 0, 0), ShapeError::MissingReceiver),
+        ((false, 0, 2, 1, 0), ShapeError::MissingReceiver),
+        ((true, 0, 2, 0, 1), ShapeError::MissingReceiver),
+        ((true, 1, 0, 2, 0), ShapeError::TooManyReceivers),
+        ((false, 0, 2, 3, 0), ShapeError::TooManyReceivers),
+        ((true, 0, 2, 2, 1), ShapeError::TooManyReceivers),
+        ((true, 0, 2, 1, 2), ShapeError::TooManySinks),
+        ((true, 1, 0, 1, 1), ShapeError::UnexpectedSink),
+    ] {
+        let (asset_id_present, sources, senders, receivers, sinks) = counts;
+        assert_eq!(
+            classify_shape_error(asset_id_present, sources, senders, receivers, sinks),
+            Some(expected),
+            "Unexpected classification of {:?}.",
+            counts
+        );
+    }
+    for encoded in [MINT, PRIVATE_TRANSFER, RECLAIM] {
+        let post = TransferPost::decode(&mut &*encoded).expect("Unable to decode post.");
+        assert_eq!(post.shape_error(), None);
+    }
+}
+
+/// Tests that a post whose ephemeral public key bytes are not a curve point is rejected when it
+/// is decoded, before it can reach registration.
+#[test]
+fn corrupted_ephemeral_key_should_not_decode() {
+    let post = TransferPost::decode(&mut &*MINT).expect("Unable to decode post.");
+    let key = post.receiver_posts[0].note.ephemeral_public_key.encode();
+    let mut encoded = post.encode();
+    let offset = encoded
+        .windows(key.len())
+        .position(|window| window == key.as_slice())
+        .expect("The encoded post should contain the ephemeral public key.");
+    encoded[offset..offset + key.len()].fill(0xff);
+    assert!(TransferPost::decode(&mut encoded.as_slice()).is_err());
+}
+
+/// Tests that the shape classifier accepts exactly the counts which [`TransferShape::select`]
+/// accepts, over every combination of small counts.
+#[test]
+fn shape_classification_should_match_selection() {
+    for asset_id_present in [false, true] {
+        for sources in 0..4 {
+            for senders in 0..4 {
+                for receivers in 0..4 {
+                    for sinks in 0..4 {
+                        assert_eq!(
+                            classify_shape_error(
+                                asset_id_present,
+                                sources,
+                                senders,
+                                receivers,
+                                sinks
+                            )
+                            .is_none(),
+                            TransferShape::select(
+                                asset_id_present,
+                                sources,
+                                senders,
+                                receivers,
+                                sinks
+                            )
+                            .is_some(),
+                            "Classification should match selection for {:?}.",
+                            (asset_id_present, sources, senders, receivers, sinks)
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Tests that the shape requirements are accepted by the shape classifier, that changing any of
+/// their counts is rejected, and that the precomputed posts have the dimensions of their shape.
+#[test]
+fn shape_requirements_should_match_accepted_shapes() {
+    let requirements = shape_requirements();
+    assert_eq!(
+        requirements
+            .iter()
+            .map(|(shape, _)| *shape)
+            .collect::<Vec<_>>(),
+        [
+            PostShape::Mint,
+            PostShape::PrivateTransfer,
+            PostShape::Reclaim
+        ]
+    );
+    for (shape, dims) in &requirements {
+        let counts = [dims.sources, dims.senders, dims.receivers, dims.sinks].map(|c| c as usize);
+        assert_eq!(
+            classify_shape_error(dims.asset_id, counts[0], counts[1], counts[2], counts[3]),
+            None,
+            "Requirements of {:?} should be accepted.",
+            shape
+        );
+        assert_eq!(
+            TransferShape::select(dims.asset_id, counts[0], counts[1], counts[2], counts[3])
+                .map(PostShape::from),
+            Some(*shape),
+            "Requirements of {:?} should select the same shape.",
+            shape
+        );
+        assert!(
+            classify_shape_error(!dims.asset_id, counts[0], counts[1], counts[2], counts[3])
+                .is_some()
+        );
+        for i in 0..counts.len() {
+            let mut changed = counts;
+            changed[i] += 1;
+            assert!(
+                classify_shape_error(
+                    dims.asset_id,
+                    changed[0],
+                    changed[1],
+                    changed[2],
+                    changed[3]
+                )
+                .is_some(),
+                "Requirements of {:?} should be exact.",
+                shape
+            );
+        }
+    }
+    for encoded in [MINT, PRIVATE_TRANSFER, RECLAIM] {
+        let post = TransferPost::decode(&mut &*encoded).expect("Unable to decode post.");
+        let description = post.describe();
+        let (_, dims) = requirements
+            .iter()
+            .find(|(shape, _)| Some(*shape) == description.shape)
+            .expect("Precomputed posts should match a shape.");
+        assert_eq!(description.asset_id.is_some(), dims.asset_id);
+        assert_eq!(description.sources.len() as u32, dims.sources);
+        assert_eq!(description.sender_count, dims.senders);
+        assert_eq!(description.receiver_count, dims.receivers);
+        assert_eq!(description.sinks.len() as u32, dims.sinks);
+    }
+}
+
+/// Tests that decoding untrusted bytes as a [`TransferPost`] never panics, feeding the decoder
+/// random bytes, every truncation of the precomputed posts, and precomputed posts with random
+/// bytes overwritten.
+///
+/// # Note
+///
+/// The seed is fixed so that any panic found here can be replayed. This is only a regression
+/// check, the coverage-guided search runs in the `transfer_post_decode` fuzz target.
+#[test]
+fn transfer_post_decode_should_not_panic() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for _ in 0..DECODE_FUZZ_ROUNDS {
+        let mut bytes = vec![0; rng.gen_range(0..2048)];
+        rng.fill_bytes(&mut bytes);
+        let _ = TransferPost::decode(&mut bytes.as_slice());
+    }
+    for post in [MINT, PRIVATE_TRANSFER, RECLAIM] {
+        assert!(TransferPost::decode(&mut &*post).is_ok());
+        for length in 0..post.len() {
+            assert!(TransferPost::decode(&mut &post[..length]).is_err());
+        }
+        for _ in 0..DECODE_FUZZ_ROUNDS {
+            let mut bytes = post.to_vec();
+            for _ in 0..rng.gen_range(1..=4) {
+                let index = rng.gen_range(0..bytes.len());
+                bytes[index] = rng.gen();
+            }
+            let _ = TransferPost::decode(&mut bytes.as_slice());
+        }
+    }
+}
+
+/// Tests that posts round-trip through their base64 encoding and that malformed strings are
+/// rejected.
+#[cfg(feature = "client")]
+#[test]
+fn transfer_post_base64_should_round_trip() {
+    use crate::client::ShareablePostError;
+    for encoded in [MINT, PRIVATE_TRANSFER, RECLAIM] {
+        let post = TransferPost::decode(&mut &*encoded).expect("Unable to decode post.");
+        let shared = post.to_base64();
+        assert_eq!(
+            TransferPost::from_base64(&shared).expect("Unable to decode shared post."),
+            post
+        );
+        assert!(matches!(
+            TransferPost::from_base64(&shared[..shared.len() / 2]),
+            Err(ShareablePostError::Base64(_) | ShareablePostError::Decode(_))
+        ));
+        assert!(matches!(
+            TransferPost::from_base64(&format!("{}AAAA", shared)),
+            Err(ShareablePostError::Base64(_) | ShareablePostError::Decode(_))
+        ));
+    }
+    assert!(matches!(
+        TransferPost::from_base64("not base64!"),
+        Err(ShareablePostError::Base64(_))
+    ));
+}
+
+/// Tests that the transfer post builder rejects posts without a proof or a shape.
+#[cfg(feature = "client")]
+#[test]
+fn transfer_post_builder_should_reject_incomplete_posts() {
+    use crate::client::TransferPostBuilderError;
+    let post = TransferPost::decode(&mut &*MINT).expect("Unable to decode post.");
+    assert_eq!(
+        rebuild(&post).build(),
+        Err(TransferPostBuilderError::MissingProof)
+    );
+    assert_eq!(
+        rebuild(&post)
+            .sink(1)
+            .proof(post.validity_proof.clone())
+            .build(),
+        Err(TransferPostBuilderError::InvalidShape)
+    );
+}